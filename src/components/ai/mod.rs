@@ -1,16 +1,26 @@
 pub mod actions;
 pub mod scorers;
 
-use bevy::prelude::*;
+use bevy::{
+    ecs::{
+        entity::{EntityMapper, MapEntities},
+        reflect::ReflectMapEntities,
+    },
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
 
 /// Tracks things in a specified range
-#[derive(Component, Reflect)]
+#[derive(Component, Reflect, Clone)]
+#[reflect(Component)]
 pub struct InRange {
     /// Radius of the range to find objects
     pub range: f32,
-    /// All allied entities in range
+    /// All allied entities in range. Recalculated every frame, so it is not saved.
+    #[reflect(ignore)]
     pub allies: Vec<Entity>,
-    /// All enemy entities in range
+    /// All enemy entities in range. Recalculated every frame, so it is not saved.
+    #[reflect(ignore)]
     pub enemies: Vec<Entity>,
 }
 
@@ -37,10 +47,19 @@ impl Default for InRange {
     }
 }
 /// Describes a dynamic or static position in the world
-#[derive(Component)]
+#[derive(Component, Reflect, Serialize, Deserialize, Clone, Copy)]
+#[reflect(Component, MapEntities, Serialize, Deserialize)]
 pub enum Waypoint {
     /// Build a waypoint set to the dynamic `translation` of the specified `Entity`
     Entity(Entity),
     /// Build a waypoint set to a static `Vec2` position
     Position(Vec2),
 }
+
+impl MapEntities for Waypoint {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        if let Waypoint::Entity(entity) = self {
+            *entity = entity_mapper.map_entity(*entity);
+        }
+    }
+}
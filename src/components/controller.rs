@@ -1,6 +1,8 @@
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use crate::prelude::*;
+
 /// Thruster control
 #[derive(Component, Default, Reflect, Clone, Copy, Serialize, Deserialize)]
 #[reflect(Component, Serialize, Deserialize)]
@@ -14,4 +16,48 @@ pub struct Controller {
     /// Braking force as a normalized value
     #[serde(skip)]
     pub brake: f32,
+    /// Whether the afterburners of the [`Engine`]s should burn
+    #[serde(skip)]
+    pub afterburner: bool,
+}
+
+/// Movement stats of a [`Craft`] after its [`Engine`]s and the mass of its cargo and equipment
+#[derive(Component, Default, Reflect, Clone, Copy, Debug, PartialEq)]
+pub struct Handling {
+    /// Top speed
+    pub speed: f32,
+    /// Top rotational speed
+    pub rotation: f32,
+    /// Braking force
+    pub brake: f32,
+    /// Acceleration at full thrust
+    pub acceleration: f32,
+}
+
+impl Handling {
+    /// Handling of a craft with a total mass and equipped engines. Each engine comes with the
+    /// fraction of its afterburner that is burning.
+    pub fn new<'a>(
+        craft: &Craft,
+        mass: f32,
+        engines: impl IntoIterator<Item = (&'a Engine, f32)>,
+    ) -> Self {
+        let (mut thrust, mut turn, mut top_speed) = (1f32, 1f32, 1f32);
+        for (engine, burn) in engines {
+            thrust += engine.thrust;
+            turn += engine.turn;
+            top_speed += engine.top_speed;
+            if let Some(afterburner) = engine.afterburner {
+                thrust += afterburner.thrust * burn;
+                top_speed += afterburner.top_speed * burn;
+            }
+        }
+        Self {
+            speed: craft.speed * top_speed.max(0f32),
+            rotation: craft.rotation * turn.max(0f32),
+            brake: craft.brake,
+            // Thrust is a force, so heavier crafts accelerate slower
+            acceleration: craft.acceleration * thrust.max(0f32) / mass.max(f32::EPSILON),
+        }
+    }
 }
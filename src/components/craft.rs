@@ -1,5 +1,5 @@
 use avian3d::prelude::*;
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::HashMap};
 use serde::{Deserialize, Serialize};
 
 use crate::prelude::*;
@@ -8,6 +8,14 @@ use crate::prelude::*;
 #[reflect(Component, Serialize, Deserialize)]
 pub struct Destroyed;
 
+/// The remains of a destroyed craft. Its cargo can be salvaged until it decays.
+#[derive(Component, Reflect, Serialize, Deserialize, Clone, Copy)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct Wreck {
+    /// Seconds until the wreck decays and is removed
+    pub remaining: f32,
+}
+
 /// Total alotted hitpoints
 #[derive(Component, Reflect, Serialize, Deserialize, Clone, Copy)]
 #[reflect(Component, Serialize, Deserialize)]
@@ -41,9 +49,6 @@ impl Health {
 #[reflect(Component, Serialize, Deserialize)]
 pub struct Damage(f32);
 
-// #[derive(Component, Reflect, Deref, DerefMut, Default)]
-// pub struct ShieldStore(f32);
-
 // #[derive(Component, Reflect, Deref, DerefMut, Default)]
 // pub struct EnergyStore(f32);
 
@@ -53,6 +58,60 @@ impl From<f32> for Damage {
     }
 }
 
+/// Kind of damage inflicted by a weapon. [`Armor`] can resist each type differently.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Reflect, Serialize, Deserialize)]
+pub enum DamageType {
+    /// Bullets and debris
+    #[default]
+    Kinetic,
+    /// Lasers and ions
+    Energy,
+    /// Fire and plasma
+    Thermal,
+    /// Explosions
+    Explosive,
+}
+
+impl std::fmt::Display for DamageType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            DamageType::Kinetic => "kinetic",
+            DamageType::Energy => "energy",
+            DamageType::Thermal => "thermal",
+            DamageType::Explosive => "explosive",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Fraction of each [`DamageType`] that is ignored, where `0.25` ignores a quarter of the damage
+#[derive(Debug, Default, Clone, PartialEq, Reflect, Serialize, Deserialize, Deref, DerefMut)]
+#[serde(transparent)]
+pub struct Resistances(HashMap<DamageType, f32>);
+
+impl Resistances {
+    /// Most damage that can be ignored, so nothing is invulnerable
+    pub const MAX: f32 = 0.9;
+
+    /// Resistance to a type of damage
+    pub fn get(&self, damage_type: DamageType) -> f32 {
+        self.0.get(&damage_type).copied().unwrap_or_default()
+    }
+
+    /// Damage left after several resistances are added together
+    pub fn resist<'a>(
+        amount: f32,
+        damage_type: DamageType,
+        resistances: impl IntoIterator<Item = &'a Resistances>,
+    ) -> f32 {
+        let resistance = resistances
+            .into_iter()
+            .map(|resistances| resistances.get(damage_type))
+            .sum::<f32>();
+        amount * (1f32 - resistance.clamp(0f32, Self::MAX))
+    }
+}
+
 /// Moveable thing
 #[derive(Debug, Reflect, Clone, Component, Asset, Serialize, Deserialize)]
 #[reflect(Component, Serialize, Deserialize)]
@@ -65,8 +124,13 @@ pub struct Craft {
     pub rotation: f32,
     /// Braking force
     pub brake: f32,
-    /// Acceleration
+    /// Thrust force. The acceleration of the craft is this divided by its mass, including its cargo
+    /// and equipment.
     pub acceleration: f32,
+    /// [`Energy`] drawn per second at full thrust. Engines that are starved of energy thrust with
+    /// only the fraction they were given.
+    #[serde(default)]
+    pub thrust_energy: f32,
     /// Base health
     pub health: usize,
     /// Hitbox size
@@ -75,8 +139,48 @@ pub struct Craft {
     pub mass: f32,
     /// Inventory capacity
     pub capacity: usize,
+    /// Number of inventory slots, or `None` for an inventory limited only by capacity
+    #[serde(default)]
+    pub slots: Option<usize>,
     /// Craft value
     pub value: usize,
+    /// Positions that equipment is mounted at. Equipment without a free hardpoint is mounted at
+    /// the center of the craft.
+    #[serde(default)]
+    pub hardpoints: Vec<Hardpoint>,
+    /// Explosion when the craft is destroyed, if any
+    #[serde(default)]
+    pub explosion: Option<Explosive>,
+}
+
+/// A named position on a [`Craft`] where equipment is mounted. Weapons fire from their hardpoint
+/// and can only turn within its arc.
+#[derive(Debug, Reflect, Clone, Component, Serialize, Deserialize, PartialEq)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct Hardpoint {
+    /// Name
+    pub name: String,
+    /// Position relative to the center of the craft, where `x` is to the right and `y` is forward
+    pub offset: (f32, f32),
+    /// Direction the hardpoint faces in radians, counter-clockwise from forward
+    #[serde(default)]
+    pub facing: f32,
+    /// Type of equipment that can be mounted here
+    pub equipment: EquipmentTypeId,
+    /// Maximum (absolute) angle in radians a weapon can turn away from `facing`, where PI is full
+    /// sweeping coverage. Weapons on a hardpoint without an arc turn within their own tracking.
+    #[serde(default)]
+    pub arc: Option<f32>,
+}
+
+impl Hardpoint {
+    /// Transform of mounted equipment relative to the craft
+    pub fn transform(&self) -> Transform {
+        let (x, y) = self.offset;
+        // Craft children use -Z as forward and Y as up
+        Transform::from_translation(Vec3::new(x, 0f32, -y))
+            .with_rotation(Quat::from_axis_angle(Vec3::Y, self.facing))
+    }
 }
 
 #[derive(Debug, Reflect, Clone, Component, Asset, Serialize, Deserialize)]
@@ -88,6 +192,8 @@ pub struct CraftName(pub String);
 pub struct CraftBundle {
     /// Starting [`Energy`]
     pub energy: Energy,
+    /// How the [`Energy`] is shared between systems
+    pub power: PowerDistribution,
     /// A [`Controller`] allows this craft to move
     pub controller: Controller,
     /// Always will be set to `Dynamic`
@@ -124,6 +230,7 @@ impl Default for CraftBundle {
     fn default() -> Self {
         Self {
             energy: Energy::default(),
+            power: PowerDistribution::default(),
             controller: Controller::default(),
             rigid_body: RigidBody::Dynamic,
             collider: Collider::sphere(0.5f32),
@@ -133,12 +240,16 @@ impl Default for CraftBundle {
                 rotation: 400f32,
                 brake: 200f32,
                 acceleration: 40f32,
+                thrust_energy: 0f32,
                 health: 64,
                 size: 1f32,
                 mass: 100f32,
                 capacity: 100,
+                slots: None,
                 name: "craft".to_string(),
                 value: 1000,
+                hardpoints: Vec::new(),
+                explosion: None,
             },
             locked_axes: LockedAxes::default().lock_translation_z(),
             // .lock_rotation_y() // TODO: Avian bug?
@@ -149,7 +260,7 @@ impl Default for CraftBundle {
             equipped: EquippedBuilder::default(),
             collision_layers: CollisionLayers {
                 memberships: LayerMask::from([PhysicsCategory::Craft]),
-                filters: LayerMask::from([PhysicsCategory::Weapon]),
+                filters: LayerMask::from([PhysicsCategory::Weapon, PhysicsCategory::Missile]),
             },
             credits: default(),
             transform: Transform::default(),
@@ -28,7 +28,7 @@ impl Credits {
 }
 
 /// Errors for `Credits` transations
-#[derive(Error, Debug)]
+#[derive(Error, Debug, PartialEq, Eq)]
 pub enum CreditsError {
     /// Insufficient credits
     #[error("insufficient credits")]
@@ -0,0 +1,64 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Shape of an explosion
+#[derive(Debug, Clone, Copy, Default, PartialEq, Reflect, Serialize, Deserialize)]
+pub struct Blast {
+    /// Distance the explosion reaches
+    pub radius: f32,
+    /// Fraction of the damage and knockback lost at the edge of the radius, where 0 is the same
+    /// everywhere and 1 is nothing at the edge
+    #[serde(default)]
+    pub falloff: f32,
+    /// Impulse applied to things at the center of the explosion, pushing them away
+    #[serde(default)]
+    pub knockback: f32,
+}
+
+impl Blast {
+    /// Fraction of the full damage and knockback at a distance from the center
+    pub fn scale(&self, distance: f32) -> f32 {
+        if distance > self.radius {
+            return 0f32;
+        }
+        let edge = if self.radius > 0f32 {
+            distance / self.radius
+        } else {
+            0f32
+        };
+        (1f32 - self.falloff * edge).clamp(0f32, 1f32)
+    }
+}
+
+/// Explodes when [`Destroyed`](crate::prelude::Destroyed), harming everything nearby
+#[derive(Component, Debug, Clone, Copy, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct Explosive {
+    /// Damage at the center of the explosion
+    pub damage: f32,
+    /// Shape of the explosion
+    pub blast: Blast,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blast_falloff() {
+        let blast = Blast {
+            radius: 4f32,
+            falloff: 0.5,
+            knockback: 0f32,
+        };
+        assert_eq!(blast.scale(0f32), 1f32);
+        assert_eq!(blast.scale(2f32), 0.75);
+        assert_eq!(blast.scale(4f32), 0.5);
+        assert_eq!(blast.scale(5f32), 0f32);
+
+        // Explosions without a radius only harm what is at their center
+        let point = Blast::default();
+        assert_eq!(point.scale(0f32), 1f32);
+        assert_eq!(point.scale(0.1), 0f32);
+    }
+}
@@ -24,6 +24,11 @@ impl Faction {
         Self(rand::thread_rng().next_u32())
     }
 
+    /// Create a faction ID derived from its name, so that it is the same in every game
+    pub fn from_name(name: &str) -> Faction {
+        Self(crate::util::stable_hash(name.as_bytes()) as u32)
+    }
+
     /// Create a new faction without any explicitly assigned faction. It may, however, still resolve.
     pub fn none() -> Faction {
         Self::default()
@@ -67,6 +72,15 @@ impl FactionSet {
     }
 }
 
+impl FromIterator<Faction> for FactionSet {
+    fn from_iter<T: IntoIterator<Item = Faction>>(iter: T) -> Self {
+        FactionSet {
+            data: iter.into_iter().collect(),
+            all: false,
+        }
+    }
+}
+
 impl<const N: usize> From<[Faction; N]> for FactionSet {
     fn from(value: [Faction; N]) -> Self {
         FactionSet {
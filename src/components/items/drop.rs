@@ -64,3 +64,31 @@ pub struct DropRate {
     /// 1 in X chance to drop. 1 will always drop.
     pub d: usize,
 }
+
+impl DropRate {
+    /// 1 in X chance to drop an item of some rarity. Rarer items are less likely to drop.
+    pub fn odds(&self, rarity: Rarity) -> usize {
+        (self.d * rarity.scarcity()).max(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drop_rate_odds() {
+        let rate = DropRate {
+            min: 1,
+            max: 2,
+            d: 3,
+        };
+        assert_eq!(rate.odds(Rarity::Common), 3);
+        assert_eq!(rate.odds(Rarity::Legendary), 24);
+        // Guaranteed drops of common items stay guaranteed, and nothing is less than certain
+        let always = DropRate { d: 1, ..rate };
+        assert_eq!(always.odds(Rarity::Common), 1);
+        assert_eq!(always.odds(Rarity::Uncommon), 2);
+        assert_eq!(DropRate { d: 0, ..rate }.odds(Rarity::Rare), 1);
+    }
+}
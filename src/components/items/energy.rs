@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
@@ -8,6 +10,9 @@ use crate::error::EnergyError;
 pub struct Generator {
     /// How much [`Energy`] is restored per second
     pub recharge_rate: f32,
+    /// Heat added per second while recharging, where 1 is overheated
+    #[serde(default)]
+    pub heat_per_second: f32,
 }
 
 /// Item that contributes to the maximum storable [`Energy`] charge
@@ -79,3 +84,260 @@ impl From<f32> for Energy {
         Self(value)
     }
 }
+
+/// Systems of a craft that draw [`Energy`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Reflect, Serialize, Deserialize)]
+pub enum PowerSystem {
+    /// Thrust of the craft
+    Engines,
+    /// Recharging shields
+    Shields,
+    /// Firing weapons
+    Weapons,
+}
+
+impl PowerSystem {
+    /// Every system. Ties between systems with as many pips are broken in this order when pips
+    /// are moved between them.
+    pub const ALL: [PowerSystem; 3] = [
+        PowerSystem::Engines,
+        PowerSystem::Shields,
+        PowerSystem::Weapons,
+    ];
+}
+
+impl std::fmt::Display for PowerSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            PowerSystem::Engines => "engines",
+            PowerSystem::Shields => "shields",
+            PowerSystem::Weapons => "weapons",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// How the [`Energy`] of a craft is shared between its [`PowerSystem`]s. Each system is allocated
+/// pips, and systems with more pips are served first: a share of the energy capacity is kept in
+/// reserve for them that systems with fewer pips cannot draw from. Systems with as many pips as
+/// each other draw from the same energy, so an even distribution keeps nothing in reserve and
+/// every system can use all of the capacity. Systems that cannot get all the energy they ask for
+/// are starved, and make do with what is left for them.
+#[derive(Clone, Debug, Component, Reflect, Serialize, Deserialize, PartialEq)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct PowerDistribution {
+    /// Pips allocated to each system, in the order of [`PowerSystem::ALL`]
+    pips: [u8; 3],
+    /// Maximum [`Energy`] of the craft, as last counted from its batteries
+    #[serde(skip)]
+    capacity: f32,
+    /// The timestamp each system was last starved
+    #[serde(skip)]
+    starved: [Option<Duration>; 3],
+}
+
+impl Default for PowerDistribution {
+    fn default() -> Self {
+        Self {
+            pips: [2; 3],
+            capacity: 0f32,
+            starved: [None; 3],
+        }
+    }
+}
+
+impl PowerDistribution {
+    /// Pips shared between every system
+    pub const TOTAL_PIPS: u8 = 6;
+    /// Most pips a single system can have
+    pub const MAX_PIPS: u8 = 4;
+    /// How long a system is shown as starved after it last was
+    pub const STARVED_DURATION: Duration = Duration::from_millis(500);
+
+    /// Pips allocated to a system
+    pub fn pips(&self, system: PowerSystem) -> u8 {
+        self.pips[system as usize]
+    }
+
+    /// Move a pip to a system from the system with the most pips, if it has room for another
+    pub fn increase(&mut self, system: PowerSystem) {
+        if self.pips(system) >= Self::MAX_PIPS {
+            return;
+        }
+        // Ties are taken from the system that is served last
+        let donor = PowerSystem::ALL
+            .into_iter()
+            .filter(|other| *other != system && self.pips(*other) > 0)
+            .max_by_key(|other| (self.pips(*other), *other as usize));
+        if let Some(donor) = donor {
+            self.pips[donor as usize] -= 1;
+            self.pips[system as usize] += 1;
+        }
+    }
+
+    /// Share the pips evenly between every system
+    pub fn balance(&mut self) {
+        self.pips = Self::default().pips;
+    }
+
+    /// Set the maximum [`Energy`] of the craft, which the reserves are a share of
+    pub fn set_capacity(&mut self, capacity: f32) {
+        self.capacity = capacity;
+    }
+
+    /// Whether a system is served before another
+    fn outranks(&self, system: PowerSystem, other: PowerSystem) -> bool {
+        self.pips(system) > self.pips(other)
+    }
+
+    /// Energy kept in reserve for the systems that are served before this one
+    pub fn reserve(&self, system: PowerSystem) -> f32 {
+        let pips = PowerSystem::ALL
+            .into_iter()
+            .filter(|other| self.outranks(*other, system))
+            .map(|other| self.pips(other))
+            .sum::<u8>();
+        self.capacity * pips as f32 / Self::TOTAL_PIPS as f32
+    }
+
+    /// Energy a system can draw
+    pub fn available(&self, system: PowerSystem, energy: &Energy) -> f32 {
+        (energy.charge() - self.reserve(system)).max(0f32)
+    }
+
+    /// Draw as much of an amount of energy as is available to a system, returning the energy drawn
+    pub fn draw(
+        &mut self,
+        energy: &mut Energy,
+        system: PowerSystem,
+        amount: f32,
+        now: Duration,
+    ) -> f32 {
+        let drawn = amount.min(self.available(system, energy)).max(0f32);
+        if drawn < amount {
+            self.starve(system, now);
+        }
+        energy.0 -= drawn;
+        drawn
+    }
+
+    /// Draw all of an amount of energy for a system. Errors, drawing nothing, if not enough is
+    /// available to it.
+    pub fn try_draw(
+        &mut self,
+        energy: &mut Energy,
+        system: PowerSystem,
+        amount: f32,
+        now: Duration,
+    ) -> Result<(), EnergyError> {
+        let available = self.available(system, energy);
+        if amount > available {
+            self.starve(system, now);
+            return Err(EnergyError::InsufficientCharge {
+                requested: amount,
+                actual: available,
+            });
+        }
+        energy.0 -= amount;
+        Ok(())
+    }
+
+    /// Mark a system as starved
+    pub fn starve(&mut self, system: PowerSystem, now: Duration) {
+        self.starved[system as usize] = Some(now);
+    }
+
+    /// Whether a system was recently starved
+    pub fn is_starved(&self, system: PowerSystem, now: Duration) -> bool {
+        self.starved[system as usize].is_some_and(|starved| now < starved + Self::STARVED_DURATION)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_power_distribution() {
+        let mut power = PowerDistribution::default();
+        power.set_capacity(30f32);
+        let mut energy = Energy::from(30f32);
+
+        // Evenly shared systems keep nothing in reserve from each other
+        for system in PowerSystem::ALL {
+            assert_eq!(power.reserve(system), 0f32);
+        }
+
+        // Pips are taken from the system with the most, and the last served on ties
+        power.increase(PowerSystem::Weapons);
+        assert_eq!(power.pips(PowerSystem::Shields), 1);
+        power.increase(PowerSystem::Weapons);
+        power.increase(PowerSystem::Weapons);
+        assert_eq!(
+            power.pips(PowerSystem::Weapons),
+            PowerDistribution::MAX_PIPS
+        );
+        assert_eq!(power.pips(PowerSystem::Engines), 1);
+        assert_eq!(power.pips(PowerSystem::Shields), 1);
+        assert_eq!(power.reserve(PowerSystem::Weapons), 0f32);
+        assert_eq!(power.reserve(PowerSystem::Shields), 20f32);
+        assert_eq!(power.reserve(PowerSystem::Engines), 20f32);
+
+        // Starved systems get what is left for them
+        let now = Duration::from_secs(1);
+        assert_eq!(
+            power.draw(&mut energy, PowerSystem::Shields, 15f32, now),
+            10f32
+        );
+        assert!(power.is_starved(PowerSystem::Shields, now));
+        assert!(power
+            .try_draw(&mut energy, PowerSystem::Engines, 1f32, now)
+            .is_err());
+        assert_eq!(energy.charge(), 20f32);
+        assert!(power
+            .try_draw(&mut energy, PowerSystem::Weapons, 20f32, now)
+            .is_ok());
+        assert!(!power.is_starved(PowerSystem::Weapons, now));
+        assert!(!power.is_starved(PowerSystem::Shields, now + Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn test_single_battery_fires_weapons() {
+        use crate::prelude::*;
+
+        let item = |ron: &str| ron::from_str::<Item>(ron).unwrap().equipment;
+        let Some(EquipmentType::Battery(battery)) =
+            item(include_str!("../../../assets/items/ion.battery.ron"))
+        else {
+            panic!("ion pack should be a battery");
+        };
+
+        // A craft with its charge full and pips untouched, like every AI controlled craft
+        let mut power = PowerDistribution::default();
+        power.set_capacity(battery.capacity());
+        for weapon in [
+            include_str!("../../../assets/items/shotgun.weapon.ron"),
+            include_str!("../../../assets/items/dart_2.weapon.ron"),
+            include_str!("../../../assets/items/hornet.weapon.ron"),
+        ] {
+            let Some(EquipmentType::Weapon(Weapon {
+                weapon_type:
+                    WeaponType::ProjectileWeapon { energy, .. }
+                    | WeaponType::MissileWeapon { energy, .. },
+                ..
+            })) = item(weapon)
+            else {
+                panic!("weapon should fire shots");
+            };
+            let mut charge = Energy::from(battery.capacity());
+            assert!(power
+                .try_draw(
+                    &mut charge,
+                    PowerSystem::Weapons,
+                    energy as f32,
+                    Duration::ZERO
+                )
+                .is_ok());
+        }
+    }
+}
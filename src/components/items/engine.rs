@@ -0,0 +1,36 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+/// Changes how its parent [`Craft`] handles. The modifiers of every equipped engine are added
+/// together, so two engines with `thrust: 0.1` give +20% thrust.
+#[derive(Clone, Debug, Default, Component, Reflect, Serialize, Deserialize, PartialEq)]
+pub struct Engine {
+    /// Fraction added to the thrust of the craft, where `0.1` is +10%
+    #[serde(default)]
+    pub thrust: f32,
+    /// Fraction added to the turn rate of the craft
+    #[serde(default)]
+    pub turn: f32,
+    /// Fraction added to the top speed of the craft
+    #[serde(default)]
+    pub top_speed: f32,
+    /// Boost used on demand while thrusting, if the engine has one
+    #[serde(default)]
+    pub afterburner: Option<Afterburner>,
+}
+
+/// Extra thrust and top speed for an [`Engine`], which draws [`Energy`] and adds [`Heat`] while it
+/// burns
+#[derive(Clone, Copy, Debug, Default, Reflect, Serialize, Deserialize, PartialEq)]
+pub struct Afterburner {
+    /// Fraction added to the thrust of the craft while burning
+    pub thrust: f32,
+    /// Fraction added to the top speed of the craft while burning
+    pub top_speed: f32,
+    /// [`Energy`] drawn per second while burning
+    pub energy_per_second: f32,
+    /// Heat added per second while burning, where 1 is overheated
+    pub heat_per_second: f32,
+}
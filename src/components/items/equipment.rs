@@ -24,6 +24,8 @@ pub struct Equipped {
     pub equipped: HashMap<EquipmentTypeId, HashSet<Entity>>,
     /// Defines the shape of what can be `equipped`, where `usize` is the total maximum equippable of that type.
     pub slots: HashMap<EquipmentTypeId, usize>,
+    /// A map of indices into the [`Craft`] hardpoints to the equipped entity mounted there
+    pub hardpoints: HashMap<usize, Entity>,
     // pub inventory: Inventory,
 }
 
@@ -62,6 +64,21 @@ impl Equipped {
             })
     }
 
+    /// Index of the first hardpoint that can mount a type of equipment and has nothing mounted
+    pub fn free_hardpoint(
+        &self,
+        hardpoints: &[Hardpoint],
+        equipment_type: &EquipmentTypeId,
+    ) -> Option<usize> {
+        hardpoints
+            .iter()
+            .enumerate()
+            .find(|(i, hardpoint)| {
+                hardpoint.equipment == *equipment_type && !self.hardpoints.contains_key(i)
+            })
+            .map(|(i, _)| i)
+    }
+
     pub fn get_by_type(
         &self,
         equipment_type_id: EquipmentTypeId,
@@ -82,6 +99,9 @@ impl Equipped {
 pub struct EquippedBuilder {
     /// Names of items to equip
     pub equipped: Vec<String>,
+    /// Metadata of the items to equip, in the same order. Items without metadata use the default.
+    #[serde(default)]
+    pub metadata: Vec<ItemMetadata>,
     /// Slot shape definitions
     pub slots: Vec<(EquipmentTypeId, usize)>,
 }
@@ -99,6 +119,12 @@ pub enum EquipmentType {
     Battery(Battery),
     /// Increases maximum [`Health`]
     Armor(Armor),
+    /// Absorbs damage with [`Energy`]
+    Shield(Shield),
+    /// Cools other equipment
+    Radiator(Radiator),
+    /// Changes how the craft handles
+    Engine(Engine),
 }
 
 /// Defines an `EquipmentType` without associated information. This should be kept in sync with `EquipmentType`.
@@ -116,6 +142,12 @@ pub enum EquipmentTypeId {
     Battery,
     /// Increases maximum [`Health`]
     Armor,
+    /// Absorbs damage with [`Energy`]
+    Shield,
+    /// Cools other equipment
+    Radiator,
+    /// Changes how the craft handles
+    Engine,
 }
 
 impl std::fmt::Display for EquipmentTypeId {
@@ -132,6 +164,9 @@ impl From<&EquipmentType> for EquipmentTypeId {
             EquipmentType::Generator(_) => Self::Generator,
             EquipmentType::Battery(_) => Self::Battery,
             EquipmentType::Armor(_) => Self::Armor,
+            EquipmentType::Shield(_) => Self::Shield,
+            EquipmentType::Radiator(_) => Self::Radiator,
+            EquipmentType::Engine(_) => Self::Engine,
         }
     }
 }
@@ -143,10 +178,11 @@ impl EquipmentType {
     }
 }
 
-/// Wrapper component for a handle that is guarenteed to be an equipment item.
-/// Inserting these as a child with [`Equipped`] will trigger equipment management.
+/// Wrapper component for a handle that is guarenteed to be an equipment item, along with the
+/// metadata of that particular item. Inserting these as a child with [`Equipped`] will trigger
+/// equipment management.
 #[derive(Debug, Reflect, Clone)]
-pub struct Equipment(Handle<Item>);
+pub struct Equipment(Handle<Item>, ItemMetadata);
 
 impl Equipment {
     /// Obtain the handle of this [`Item`]
@@ -154,9 +190,25 @@ impl Equipment {
         self.0.clone()
     }
 
+    /// Metadata of the item, such as its modifiers
+    pub fn metadata(&self) -> &ItemMetadata {
+        &self.1
+    }
+
     /// Crate a new [`Equipment`] from an existing [`Item`] handle
     pub fn new(item_handle: Handle<Item>) -> Self {
-        Self(item_handle)
+        Self(item_handle, default())
+    }
+
+    /// Mutably borrow the metadata of the item
+    pub fn metadata_mut(&mut self) -> &mut ItemMetadata {
+        &mut self.1
+    }
+
+    /// Set the metadata of the item
+    pub fn with_metadata(mut self, metadata: ItemMetadata) -> Self {
+        self.1 = metadata;
+        self
     }
 }
 
@@ -173,13 +225,22 @@ impl Component for Equipment {
             ) {
                 let items = world.get_resource::<Assets<Item>>().unwrap();
                 let retrieved_item = items.get(&equipment.0).unwrap().clone();
+                let hardpoints = world
+                    .get::<Craft>(parent)
+                    .map(|craft| craft.hardpoints.clone())
+                    .unwrap_or_default();
 
                 if let Some(mut equipped) = world.get_mut::<Equipped>(parent) {
                     // We have all necessary variables!
                     // NOTE: We assume that there is a slot available in equipment at this point - it should
                     // be checked before adding.
                     if add {
-                        let equipment_type = retrieved_item.equipment.clone().unwrap();
+                        // Modifiers of this particular item apply on top of the base stats
+                        let equipment_type = retrieved_item
+                            .equipment
+                            .clone()
+                            .unwrap()
+                            .with_modifiers(&equipment.1.modifiers);
 
                         // Register or add the entity in the equipped
                         let id = equipment_type.id();
@@ -195,6 +256,12 @@ impl Component for Equipment {
                             .unwrap()
                             .insert(entity);
 
+                        // Mount the equipment on a free hardpoint, or the center of the craft
+                        let hardpoint = equipped.free_hardpoint(&hardpoints, &id).map(|index| {
+                            equipped.hardpoints.insert(index, entity);
+                            hardpoints[index].clone()
+                        });
+
                         // Add a few more components to the entity that will let it function as equipment
                         // The actual equipment (Like [`Weapon`]), and the overall [`Item`]. There is some redundancy -
                         // this can be refactored somewhat
@@ -205,18 +272,35 @@ impl Component for Equipment {
                         let item_name = retrieved_item.name.clone();
                         entity.insert((
                             retrieved_item,
-                            TransformBundle::default(),
-                            Heat::default(),
+                            TransformBundle::from_transform(
+                                hardpoint
+                                    .as_ref()
+                                    .map(Hardpoint::transform)
+                                    .unwrap_or_default(),
+                            ),
+                            Heat::from(equipment.1.heat.unwrap_or_default()),
                             Name::new(item_name),
                             equipment.0,
                         ));
+                        if let Some(hardpoint) = hardpoint {
+                            entity.insert(hardpoint);
+                        }
 
                         match equipment_type {
-                            EquipmentType::Weapon(weapon) => entity.insert(weapon.clone()),
+                            EquipmentType::Weapon(mut weapon) => {
+                                // Fire settings chosen by the player replace those of the item
+                                if let Some(fire_control) = equipment.1.fire_control {
+                                    weapon.fire_control = fire_control;
+                                }
+                                entity.insert(weapon)
+                            }
                             EquipmentType::RepairBot(repair) => entity.insert(repair.clone()),
                             EquipmentType::Generator(generator) => entity.insert(generator.clone()),
                             EquipmentType::Battery(battery) => entity.insert(battery.clone()),
                             EquipmentType::Armor(armor) => entity.insert(armor.clone()),
+                            EquipmentType::Shield(shield) => entity.insert(shield.clone()),
+                            EquipmentType::Radiator(radiator) => entity.insert(radiator.clone()),
+                            EquipmentType::Engine(engine) => entity.insert(engine.clone()),
                         };
                     } else {
                         let equipment_type = retrieved_item.equipment.unwrap();
@@ -227,11 +311,12 @@ impl Component for Equipment {
                             .get_mut(&equipment_type.id())
                             .unwrap()
                             .remove(&entity);
+                        equipped.hardpoints.retain(|_, mounted| *mounted != entity);
 
                         let mut cmd = world.commands();
                         let mut entity = cmd.entity(entity);
 
-                        entity.remove::<Item>();
+                        entity.remove::<(Item, Hardpoint)>();
 
                         match equipment_type {
                             EquipmentType::Weapon(_) => entity.remove::<Weapon>(),
@@ -239,6 +324,9 @@ impl Component for Equipment {
                             EquipmentType::Generator(_) => entity.remove::<Generator>(),
                             EquipmentType::Battery(_) => entity.remove::<Battery>(),
                             EquipmentType::Armor(_) => entity.remove::<Armor>(),
+                            EquipmentType::Shield(_) => entity.remove::<Shield>(),
+                            EquipmentType::Radiator(_) => entity.remove::<Radiator>(),
+                            EquipmentType::Engine(_) => entity.remove::<Engine>(),
                         };
                     }
                 }
@@ -255,11 +343,17 @@ impl Component for Equipment {
 }
 
 /// Attached to certain equipment. When overheated, equipment is disabled until fully cooled.
-/// Heat is not passively cooled and will continue to stay at its current score unless modified.
-#[derive(Default, Component, Reflect)]
+/// Hot equipment always cools at [`Heat::PASSIVE_COOLING`], and faster through its own cooling,
+/// such as a laser, or the [`Radiator`]s of its parent.
+#[derive(Default, Clone, Copy, Debug, Component, Reflect, Serialize, Deserialize)]
+#[reflect(Component, Serialize, Deserialize)]
 pub struct Heat(f32);
 
 impl Heat {
+    /// Heat lost per second by all hot equipment, even without a [`Radiator`]. Equipment that only
+    /// heats up slower than this never overheats.
+    pub const PASSIVE_COOLING: f32 = 0.1;
+
     /// Get heat value
     pub fn get(&self) -> f32 {
         self.0
@@ -289,3 +383,85 @@ impl AddAssign<f32> for Heat {
 /// Marker struct that disables equipment and is removed when [`Heat`] is 0.
 #[derive(Component, Reflect)]
 pub struct Overheated;
+
+/// Equipment that cools the other equipment of its parent. The cooling of every radiator is shared
+/// evenly by the equipment that has [`Heat`].
+#[derive(Clone, Debug, Default, Component, Reflect, Serialize, Deserialize, PartialEq)]
+pub struct Radiator {
+    /// Heat removed per second, where 1 is the heat of overheated equipment
+    pub dissipation: f32,
+}
+
+impl Radiator {
+    /// Heat removed from each piece of hot equipment by several radiators
+    pub fn share<'a>(
+        radiators: impl IntoIterator<Item = &'a Radiator>,
+        hot: usize,
+        delta: f32,
+    ) -> f32 {
+        if hot == 0 {
+            return 0f32;
+        }
+        let dissipation = radiators
+            .into_iter()
+            .map(|radiator| radiator.dissipation)
+            .sum::<f32>();
+        dissipation * delta / hot as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_free_hardpoint() {
+        let hardpoint = |equipment: EquipmentTypeId| Hardpoint {
+            name: String::new(),
+            offset: (0f32, 0f32),
+            facing: 0f32,
+            equipment,
+            arc: None,
+        };
+        let hardpoints = [
+            hardpoint(EquipmentTypeId::Weapon),
+            hardpoint(EquipmentTypeId::Shield),
+            hardpoint(EquipmentTypeId::Weapon),
+        ];
+        let mut equipped = Equipped::default();
+        let weapon = Entity::from_raw(1);
+
+        // Hardpoints are filled in order, and only by their type of equipment
+        assert_eq!(
+            equipped.free_hardpoint(&hardpoints, &EquipmentTypeId::Weapon),
+            Some(0)
+        );
+        equipped.hardpoints.insert(0, weapon);
+        assert_eq!(
+            equipped.free_hardpoint(&hardpoints, &EquipmentTypeId::Weapon),
+            Some(2)
+        );
+        equipped.hardpoints.insert(2, weapon);
+        assert_eq!(
+            equipped.free_hardpoint(&hardpoints, &EquipmentTypeId::Weapon),
+            None
+        );
+        assert_eq!(
+            equipped.free_hardpoint(&hardpoints, &EquipmentTypeId::Shield),
+            Some(1)
+        );
+        assert_eq!(
+            equipped.free_hardpoint(&hardpoints, &EquipmentTypeId::Battery),
+            None
+        );
+    }
+
+    #[test]
+    fn test_radiator_share() {
+        let radiators = [Radiator { dissipation: 0.3 }, Radiator { dissipation: 0.1 }];
+        assert_eq!(Radiator::share(&radiators, 0, 1f32), 0f32);
+        assert!((Radiator::share(&radiators, 1, 0.5) - 0.2).abs() < f32::EPSILON);
+        assert!((Radiator::share(&radiators, 4, 1f32) - 0.1).abs() < f32::EPSILON);
+        assert_eq!(Radiator::share([], 2, 1f32), 0f32);
+    }
+}
@@ -1,8 +1,4 @@
-use bevy::{
-    ecs::component::StorageType,
-    prelude::*,
-    utils::{hashbrown, HashMap},
-};
+use bevy::{ecs::component::StorageType, prelude::*, utils::HashMap};
 use serde::{Deserialize, Serialize};
 
 use crate::prelude::*;
@@ -14,16 +10,91 @@ pub struct Inventory {
     capacity: usize,
     /// Spaces out of the capacity that are occupied by items
     space_occupied: usize,
-    /// Items and their amount in the inventory
-    items: HashMap<Handle<Item>, usize>,
+    /// Number of slots if the inventory is slot based. Each slot holds a single stack.
+    slots: Option<usize>,
+    /// Stacks of items in the inventory, in slot order
+    stacks: Vec<ItemStack>,
+}
+
+/// Some amount of a single type of item that all share the same metadata
+#[derive(Debug, Clone, PartialEq, Reflect)]
+pub struct ItemStack {
+    /// Handle of the item
+    pub item: Handle<Item>,
+    /// Number of items in the stack
+    pub amount: usize,
+    /// Data that belongs to these items in particular
+    pub metadata: ItemMetadata,
+}
+
+impl ItemStack {
+    /// Create a stack of items without any metadata
+    pub fn new(item: Handle<Item>, amount: usize) -> Self {
+        Self {
+            item,
+            amount,
+            metadata: default(),
+        }
+    }
+
+    /// Set the metadata of the stack
+    pub fn with_metadata(mut self, metadata: ItemMetadata) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Whether the items of another stack can be added onto this stack
+    pub fn stacks_with(&self, other: &ItemStack) -> bool {
+        self.item == other.item && self.metadata == other.metadata
+    }
+}
+
+/// Data that is kept with individual items, so that two items of the same type can differ
+#[derive(Debug, Default, Clone, PartialEq, Reflect, Serialize, Deserialize)]
+pub struct ItemMetadata {
+    /// Remaining durability, from 0 to 1
+    #[serde(default)]
+    pub durability: Option<f32>,
+    /// Heat of an equipped item when the game was saved
+    #[serde(default)]
+    pub heat: Option<f32>,
+    /// Name given to the item by the player
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Stat modifiers applied on top of the base stats when equipped
+    #[serde(default)]
+    pub modifiers: Vec<Modifier>,
+    /// Group and fire mode chosen by the player for an equipped weapon
+    #[serde(default)]
+    pub fire_control: Option<FireControl>,
 }
 
 /// Build an `Equipped` with starting items
 #[derive(Debug, Default, Clone, Reflect, Serialize, Deserialize)]
 #[reflect(Component, Serialize, Deserialize)]
 pub struct InventoryBuilder {
+    /// Items without metadata, as written by older saves
+    #[serde(default)]
     pub items: Vec<(String, usize)>,
     pub capacity: usize,
+    /// Number of slots if the inventory is slot based
+    #[serde(default)]
+    pub slots: Option<usize>,
+    /// Stacks of items, in slot order
+    #[serde(default)]
+    pub stacks: Vec<ItemStackBuilder>,
+}
+
+/// Build an [`ItemStack`] from the name of its item
+#[derive(Debug, Clone, Reflect, Serialize, Deserialize)]
+pub struct ItemStackBuilder {
+    /// Name of the item
+    pub item: String,
+    /// Number of items in the stack
+    pub amount: usize,
+    /// Data that belongs to these items in particular
+    #[serde(default)]
+    pub metadata: ItemMetadata,
 }
 
 impl Component for InventoryBuilder {
@@ -38,33 +109,45 @@ impl Builder for InventoryBuilder {
 
     fn from_output(output: Self::Output) -> Self {
         InventoryBuilder {
-            items: output
+            items: Vec::new(),
+            capacity: output.capacity(),
+            slots: output.slots(),
+            stacks: output
                 .iter()
-                .map(|(handle, count)| {
-                    let path = handle.path().unwrap();
+                .map(|stack| {
+                    let path = stack.item.path().unwrap();
                     let name = path
                         .path()
                         .file_stem()
                         .unwrap()
                         .to_string_lossy()
                         .to_string();
-                    (name, *count)
+                    ItemStackBuilder {
+                        item: name,
+                        amount: stack.amount,
+                        metadata: stack.metadata.clone(),
+                    }
                 })
                 .collect(),
-            capacity: output.capacity(),
         }
     }
 
     fn into_output(builder: Self, library: &Library) -> Self::Output {
+        let legacy = builder.items.iter().map(|(name, count)| ItemStackBuilder {
+            item: name.clone(),
+            amount: *count,
+            metadata: default(),
+        });
         Self::Output {
             capacity: builder.capacity,
             space_occupied: 0,
-            items: builder
-                .items
-                .iter()
-                .map(|(name, count)| {
-                    let handle = library.item(name).unwrap();
-                    (handle, *count)
+            slots: builder.slots,
+            stacks: legacy
+                .chain(builder.stacks.iter().cloned())
+                .map(|stack| ItemStack {
+                    item: library.item(&stack.item).unwrap(),
+                    amount: stack.amount,
+                    metadata: stack.metadata,
                 })
                 .collect(),
         }
@@ -75,8 +158,9 @@ impl Default for Inventory {
     fn default() -> Self {
         Self {
             capacity: 64,
-            items: HashMap::default(),
             space_occupied: default(),
+            slots: None,
+            stacks: Vec::new(),
         }
     }
 }
@@ -86,14 +170,13 @@ impl Inventory {
     pub fn max_capacity() -> Self {
         Self {
             capacity: usize::MAX,
-            space_occupied: 0,
-            items: HashMap::new(),
+            ..default()
         }
     }
 
     /// Get the amount of a given `Item` handle
     pub fn quantity(&self, item: &Handle<Item>) -> usize {
-        self.items.get(item).cloned().unwrap_or_default()
+        self.count(item)
     }
 
     /// Maximum capacity of this inventory
@@ -101,6 +184,11 @@ impl Inventory {
         self.capacity
     }
 
+    /// Number of slots if the inventory is slot based
+    pub fn slots(&self) -> Option<usize> {
+        self.slots
+    }
+
     /// Gets space occupied
     pub fn space_occupied(&self) -> usize {
         self.space_occupied
@@ -111,9 +199,9 @@ impl Inventory {
         self.capacity - self.space_occupied
     }
 
-    /// Iterates through all [`Item`] handles in this [`Inventory`]
-    pub fn iter(&self) -> hashbrown::hash_map::Iter<Handle<Item>, usize> {
-        self.items.iter()
+    /// Iterates through all [`ItemStack`]s in this [`Inventory`], in slot order
+    pub fn iter(&self) -> std::slice::Iter<ItemStack> {
+        self.stacks.iter()
     }
 
     /// Creates a new [`Inventory`] with a specified `capacity`
@@ -124,6 +212,12 @@ impl Inventory {
         }
     }
 
+    /// Make the inventory slot based, or not slot based when `slots` is `None`
+    pub fn with_slots(mut self, slots: Option<usize>) -> Self {
+        self.slots = slots;
+        self
+    }
+
     /// Adds an item into the [`Inventory`], and may error.
     pub fn with(
         mut self,
@@ -132,114 +226,175 @@ impl Inventory {
         items: &Assets<Item>,
     ) -> Result<Self, InventoryError> {
         let retrieved_item = items.get(&item).ok_or(InventoryError::ItemNotFound)?;
-        self.add(item, retrieved_item.size, amount)?;
+        self.add(ItemStack::new(item, amount), retrieved_item)?;
         Ok(self)
     }
 
-    /// Add an item to the inventory
-    pub fn add(
-        &mut self,
-        item: Handle<Item>,
-        size: usize,
-        amount: usize,
-    ) -> Result<(), InventoryError> {
+    /// Add a stack of items to the inventory. Items fill up existing stacks with the same metadata
+    /// before new stacks are started, and no stack grows past the [`Item::stack_size`].
+    pub fn add(&mut self, stack: ItemStack, item: &Item) -> Result<(), InventoryError> {
         // Ensure we can handle the space
-        let total_size = size * amount;
+        let total_size = item.size * stack.amount;
         if self.space_occupied + total_size > self.capacity() {
-            Err(InventoryError::NoSpaceLeft {
+            return Err(InventoryError::NoSpaceLeft {
                 overage: self.space_occupied + total_size - self.capacity,
-            })
-        } else {
-            // We can handle the space
-            // Add the size of the added items to the inventory's tracker so we don't need to calculate
-            // this every frame
-            self.space_occupied += total_size;
-            // Add items to the inventory. If we already have an item of the type,
-            // get it from our hashmap and add. Otherwise, insert.
-            if let Some(existing_amount) = self.items.get_mut(&item) {
-                let new_amount = *existing_amount + amount;
-                *existing_amount = new_amount;
-            } else {
-                self.items.insert(item, amount);
-            };
-            Ok(())
+            });
+        }
+
+        // Ensure there are enough free slots for any new stacks
+        let limit = item.stack_size.unwrap_or(usize::MAX).max(1);
+        let room = self.stack_room(&stack, limit);
+        let new_stacks = stack.amount.saturating_sub(room).div_ceil(limit);
+        if let Some(slots) = self.slots {
+            if self.stacks.len() + new_stacks > slots {
+                return Err(InventoryError::NoSlotsLeft {
+                    needed: self.stacks.len() + new_stacks - slots,
+                });
+            }
+        }
+
+        // We can handle the space
+        // Add the size of the added items to the inventory's tracker so we don't need to calculate
+        // this every frame
+        self.space_occupied += total_size;
+        let mut remaining = stack.amount;
+        for existing in self
+            .stacks
+            .iter_mut()
+            .filter(|existing| existing.stacks_with(&stack))
+        {
+            let added = remaining.min(limit.saturating_sub(existing.amount));
+            existing.amount += added;
+            remaining -= added;
+        }
+        while remaining > 0 {
+            let amount = remaining.min(limit);
+            self.stacks.push(ItemStack {
+                amount,
+                ..stack.clone()
+            });
+            remaining -= amount;
         }
+        Ok(())
+    }
+
+    /// Largest amount of a stack that can be added, limited by both the remaining space and slots
+    pub fn room_for(&self, stack: &ItemStack, item: &Item) -> usize {
+        let by_space = match item.size {
+            0 => usize::MAX,
+            size => self.space_remaining() / size,
+        };
+        let limit = item.stack_size.unwrap_or(usize::MAX).max(1);
+        let by_slots = match self.slots {
+            Some(slots) => slots
+                .saturating_sub(self.stacks.len())
+                .saturating_mul(limit)
+                .saturating_add(self.stack_room(stack, limit)),
+            None => usize::MAX,
+        };
+        by_space.min(by_slots)
+    }
+
+    /// Items that can be added onto existing stacks without starting a new one
+    fn stack_room(&self, stack: &ItemStack, limit: usize) -> usize {
+        self.stacks
+            .iter()
+            .filter(|existing| existing.stacks_with(stack))
+            .map(|existing| limit.saturating_sub(existing.amount))
+            .fold(0usize, usize::saturating_add)
     }
 
-    /// Try to remove an item from the inventory
-    pub fn remove(
+    /// Try to remove a stack of items with matching metadata from the inventory
+    pub fn remove(&mut self, stack: &ItemStack, item: &Item) -> Result<(), InventoryError> {
+        self.remove_where(stack.amount, item, |existing| existing.stacks_with(stack))
+            .map(|_| ())
+    }
+
+    /// Try to remove items of a type from the inventory, whatever their metadata. The removed items
+    /// are returned so their metadata can be kept.
+    pub fn remove_any(
         &mut self,
-        item: &Handle<Item>,
-        size: usize,
+        handle: &Handle<Item>,
+        item: &Item,
         amount: usize,
-    ) -> Result<(), InventoryError> {
-        // Retrieve the item from storage
-        match self.items.get_mut(item) {
-            Some(existing_amount) => {
-                // ensure the existing amount is more than the desired amount, if specified. In unspecified, we remove everything
-                if amount > *existing_amount {
-                    return Err(InventoryError::InsufficientItems {
-                        want_to_remove: amount,
-                        exists: *existing_amount,
-                    });
-                }
-
-                // Subtrack from our inventory space
-                self.space_occupied -= amount * size;
-
-                let new_amount = *existing_amount - amount;
-                *existing_amount = new_amount;
-
-                // If the existing amount is now 0, remove the item from the inventory entirely
-                if new_amount == 0 {
-                    self.items.remove(item);
-                }
-
-                Ok(())
+    ) -> Result<Vec<ItemStack>, InventoryError> {
+        self.remove_where(amount, item, |existing| existing.item == *handle)
+    }
+
+    /// Remove `amount` items from the stacks matching `filter`, emptying the last stacks first
+    fn remove_where(
+        &mut self,
+        amount: usize,
+        item: &Item,
+        filter: impl Fn(&ItemStack) -> bool,
+    ) -> Result<Vec<ItemStack>, InventoryError> {
+        // Ensure the existing amount is more than the desired amount
+        let exists = self
+            .stacks
+            .iter()
+            .filter(|existing| filter(*existing))
+            .map(|existing| existing.amount)
+            .sum::<usize>();
+        if amount > exists {
+            return Err(InventoryError::InsufficientItems {
+                want_to_remove: amount,
+                exists,
+            });
+        }
+
+        // Subtrack from our inventory space
+        self.space_occupied -= amount * item.size;
+
+        let mut removed = Vec::new();
+        let mut remaining = amount;
+        for existing in self.stacks.iter_mut().rev() {
+            if remaining == 0 {
+                break;
             }
-            None => {
-                // No items exist in the inventory to remove
-                Err(InventoryError::InsufficientItems {
-                    want_to_remove: amount,
-                    exists: 0,
-                })
+            if !filter(&*existing) {
+                continue;
             }
+            let taken = remaining.min(existing.amount);
+            existing.amount -= taken;
+            remaining -= taken;
+            removed.push(ItemStack {
+                amount: taken,
+                ..existing.clone()
+            });
         }
+
+        // Empty stacks free up their slot
+        self.stacks.retain(|existing| existing.amount > 0);
+        Ok(removed)
     }
 
     /// Get the mass of all items in the inventory
     pub fn mass(&self, items: &Assets<Item>) -> f32 {
-        self.items.iter().fold(0f32, |a, (handle, amt)| {
-            a + (items.get(handle).unwrap().mass * *amt as f32)
+        self.stacks.iter().fold(0f32, |a, stack| {
+            a + (items.get(&stack.item).unwrap().mass * stack.amount as f32)
         })
     }
 
     /// Returns `true` if there are no items in the inventory
     pub fn is_empty(&self) -> bool {
-        self.items.is_empty()
+        self.stacks.is_empty()
     }
 
     /// Count the number of items in the inventory
     pub fn count(&self, item: &Handle<Item>) -> usize {
-        self.items.get(item).cloned().unwrap_or_default()
+        self.stacks
+            .iter()
+            .filter(|stack| stack.item == *item)
+            .map(|stack| stack.amount)
+            .sum()
     }
 
-    /// Move items of a type to another inventory
-    pub fn transfer(
+    /// Move all items into another inventory
+    pub fn transfer_all(
         &mut self,
-        item: Handle<Item>,
         inventory: &mut Inventory,
-        amount: usize,
         items: &Assets<Item>,
-    ) -> Result<usize, InventoryError> {
-        let retrieved_item = items.get(&item).ok_or(InventoryError::ItemNotFound)?;
-        inventory.add(item.clone(), retrieved_item.size, amount)?;
-        self.remove(&item, retrieved_item.size, amount)?;
-        Ok(amount)
-    }
-
-    /// Move all items into another inventory
-    pub fn transfer_all(&mut self, inventory: &mut Inventory) -> Result<(), InventoryError> {
+    ) -> Result<(), InventoryError> {
         // Short circuit if not enough space in new inventory
         if self.space_occupied() > inventory.space_remaining() {
             return Err(InventoryError::NoSpaceLeft {
@@ -247,21 +402,47 @@ impl Inventory {
             });
         }
 
-        inventory.space_occupied += self.space_occupied;
-        self.space_occupied = 0;
-
-        // Drain into provided inventory
-        for (item, amount) in self.items.drain() {
-            if let Some(existing_amount) = inventory.items.get_mut(&item) {
-                *existing_amount += amount
-            } else {
-                inventory.items.insert(item, amount);
-            }
+        // Add to a copy first, so nothing moves if any stack does not fit
+        let mut merged = inventory.clone();
+        for stack in self.stacks.iter() {
+            let retrieved_item = items.get(&stack.item).ok_or(InventoryError::ItemNotFound)?;
+            merged.add(stack.clone(), retrieved_item)?;
         }
+        *inventory = merged;
+        self.drain();
 
         Ok(())
     }
 
+    /// Stacks out of `stacks` that fit in this inventory. The most valuable items for their size
+    /// are picked first, and stacks that only partly fit are split.
+    pub fn fitting(
+        &self,
+        stacks: impl IntoIterator<Item = ItemStack>,
+        items: &Assets<Item>,
+    ) -> Result<Vec<ItemStack>, InventoryError> {
+        let mut stacks = stacks
+            .into_iter()
+            .map(|stack| {
+                let retrieved_item = items.get(&stack.item).ok_or(InventoryError::ItemNotFound)?;
+                Ok((retrieved_item, stack))
+            })
+            .collect::<Result<Vec<_>, InventoryError>>()?;
+        stacks.sort_by(|(a, _), (b, _)| b.value_per_size().total_cmp(&a.value_per_size()));
+
+        let mut staged = self.clone();
+        let mut fitting = Vec::new();
+        for (item, stack) in stacks {
+            let amount = stack.amount.min(staged.room_for(&stack, item));
+            if amount > 0 {
+                let stack = ItemStack { amount, ..stack };
+                staged.add(stack.clone(), item)?;
+                fitting.push(stack);
+            }
+        }
+        Ok(fitting)
+    }
+
     /// Create an inventory with named items
     pub fn with_many_from_str(
         mut self,
@@ -275,16 +456,16 @@ impl Inventory {
                 .get(&format!("items/{}.ron", k))
                 .ok_or(InventoryError::ItemNotFound)?;
             let retrieved_item = items.get(item).ok_or(InventoryError::ItemNotFound)?;
-            self.add(item.clone(), retrieved_item.size, *v)?;
+            self.add(ItemStack::new(item.clone(), *v), retrieved_item)?;
         }
         Ok(self)
     }
 
-    /// Clears the map, returning all key-value pairs as an iterator. Keeps the
-    /// allocated memory for reuse.
-    pub fn drain(&mut self) -> hashbrown::hash_map::Drain<Handle<Item>, usize> {
+    /// Clears the inventory, returning all stacks as an iterator. Keeps the allocated memory for
+    /// reuse.
+    pub fn drain(&mut self) -> std::vec::Drain<ItemStack> {
         self.space_occupied = 0;
-        self.items.drain()
+        self.stacks.drain(..)
     }
 }
 
@@ -292,13 +473,96 @@ impl Inventory {
 mod tests {
     use super::*;
 
+    fn item(size: usize, stack_size: Option<usize>) -> Item {
+        Item {
+            size,
+            stack_size,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn test_inventory_fitting() {
+        let mut items = Assets::<Item>::default();
+        let mut add = |size, value| {
+            items.add(Item {
+                name: format!("{size} {value}"),
+                size,
+                value,
+                ..default()
+            })
+        };
+        let (cheap, dear, bulky) = (add(1, 1), add(1, 10), add(4, 20));
+        let inv = Inventory::with_capacity(7);
+        let stacks = [(&cheap, 5), (&bulky, 1), (&dear, 4)]
+            .map(|(handle, amount)| ItemStack::new(handle.clone(), amount));
+
+        // The most valuable items for their size are taken first, then whatever still fits
+        let fitting = inv.fitting(stacks.clone(), &items).unwrap();
+        assert_eq!(
+            fitting,
+            vec![ItemStack::new(dear.clone(), 4), ItemStack::new(cheap, 3)]
+        );
+
+        // Slot limits are respected
+        let inv = Inventory::with_capacity(100).with_slots(Some(1));
+        let fitting = inv.fitting(stacks, &items).unwrap();
+        assert_eq!(fitting, vec![ItemStack::new(dear, 4)]);
+    }
+
     #[test]
     fn test_inventory_add_over() {
         let mut inv = Inventory::with_capacity(10);
-        assert!(inv.add(Handle::default(), 3, 1).is_ok());
-        assert!(inv.add(Handle::default(), 6, 1).is_ok());
+        let stack = ItemStack::new(Handle::default(), 1);
+        assert!(inv.add(stack.clone(), &item(3, None)).is_ok());
+        assert!(inv.add(stack.clone(), &item(6, None)).is_ok());
         assert!(inv
-            .add(Handle::default(), 3, 1)
+            .add(stack, &item(3, None))
             .is_err_and(|x| x == InventoryError::NoSpaceLeft { overage: 2 }))
     }
+
+    #[test]
+    fn test_inventory_slots_and_metadata() {
+        let scrap = item(1, Some(5));
+        let mut inv = Inventory::with_capacity(100).with_slots(Some(3));
+        let pristine = ItemStack::new(Handle::default(), 7);
+        let damaged = ItemStack::new(Handle::default(), 1).with_metadata(ItemMetadata {
+            durability: Some(0.5),
+            ..default()
+        });
+
+        // Stacks are split at the stack size, and items with different metadata never stack
+        inv.add(pristine.clone(), &scrap).unwrap();
+        inv.add(damaged.clone(), &scrap).unwrap();
+        assert_eq!(
+            inv.iter().map(|stack| stack.amount).collect::<Vec<_>>(),
+            vec![5, 2, 1]
+        );
+        assert_eq!(inv.count(&Handle::default()), 8);
+
+        // Partial stacks are filled before running out of slots
+        assert_eq!(inv.room_for(&pristine, &scrap), 3);
+        assert_eq!(
+            Inventory::with_capacity(4)
+                .with_slots(Some(3))
+                .room_for(&pristine, &scrap),
+            4
+        );
+        inv.add(ItemStack::new(Handle::default(), 3), &scrap)
+            .unwrap();
+        assert!(inv
+            .add(ItemStack::new(Handle::default(), 1), &scrap)
+            .is_err_and(|x| x == InventoryError::NoSlotsLeft { needed: 1 }));
+
+        // Metadata is kept when moving items between inventories
+        let mut other = Inventory::max_capacity();
+        inv.remove(&damaged, &scrap).unwrap();
+        other.add(damaged.clone(), &scrap).unwrap();
+        assert_eq!(other.iter().next(), Some(&damaged));
+        assert!(inv.remove(&damaged, &scrap).is_err());
+        let removed = inv.remove_any(&Handle::default(), &scrap, 6).unwrap();
+        assert_eq!(removed.iter().map(|stack| stack.amount).sum::<usize>(), 6);
+        assert_eq!(inv.count(&Handle::default()), 4);
+        assert_eq!(inv.space_occupied(), 4);
+    }
 }
@@ -2,23 +2,34 @@
 mod drop;
 /// Energy management
 mod energy;
+/// Engines that change how crafts handle
+mod engine;
 /// Equipment management
 mod equipment;
 /// Inventory management
 mod inventory;
+/// Stat modifiers on equipment
+mod modifier;
 /// Repair bots, health, and armor
 mod repair;
+/// Shields that absorb damage
+mod shield;
+/// Changes to several inventories that either all happen or none do
+mod transaction;
 /// Weapons
 mod weapon;
 
-pub use {drop::*, energy::*, equipment::*, inventory::*, repair::*, weapon::*};
+pub use {
+    drop::*, energy::*, engine::*, equipment::*, inventory::*, modifier::*, repair::*, shield::*,
+    transaction::*, weapon::*,
+};
 
 use {
     bevy::prelude::*,
     serde::{Deserialize, Serialize},
 };
 /// A general in-game item
-#[derive(Debug, Component, Clone, Reflect, Asset, Serialize, Deserialize)]
+#[derive(Debug, Default, Component, Clone, Reflect, Asset, Serialize, Deserialize)]
 pub struct Item {
     /// The in-game name of this item
     pub name: String,
@@ -26,13 +37,74 @@ pub struct Item {
     pub mass: f32,
     /// The space this item displaces in the [`Inventory`]
     pub size: usize,
+    /// Maximum amount of this item in one [`ItemStack`], or `None` for no limit
+    #[serde(default)]
+    pub stack_size: Option<usize>,
     /// The worth of this item in `Credits`
     pub value: usize,
+    /// Free-form tags used to sort and filter items, such as `salvage`
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// How rare this item is
+    #[serde(default)]
+    pub rarity: Rarity,
     /// Whether or not this item is equippable, and its associated data. If `Some(_)`, see [`EquipmentType`]
     #[serde(default)]
     pub equipment: Option<EquipmentType>,
 }
 
+/// How rare an item is. Rarer items are dropped less often and are less likely to be in stock.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Reflect,
+    Serialize,
+    Deserialize,
+)]
+pub enum Rarity {
+    /// Found everywhere
+    #[default]
+    Common,
+    /// Found somewhat often
+    Uncommon,
+    /// Seldom found
+    Rare,
+    /// Almost never found
+    Legendary,
+}
+
+impl Rarity {
+    /// How many times less likely an item of this rarity is to drop or be in stock than a common
+    /// item
+    pub fn scarcity(&self) -> usize {
+        match self {
+            Rarity::Common => 1,
+            Rarity::Uncommon => 2,
+            Rarity::Rare => 4,
+            Rarity::Legendary => 8,
+        }
+    }
+}
+
+impl std::fmt::Display for Rarity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Rarity::Common => "common",
+            Rarity::Uncommon => "uncommon",
+            Rarity::Rare => "rare",
+            Rarity::Legendary => "legendary",
+        };
+        write!(f, "{name}")
+    }
+}
+
 /// Item(s) in the world. Uses an inventory for item management
 #[derive(Component, Reflect, Clone, Serialize, Deserialize)]
 #[reflect(Component, Serialize, Deserialize)]
@@ -59,10 +131,26 @@ impl Item {
                 EquipmentType::Generator(_) => "generator",
                 EquipmentType::Battery(_) => "battery",
                 EquipmentType::Armor(_) => "armor",
+                EquipmentType::Shield(_) => "shield",
+                EquipmentType::Radiator(_) => "radiator",
+                EquipmentType::Engine(_) => "engine",
             },
             None => "item",
         }
     }
+
+    /// Whether the item has a tag
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// Worth of this item in `Credits` for every space it takes up in an [`Inventory`]
+    pub fn value_per_size(&self) -> f32 {
+        match self.size {
+            0 => f32::INFINITY,
+            size => self.value as f32 / size as f32,
+        }
+    }
 }
 
 impl PartialEq for Item {
@@ -0,0 +1,271 @@
+use bevy::prelude::*;
+use bevy_turborand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+/// Changes a stat of a piece of equipment on top of the base stats of its [`Item`], such as +10%
+/// damage. Modifiers are kept in the [`ItemMetadata`] of the item.
+#[derive(Debug, Clone, Copy, PartialEq, Reflect, Serialize, Deserialize)]
+pub struct Modifier {
+    /// Stat that is changed
+    pub stat: Stat,
+    /// Fraction the stat is changed by, where `0.1` is +10% and `-0.2` is -20%
+    pub amount: f32,
+}
+
+/// Equipment stats that can be changed by a [`Modifier`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect, Serialize, Deserialize)]
+pub enum Stat {
+    /// Damage of a weapon
+    Damage,
+    /// Range of a weapon
+    Range,
+    /// Energy used by a weapon
+    Energy,
+    /// Heat gained by a laser, projectile weapon, repair bot or generator
+    Heat,
+    /// Shots per second of a projectile or missile weapon
+    FireRate,
+    /// Repair rate of a repair bot
+    Repair,
+    /// Recharge rate of a generator
+    Recharge,
+    /// Capacity of a battery
+    Capacity,
+    /// Health added by armor
+    Armor,
+}
+
+impl Stat {
+    /// Every stat
+    pub const ALL: [Stat; 9] = [
+        Stat::Damage,
+        Stat::Range,
+        Stat::Energy,
+        Stat::Heat,
+        Stat::FireRate,
+        Stat::Repair,
+        Stat::Recharge,
+        Stat::Capacity,
+        Stat::Armor,
+    ];
+
+    /// Whether this stat exists on a type of equipment
+    pub fn applies_to(&self, equipment: &EquipmentType) -> bool {
+        match (self, equipment) {
+            (Stat::Damage | Stat::Range | Stat::Energy, EquipmentType::Weapon(_)) => true,
+            (Stat::Heat, EquipmentType::Weapon(weapon)) => {
+                matches!(
+                    weapon.weapon_type,
+                    WeaponType::LaserWeapon { .. } | WeaponType::ProjectileWeapon { .. }
+                )
+            }
+            (Stat::FireRate, EquipmentType::Weapon(weapon)) => {
+                matches!(
+                    weapon.weapon_type,
+                    WeaponType::ProjectileWeapon { .. } | WeaponType::MissileWeapon { .. }
+                )
+            }
+            (Stat::Repair | Stat::Heat, EquipmentType::RepairBot(_))
+            | (Stat::Recharge | Stat::Heat, EquipmentType::Generator(_))
+            | (Stat::Capacity, EquipmentType::Battery(_))
+            | (Stat::Armor, EquipmentType::Armor(_)) => true,
+            _ => false,
+        }
+    }
+
+    /// Whether a lower value of this stat is better
+    fn lower_is_better(&self) -> bool {
+        matches!(self, Stat::Energy | Stat::Heat)
+    }
+}
+
+impl std::fmt::Display for Stat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Stat::Damage => "damage",
+            Stat::Range => "range",
+            Stat::Energy => "energy use",
+            Stat::Heat => "heat",
+            Stat::FireRate => "fire rate",
+            Stat::Repair => "repair rate",
+            Stat::Recharge => "recharge rate",
+            Stat::Capacity => "capacity",
+            Stat::Armor => "armor",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl std::fmt::Display for Modifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:+.0}% {}", self.amount * 100f32, self.stat)
+    }
+}
+
+impl Modifier {
+    /// Change the stat of a piece of equipment. Stats that do not exist on the equipment are left
+    /// unchanged.
+    pub fn apply(&self, equipment: &mut EquipmentType) {
+        let scale = (1f32 + self.amount).max(0f32);
+        let scale_usize = |value: &mut usize| *value = (*value as f32 * scale).round() as usize;
+        match (self.stat, equipment) {
+            (stat, EquipmentType::Weapon(weapon)) => match &mut weapon.weapon_type {
+                WeaponType::ProjectileWeapon {
+                    damage,
+                    distance,
+                    lifetime,
+                    energy,
+                    recoil,
+                    heat_per_shot,
+                    ..
+                } => match stat {
+                    Stat::Damage => scale_usize(damage),
+                    Stat::Range => {
+                        *distance *= scale;
+                        *lifetime *= scale;
+                    }
+                    Stat::Energy => scale_usize(energy),
+                    Stat::Heat => *heat_per_shot *= scale,
+                    // Faster firing is less time between shots
+                    Stat::FireRate if scale > 0f32 => *recoil /= scale,
+                    _ => (),
+                },
+                WeaponType::LaserWeapon {
+                    damage_per_second,
+                    range,
+                    energy_per_second,
+                    activation_energy,
+                    heat_per_second,
+                    ..
+                } => match stat {
+                    Stat::Damage => *damage_per_second *= scale,
+                    Stat::Range => *range *= scale,
+                    Stat::Energy => {
+                        *energy_per_second *= scale;
+                        *activation_energy *= scale;
+                    }
+                    Stat::Heat => *heat_per_second *= scale,
+                    _ => (),
+                },
+                WeaponType::MissileWeapon {
+                    damage,
+                    fuel,
+                    energy,
+                    recoil,
+                    ..
+                } => match stat {
+                    Stat::Damage => scale_usize(damage),
+                    Stat::Range => *fuel *= scale,
+                    Stat::Energy => scale_usize(energy),
+                    Stat::FireRate if scale > 0f32 => *recoil /= scale,
+                    _ => (),
+                },
+            },
+            (Stat::Repair, EquipmentType::RepairBot(repair)) => repair.rate *= scale,
+            (Stat::Heat, EquipmentType::RepairBot(repair)) => repair.heat_per_second *= scale,
+            (Stat::Recharge, EquipmentType::Generator(generator)) => {
+                generator.recharge_rate *= scale
+            }
+            (Stat::Heat, EquipmentType::Generator(generator)) => generator.heat_per_second *= scale,
+            (Stat::Capacity, EquipmentType::Battery(battery)) => {
+                *battery = Battery::from_capacity(battery.capacity() * scale)
+            }
+            (Stat::Armor, EquipmentType::Armor(armor)) => scale_usize(&mut armor.health),
+            _ => (),
+        }
+    }
+
+    /// Roll random modifiers for a piece of equipment. Rarer items roll more modifiers, and every
+    /// rolled modifier improves the equipment.
+    pub fn roll(
+        equipment: &EquipmentType,
+        rarity: Rarity,
+        rng: &mut impl TurboRand,
+    ) -> Vec<Modifier> {
+        let stats = Stat::ALL
+            .into_iter()
+            .filter(|stat| stat.applies_to(equipment))
+            .collect::<Vec<_>>();
+        if stats.is_empty() {
+            return Vec::new();
+        }
+        (0..rng.usize(0..=rarity as usize + 1))
+            .map(|_| {
+                let stat = stats[rng.usize(..stats.len())];
+                // Round to whole percentages so they read well in the inspector
+                let amount = ((0.05f32 + rng.f32() * 0.2f32) * 100f32).round() / 100f32;
+                Modifier {
+                    stat,
+                    amount: if stat.lower_is_better() {
+                        -amount
+                    } else {
+                        amount
+                    },
+                }
+            })
+            .collect()
+    }
+}
+
+impl EquipmentType {
+    /// Apply modifiers on top of the base stats of this equipment
+    pub fn with_modifiers(mut self, modifiers: &[Modifier]) -> Self {
+        for modifier in modifiers {
+            modifier.apply(&mut self);
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_modifiers() {
+        let laser = EquipmentType::Weapon(Weapon::test_laser());
+        let modifiers = [
+            Modifier {
+                stat: Stat::Damage,
+                amount: 0.1,
+            },
+            Modifier {
+                stat: Stat::Heat,
+                amount: -0.2,
+            },
+            // Lasers have no fire rate, so this does nothing
+            Modifier {
+                stat: Stat::FireRate,
+                amount: 0.5,
+            },
+        ];
+        let EquipmentType::Weapon(Weapon {
+            weapon_type:
+                WeaponType::LaserWeapon {
+                    damage_per_second,
+                    heat_per_second,
+                    range,
+                    ..
+                },
+            ..
+        }) = laser.clone().with_modifiers(&modifiers)
+        else {
+            unreachable!()
+        };
+        assert!((damage_per_second - 11f32).abs() < 1e-4);
+        assert!((heat_per_second - 0.8f32).abs() < 1e-4);
+        assert_eq!(range, 4f32);
+        assert_eq!(modifiers[0].to_string(), "+10% damage");
+
+        // Rolled modifiers only touch stats the equipment has
+        let mut rng = Rng::with_seed(16);
+        for _ in 0..100 {
+            for modifier in Modifier::roll(&laser, Rarity::Legendary, &mut rng) {
+                assert!(modifier.stat.applies_to(&laser));
+                assert!(modifier.stat != Stat::Heat || modifier.amount < 0f32);
+            }
+        }
+    }
+}
@@ -14,13 +14,19 @@ use crate::prelude::*;
 pub struct RepairBot {
     /// Repair rate / s
     pub rate: f32,
+    /// Heat added per second while repairing, where 1 is overheated
+    #[serde(default)]
+    pub heat_per_second: f32,
 }
 
-/// Increases overal [`Health`]
+/// Increases overal [`Health`] and resists some types of damage
 #[derive(Clone, Debug, Default, Reflect, Serialize, Deserialize, PartialEq)]
 pub struct Armor {
     /// Armor amount
     pub health: usize,
+    /// Damage resisted by this armor. Resistances of all equipped armor are added together.
+    #[serde(default)]
+    pub resistances: Resistances,
 }
 
 impl Component for Armor {
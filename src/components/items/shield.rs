@@ -0,0 +1,101 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+/// Absorbs damage before it reaches [`Armor`] and [`Health`]. The shield recharges with
+/// [`Energy`] from its parent once it has not been hit for a while.
+#[derive(Clone, Debug, Default, Component, Reflect, Serialize, Deserialize, PartialEq)]
+pub struct Shield {
+    /// Most damage the shield can hold
+    pub capacity: f32,
+    /// Charge restored per second
+    pub recharge_rate: f32,
+    /// [`Energy`] used for every point of charge restored
+    pub energy_per_charge: f32,
+    /// Seconds after being hit before the shield starts to recharge
+    pub recharge_delay: f32,
+    /// Damage the shield can still absorb
+    #[serde(skip)]
+    pub charge: f32,
+    /// The timestamp the shield was last hit
+    #[serde(skip)]
+    pub last_hit: Duration,
+}
+
+impl Shield {
+    /// Absorb as much of a hit as the charge allows, returning the damage that gets through
+    pub fn absorb(&mut self, amount: f32, now: Duration) -> f32 {
+        self.last_hit = now;
+        let absorbed = amount.min(self.charge).max(0f32);
+        self.charge -= absorbed;
+        amount - absorbed
+    }
+
+    /// Energy needed to restore as much charge as possible, or none if the shield was hit too
+    /// recently
+    pub fn demand(&self, now: Duration, delta: f32) -> f32 {
+        if now < self.last_hit + Duration::from_secs_f32(self.recharge_delay) {
+            return 0f32;
+        }
+        (self.recharge_rate * delta)
+            .min(self.capacity - self.charge)
+            .max(0f32)
+            * self.energy_per_charge
+    }
+
+    /// Restore charge with energy, unless the shield was hit too recently
+    pub fn recharge(&mut self, energy: &mut Energy, now: Duration, delta: f32) {
+        if now < self.last_hit + Duration::from_secs_f32(self.recharge_delay) {
+            return;
+        }
+        let mut amount = (self.recharge_rate * delta).min(self.capacity - self.charge);
+        if self.energy_per_charge > 0f32 {
+            // Recharge as much as the energy allows
+            amount = amount.min(energy.charge() / self.energy_per_charge);
+        }
+        if amount > 0f32 && energy.consume(amount * self.energy_per_charge).is_ok() {
+            self.charge += amount;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shields() {
+        let mut shield = Shield {
+            capacity: 10f32,
+            recharge_rate: 5f32,
+            energy_per_charge: 2f32,
+            recharge_delay: 1f32,
+            ..default()
+        };
+        let mut energy = Energy::from(100f32);
+
+        // Charge is limited by the capacity
+        shield.recharge(&mut energy, Duration::from_secs(2), 4f32);
+        assert_eq!(shield.charge, 10f32);
+        assert_eq!(energy.charge(), 80f32);
+
+        // Damage beyond the charge gets through
+        assert_eq!(shield.absorb(4f32, Duration::from_secs(3)), 0f32);
+        assert_eq!(shield.absorb(8f32, Duration::from_secs(3)), 2f32);
+        assert_eq!(shield.charge, 0f32);
+
+        // Nothing is recharged until the delay has passed
+        shield.recharge(&mut energy, Duration::from_millis(3500), 1f32);
+        assert_eq!(shield.charge, 0f32);
+
+        // Recharging is limited by the energy left
+        assert_eq!(shield.demand(Duration::from_secs(4), 1f32), 10f32);
+        let mut energy = Energy::from(4f32);
+        shield.recharge(&mut energy, Duration::from_secs(4), 1f32);
+        assert_eq!(shield.charge, 2f32);
+        assert_eq!(energy.charge(), 0f32);
+    }
+}
@@ -0,0 +1,328 @@
+use bevy::{ecs::entity::EntityHashMap, prelude::*};
+
+use crate::prelude::*;
+
+/// A batch of changes to the [`Inventory`] and [`Credits`] of several entities. Every change is
+/// checked before anything is committed, so either all of the changes happen or none do.
+#[derive(Debug, Default, Clone)]
+pub struct InventoryTransaction {
+    /// Changes in the order they are applied
+    operations: Vec<Operation>,
+}
+
+/// A single change in an [`InventoryTransaction`]
+#[derive(Debug, Clone)]
+enum Operation {
+    /// Add a stack to an inventory
+    Add(Entity, ItemStack),
+    /// Remove a stack with matching metadata from an inventory
+    Remove(Entity, ItemStack),
+    /// Remove items of a type from an inventory, whatever their metadata
+    RemoveAny(Entity, Handle<Item>, usize),
+    /// Move everything in one inventory to another
+    TransferAll(Entity, Entity),
+    /// Move credits from one entity to another
+    Pay(Entity, Entity, usize),
+}
+
+impl InventoryTransaction {
+    /// Add a stack of items to the inventory of `entity`
+    pub fn add(mut self, entity: Entity, stack: ItemStack) -> Self {
+        self.operations.push(Operation::Add(entity, stack));
+        self
+    }
+
+    /// Remove a stack of items with matching metadata from the inventory of `entity`
+    pub fn remove(mut self, entity: Entity, stack: ItemStack) -> Self {
+        self.operations.push(Operation::Remove(entity, stack));
+        self
+    }
+
+    /// Remove items of a type from the inventory of `entity`, whatever their metadata
+    pub fn remove_any(mut self, entity: Entity, item: Handle<Item>, amount: usize) -> Self {
+        self.operations
+            .push(Operation::RemoveAny(entity, item, amount));
+        self
+    }
+
+    /// Move a stack of items with matching metadata between inventories
+    pub fn transfer(self, from: Entity, to: Entity, stack: ItemStack) -> Self {
+        self.remove(from, stack.clone()).add(to, stack)
+    }
+
+    /// Move every item in one inventory to another
+    pub fn transfer_all(mut self, from: Entity, to: Entity) -> Self {
+        self.operations.push(Operation::TransferAll(from, to));
+        self
+    }
+
+    /// Move credits from one entity to another
+    pub fn pay(mut self, from: Entity, to: Entity, amount: usize) -> Self {
+        self.operations.push(Operation::Pay(from, to, amount));
+        self
+    }
+
+    /// Whether the transaction has no changes
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    /// Apply the transaction to inventories and credits held in maps. The maps are left unchanged
+    /// if any part of the transaction fails.
+    pub fn apply(
+        &self,
+        inventories: &mut EntityHashMap<Inventory>,
+        credits: &mut EntityHashMap<Credits>,
+        items: &Assets<Item>,
+    ) -> Result<(), InventoryError> {
+        let mut staged_inventories = inventories.clone();
+        let mut staged_credits = credits.clone();
+        let retrieve =
+            |handle: &Handle<Item>| items.get(handle).ok_or(InventoryError::ItemNotFound);
+
+        for operation in self.operations.iter() {
+            match operation {
+                Operation::Add(entity, stack) => {
+                    let inventory = get_staged(&mut staged_inventories, *entity)?;
+                    inventory.add(stack.clone(), retrieve(&stack.item)?)?;
+                }
+                Operation::Remove(entity, stack) => {
+                    let inventory = get_staged(&mut staged_inventories, *entity)?;
+                    inventory.remove(stack, retrieve(&stack.item)?)?;
+                }
+                Operation::RemoveAny(entity, item, amount) => {
+                    let inventory = get_staged(&mut staged_inventories, *entity)?;
+                    inventory.remove_any(item, retrieve(item)?, *amount)?;
+                }
+                Operation::TransferAll(from, to) => {
+                    let stacks = get_staged(&mut staged_inventories, *from)?
+                        .drain()
+                        .collect::<Vec<_>>();
+                    let inventory = get_staged(&mut staged_inventories, *to)?;
+                    for stack in stacks {
+                        let retrieved_item = retrieve(&stack.item)?;
+                        inventory.add(stack, retrieved_item)?;
+                    }
+                }
+                Operation::Pay(from, to, amount) => {
+                    let mut from_credits = *get_staged(&mut staged_credits, *from)?;
+                    let mut to_credits = *get_staged(&mut staged_credits, *to)?;
+                    from_credits.transfer(&mut to_credits, *amount)?;
+                    // Paying yourself only needs the funds to exist
+                    if from != to {
+                        staged_credits.insert(*from, from_credits);
+                        staged_credits.insert(*to, to_credits);
+                    }
+                }
+            }
+        }
+
+        *inventories = staged_inventories;
+        *credits = staged_credits;
+        Ok(())
+    }
+
+    /// Apply the transaction to the components of entities in the world
+    pub fn commit(
+        &self,
+        inventories: &mut Query<&mut Inventory>,
+        credits: &mut Query<&mut Credits>,
+        items: &Assets<Item>,
+    ) -> Result<(), InventoryError> {
+        let mut staged_inventories = EntityHashMap::default();
+        let mut staged_credits = EntityHashMap::default();
+        for operation in self.operations.iter() {
+            let (inventory_entities, credit_entities) = match operation {
+                Operation::Add(entity, _)
+                | Operation::Remove(entity, _)
+                | Operation::RemoveAny(entity, _, _) => (vec![*entity], vec![]),
+                Operation::TransferAll(from, to) => (vec![*from, *to], vec![]),
+                Operation::Pay(from, to, _) => (vec![], vec![*from, *to]),
+            };
+            for entity in inventory_entities {
+                staged_inventories.insert(entity, inventories.get(entity)?.clone());
+            }
+            for entity in credit_entities {
+                staged_credits.insert(entity, *credits.get(entity)?);
+            }
+        }
+
+        self.apply(&mut staged_inventories, &mut staged_credits, items)?;
+
+        // Everything succeeded, so write the changes back
+        for (entity, inventory) in staged_inventories {
+            *inventories.get_mut(entity)? = inventory;
+        }
+        for (entity, staged) in staged_credits {
+            *credits.get_mut(entity)? = staged;
+        }
+        Ok(())
+    }
+}
+
+/// Get a staged component, erroring when the entity was never staged
+fn get_staged<T>(staged: &mut EntityHashMap<T>, entity: Entity) -> Result<&mut T, InventoryError> {
+    staged.get_mut(&entity).ok_or(InventoryError::Unqueriable)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_turborand::prelude::*;
+
+    /// Total amount of every item type across all inventories
+    fn totals(inventories: &EntityHashMap<Inventory>, handles: &[Handle<Item>]) -> Vec<usize> {
+        handles
+            .iter()
+            .map(|handle| inventories.values().map(|inv| inv.count(handle)).sum())
+            .collect()
+    }
+
+    /// Snapshot of every stack and credit balance, to compare states
+    fn snapshot(
+        inventories: &EntityHashMap<Inventory>,
+        credits: &EntityHashMap<Credits>,
+        entities: &[Entity],
+    ) -> Vec<(Vec<ItemStack>, usize)> {
+        entities
+            .iter()
+            .map(|entity| {
+                (
+                    inventories[entity].iter().cloned().collect(),
+                    credits[entity].get(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_transaction_is_atomic() {
+        let mut items = Assets::<Item>::default();
+        let ore = items.add(Item {
+            size: 1,
+            ..default()
+        });
+        let mut inventories = EntityHashMap::default();
+        let mut credits = EntityHashMap::default();
+        let (a, b) = (Entity::from_raw(0), Entity::from_raw(1));
+        inventories.insert(a, Inventory::with_capacity(10));
+        inventories.insert(b, Inventory::with_capacity(10));
+        credits.insert(a, Credits::new(100));
+        credits.insert(b, Credits::new(0));
+        inventories
+            .get_mut(&a)
+            .unwrap()
+            .add(ItemStack::new(ore.clone(), 5), items.get(&ore).unwrap())
+            .unwrap();
+
+        // Paying for more items than exist changes nothing
+        let result = InventoryTransaction::default()
+            .pay(a, b, 50)
+            .transfer(a, b, ItemStack::new(ore.clone(), 6))
+            .apply(&mut inventories, &mut credits, &items);
+        assert_eq!(
+            result,
+            Err(InventoryError::InsufficientItems {
+                want_to_remove: 6,
+                exists: 5
+            })
+        );
+        assert_eq!(credits[&a].get(), 100);
+        assert_eq!(inventories[&a].count(&ore), 5);
+
+        // A valid transaction changes everything
+        InventoryTransaction::default()
+            .pay(a, b, 50)
+            .transfer(a, b, ItemStack::new(ore.clone(), 5))
+            .apply(&mut inventories, &mut credits, &items)
+            .unwrap();
+        assert_eq!(credits[&b].get(), 50);
+        assert_eq!(inventories[&b].count(&ore), 5);
+        assert!(inventories[&a].is_empty());
+    }
+
+    #[test]
+    fn test_transactions_conserve_items_and_credits() {
+        let mut items = Assets::<Item>::default();
+        let handles = [(1, None), (2, Some(3)), (3, Some(1))]
+            .map(|(size, stack_size)| {
+                items.add(Item {
+                    size,
+                    stack_size,
+                    ..default()
+                })
+            })
+            .to_vec();
+        let metadata = [
+            ItemMetadata::default(),
+            ItemMetadata {
+                durability: Some(0.5),
+                ..default()
+            },
+        ];
+        let entities = (0..3).map(Entity::from_raw).collect::<Vec<_>>();
+
+        for seed in 0..200 {
+            let mut rng = Rng::with_seed(seed);
+            let mut inventories = EntityHashMap::default();
+            let mut credits = EntityHashMap::default();
+            for entity in entities.iter() {
+                let slots = rng.bool().then(|| rng.usize(1..6));
+                let mut inventory = Inventory::with_capacity(rng.usize(10..40)).with_slots(slots);
+                for _ in 0..rng.usize(0..6) {
+                    let handle = &handles[rng.usize(..handles.len())];
+                    let stack = ItemStack::new(handle.clone(), rng.usize(1..4))
+                        .with_metadata(metadata[rng.usize(..metadata.len())].clone());
+                    // Stacks that do not fit are skipped
+                    let _ = inventory.add(stack, items.get(handle).unwrap());
+                }
+                inventories.insert(*entity, inventory);
+                credits.insert(*entity, Credits::new(rng.usize(0..100)));
+            }
+
+            let item_totals = totals(&inventories, &handles);
+            let credit_total = credits.values().map(Credits::get).sum::<usize>();
+
+            for _ in 0..10 {
+                let mut transaction = InventoryTransaction::default();
+                for _ in 0..rng.usize(1..4) {
+                    let from = entities[rng.usize(..entities.len())];
+                    let to = entities[rng.usize(..entities.len())];
+                    transaction = match rng.usize(..3) {
+                        0 => transaction.pay(from, to, rng.usize(0..60)),
+                        1 => transaction.transfer_all(from, to),
+                        _ => transaction.transfer(
+                            from,
+                            to,
+                            ItemStack::new(
+                                handles[rng.usize(..handles.len())].clone(),
+                                rng.usize(1..5),
+                            )
+                            .with_metadata(metadata[rng.usize(..metadata.len())].clone()),
+                        ),
+                    };
+                }
+
+                let before = snapshot(&inventories, &credits, &entities);
+                if transaction
+                    .apply(&mut inventories, &mut credits, &items)
+                    .is_err()
+                {
+                    // Failed transactions change nothing
+                    assert_eq!(before, snapshot(&inventories, &credits, &entities));
+                }
+                assert_eq!(item_totals, totals(&inventories, &handles));
+                assert_eq!(
+                    credit_total,
+                    credits.values().map(Credits::get).sum::<usize>()
+                );
+                for inventory in inventories.values() {
+                    assert!(inventory.space_occupied() <= inventory.capacity());
+                    if let Some(slots) = inventory.slots() {
+                        assert!(inventory.iter().len() <= slots);
+                    }
+                }
+            }
+        }
+    }
+}
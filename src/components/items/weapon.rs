@@ -3,6 +3,8 @@ use std::time::Duration;
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use crate::prelude::*;
+
 /// A weapon that can fire
 #[derive(Debug, Clone, Reflect, Component, Serialize, Deserialize, PartialEq)]
 pub struct Weapon {
@@ -20,6 +22,99 @@ pub struct Weapon {
     pub last_fired: Duration,
     /// The specific weapon type will further influence how this weapon behaves
     pub weapon_type: WeaponType,
+    /// Which fire action fires this weapon, and how it responds to that action
+    #[serde(default)]
+    pub fire_control: FireControl,
+    /// If `true`, a weapon in [`FireMode::Toggle`] has been toggled on
+    #[serde(skip)]
+    pub toggled: bool,
+    /// The timestamp that a weapon in [`FireMode::Burst`] stops firing
+    #[serde(skip)]
+    pub burst_until: Duration,
+}
+
+impl Weapon {
+    /// Update `wants_to_fire` from the state of the fire action of this weapon's group
+    pub fn apply_trigger(&mut self, pressed: bool, just_pressed: bool, now: Duration) {
+        self.wants_to_fire = match self.fire_control.mode {
+            FireMode::Hold | FireMode::Alternating => pressed,
+            FireMode::Toggle => {
+                if just_pressed {
+                    self.toggled = !self.toggled;
+                }
+                self.toggled
+            }
+            FireMode::Burst(seconds) => {
+                if just_pressed {
+                    self.burst_until = now + Duration::from_secs_f32(seconds.max(0f32));
+                }
+                now < self.burst_until
+            }
+        };
+    }
+}
+
+/// Group of weapons fired together by one of the player's fire actions
+#[derive(Debug, Clone, Copy, Default, Reflect, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum WeaponGroup {
+    /// Fired by the primary fire action
+    #[default]
+    Primary,
+    /// Fired by the secondary fire action
+    Secondary,
+}
+
+/// How a weapon responds to the fire action of its group
+#[derive(Debug, Clone, Copy, Default, Reflect, Serialize, Deserialize, PartialEq)]
+pub enum FireMode {
+    /// Fire while the action is held
+    #[default]
+    Hold,
+    /// Start firing when the action is pressed, and stop when it is pressed again
+    Toggle,
+    /// Fire for this many seconds each time the action is pressed
+    Burst(f32),
+    /// Fire while the action is held, taking turns with identical weapons in the same group
+    Alternating,
+}
+
+impl FireMode {
+    /// Choose which of several identical [`FireMode::Alternating`] weapons fires next, from when
+    /// each of them last fired. The weapons share the recoil of one of them, so that their shots
+    /// are spread evenly: the weapon that fired longest ago may only fire once `recoil / n` has
+    /// passed since the last shot of any of them. Returns its index if it may fire now.
+    pub fn next_in_turn(last_fired: &[Duration], recoil: Duration, now: Duration) -> Option<usize> {
+        let last_shot = last_fired.iter().max()?;
+        let (next, _) = last_fired
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, last_fired)| **last_fired)?;
+        (now >= *last_shot + recoil / last_fired.len() as u32).then_some(next)
+    }
+}
+
+/// Group and fire mode of a weapon
+#[derive(Debug, Clone, Copy, Default, Reflect, Serialize, Deserialize, PartialEq)]
+pub struct FireControl {
+    /// Group the weapon belongs to
+    pub group: WeaponGroup,
+    /// How the weapon responds to the fire action of its group
+    pub mode: FireMode,
+}
+
+impl std::fmt::Display for WeaponGroup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::fmt::Display for FireMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FireMode::Burst(seconds) => write!(f, "Burst ({seconds}s)"),
+            mode => write!(f, "{:?}", mode),
+        }
+    }
 }
 /// Specific weapon type
 #[derive(Debug, Clone, Reflect, Serialize, Deserialize, PartialEq)]
@@ -41,6 +136,9 @@ pub enum WeaponType {
         shots: usize,
         /// Damage to inflict per shot
         damage: usize,
+        /// Type of damage inflicted by each shot
+        #[serde(default)]
+        damage_type: DamageType,
         /// Radius of the projectile to be fired
         radius: f32,
         /// Maximum lifetime in seconds
@@ -51,6 +149,12 @@ pub enum WeaponType {
         energy: usize,
         /// Model for projectile
         projectile_model: String,
+        /// If `Some`, shots explode on hit and damage everything in the blast
+        #[serde(default)]
+        blast: Option<Blast>,
+        /// Heat added every time the weapon fires, where 1 is overheated
+        #[serde(default)]
+        heat_per_shot: f32,
     },
     /// A laser
     LaserWeapon {
@@ -59,6 +163,9 @@ pub enum WeaponType {
         tracking: f32,
         /// Damage to inflict per second of contact
         damage_per_second: f32,
+        /// Type of damage inflicted by the beam
+        #[serde(default = "default_laser_damage_type")]
+        damage_type: DamageType,
         /// Energy to consume per second of firing
         energy_per_second: f32,
         /// Length of the laser
@@ -74,6 +181,68 @@ pub enum WeaponType {
         /// Beam color
         color: (f32, f32, f32),
     },
+    /// Guided missiles that lock on to a target and steer towards it
+    MissileWeapon {
+        /// Maximum (absolute) angle from 0-PI from forward that targets can be locked within
+        #[serde(default)]
+        tracking: f32,
+        /// Seconds a target must stay locked before a missile is launched
+        lock_on: f32,
+        /// Speed of the missile at launch
+        speed: f32,
+        /// Top speed of the missile
+        max_speed: f32,
+        /// Speed gained per second
+        acceleration: f32,
+        /// Maximum turn in radians per second
+        turn_rate: f32,
+        /// Duration between launches in seconds
+        recoil: f32,
+        /// Seconds of fuel. The missile is lost when it runs out.
+        fuel: f32,
+        /// Damage to inflict at the center of the blast
+        damage: usize,
+        /// Distance to the target at which the missile detonates
+        proximity: f32,
+        /// Shape of the explosion when the missile detonates
+        blast: Blast,
+        /// Hitpoints of each missile, so they can be shot down
+        health: usize,
+        /// Radius of the missile
+        radius: f32,
+        /// Energy consumed by each launch
+        energy: usize,
+        /// Model for the missile
+        projectile_model: String,
+    },
+}
+
+impl WeaponType {
+    /// Seconds between shots. Lasers fire continuously, and have none.
+    pub fn recoil(&self) -> f32 {
+        match self {
+            WeaponType::ProjectileWeapon { recoil, .. }
+            | WeaponType::MissileWeapon { recoil, .. } => *recoil,
+            WeaponType::LaserWeapon { .. } => 0f32,
+        }
+    }
+
+    /// Furthest distance this weapon can reach
+    pub fn range(&self) -> f32 {
+        match self {
+            WeaponType::ProjectileWeapon { distance, .. } => *distance,
+            WeaponType::LaserWeapon { range, .. } => *range,
+            // Missiles can travel as far as their fuel takes them at top speed
+            WeaponType::MissileWeapon {
+                max_speed, fuel, ..
+            } => max_speed * fuel,
+        }
+    }
+}
+
+/// Lasers inflict energy damage unless stated otherwise
+fn default_laser_damage_type() -> DamageType {
+    DamageType::Energy
 }
 
 /// Shot from a [`ProjectileWeapon`]
@@ -81,6 +250,38 @@ pub enum WeaponType {
 pub struct Projectile {
     /// Damage to inflict on hit
     pub damage: usize,
+    /// Type of damage inflicted on hit
+    pub damage_type: DamageType,
+    /// If `Some`, the projectile explodes on hit instead
+    pub blast: Option<Blast>,
+}
+
+/// Launched from a [`MissileWeapon`]
+#[derive(Clone, Reflect, Component)]
+pub struct Missile {
+    /// Entity the missile steers towards
+    pub target: Option<Entity>,
+    /// Top speed
+    pub max_speed: f32,
+    /// Speed gained per second
+    pub acceleration: f32,
+    /// Maximum turn in radians per second
+    pub turn_rate: f32,
+    /// Damage to inflict at the center of the blast
+    pub damage: usize,
+    /// Distance to the target at which the missile detonates
+    pub proximity: f32,
+    /// Shape of the explosion when the missile detonates
+    pub blast: Blast,
+}
+
+/// Added to a [`MissileWeapon`] that has a target in its sights
+#[derive(Clone, Copy, Reflect, Component)]
+pub struct LockOn {
+    /// Entity that is locked on to
+    pub target: Entity,
+    /// The timestamp that the lock began
+    pub since: Duration,
 }
 
 /// Fired from a [`LaserWeapon`]
@@ -88,8 +289,93 @@ pub struct Projectile {
 pub struct Laser {
     /// Damage to inflict per second on hit
     pub damage_per_second: f32,
+    /// Type of damage inflicted on hit
+    pub damage_type: DamageType,
     /// Length of the laser
     pub range: f32,
     /// Width of the laser
     pub width: f32,
 }
+
+#[cfg(test)]
+impl Weapon {
+    /// A laser with simple stats, shared by tests
+    pub(crate) fn test_laser() -> Self {
+        Weapon {
+            wants_to_fire: false,
+            firing: None,
+            target: None,
+            last_fired: Duration::ZERO,
+            weapon_type: WeaponType::LaserWeapon {
+                tracking: 0f32,
+                damage_per_second: 10f32,
+                damage_type: DamageType::Energy,
+                energy_per_second: 5f32,
+                range: 4f32,
+                width: 0.1f32,
+                activation_energy: 1f32,
+                heat_per_second: 1f32,
+                cooling_per_second: 1f32,
+                color: (1f32, 1f32, 1f32),
+            },
+            fire_control: FireControl::default(),
+            toggled: false,
+            burst_until: Duration::ZERO,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fire_modes() {
+        let mut weapon = Weapon::test_laser();
+        weapon.fire_control.mode = FireMode::Toggle;
+        let at = Duration::from_secs_f32;
+
+        // Toggled weapons keep firing after the action is released
+        weapon.apply_trigger(true, true, at(0f32));
+        assert!(weapon.wants_to_fire);
+        weapon.apply_trigger(false, false, at(1f32));
+        assert!(weapon.wants_to_fire);
+        weapon.apply_trigger(true, true, at(2f32));
+        assert!(!weapon.wants_to_fire);
+
+        // Bursts fire for their duration after a single press
+        weapon.fire_control.mode = FireMode::Burst(0.5);
+        weapon.apply_trigger(true, true, at(3f32));
+        weapon.apply_trigger(false, false, at(3.4));
+        assert!(weapon.wants_to_fire);
+        weapon.apply_trigger(false, false, at(3.6));
+        assert!(!weapon.wants_to_fire);
+
+        // Held weapons only fire while held
+        weapon.fire_control.mode = FireMode::Hold;
+        weapon.apply_trigger(true, false, at(4f32));
+        assert!(weapon.wants_to_fire);
+        weapon.apply_trigger(false, false, at(4.1));
+        assert!(!weapon.wants_to_fire);
+
+        // Alternating weapons fire in turn, spread evenly over the recoil of one of them
+        weapon.fire_control.mode = FireMode::Alternating;
+        weapon.apply_trigger(true, false, at(5f32));
+        assert!(weapon.wants_to_fire);
+        let recoil = at(1f32);
+        let mut last_fired = [at(4f32), at(4.5)];
+        assert_eq!(FireMode::next_in_turn(&last_fired, recoil, at(4.9)), None);
+        assert_eq!(
+            FireMode::next_in_turn(&last_fired, recoil, at(5f32)),
+            Some(0)
+        );
+        last_fired[0] = at(5f32);
+        // The other weapon is in turn, but not a frame later
+        assert_eq!(FireMode::next_in_turn(&last_fired, recoil, at(5.016)), None);
+        assert_eq!(
+            FireMode::next_in_turn(&last_fired, recoil, at(5.5)),
+            Some(1)
+        );
+        assert_eq!(FireMode::next_in_turn(&[], recoil, at(6f32)), None);
+    }
+}
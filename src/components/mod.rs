@@ -6,6 +6,8 @@ mod controller;
 mod craft;
 /// Money handling
 mod credits;
+/// Explosions and the things that cause them
+mod explosion;
 /// Factions
 mod faction;
 /// Item components, inventory, equipment, and credits
@@ -17,6 +19,14 @@ mod structure;
 /// Utility components that do not fit in any specific grouping
 mod utility;
 pub use {
-    ai::*, controller::Controller, craft::*, credits::*, faction::*, items::*, player::*,
-    structure::*, utility::*,
+    ai::*,
+    controller::{Controller, Handling},
+    craft::*,
+    credits::*,
+    explosion::*,
+    faction::*,
+    items::*,
+    player::*,
+    structure::*,
+    utility::*,
 };
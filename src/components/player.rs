@@ -7,3 +7,9 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone, Copy, Component, Reflect, Serialize, Deserialize, DerefMut, Deref)]
 #[reflect(Component, Serialize, Deserialize)]
 pub struct Player(pub usize);
+
+/// Where the player comes back after being destroyed. This is the last station the player docked
+/// at, or where the player arrived in the current zone.
+#[derive(Debug, Clone, Copy, Component, Reflect, Serialize, Deserialize, Default, Deref)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct RespawnPoint(pub Vec2);
@@ -3,6 +3,10 @@
 use std::time::Duration;
 
 use bevy::{
+    ecs::{
+        entity::{EntityMapper, MapEntities},
+        reflect::ReflectMapEntities,
+    },
     prelude::*,
     utils::{HashMap, HashSet},
 };
@@ -15,10 +19,25 @@ use crate::prelude::*;
 #[reflect(Component, Serialize, Deserialize)]
 pub struct Structure;
 
+/// Jump gate that sends the player to another zone of the [`Universe`]
+#[derive(Component, Reflect, Serialize, Deserialize, Debug, Clone, Copy)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct Gate {
+    /// Index of the zone the gate leads to
+    pub destination: usize,
+}
+
 /// Added when a craft is docked, and removed when undocked
-#[derive(Component, Reflect, Deref, DerefMut)]
+#[derive(Component, Reflect, Deref, DerefMut, Serialize, Deserialize, Clone, Copy)]
+#[reflect(Component, MapEntities, Serialize, Deserialize)]
 pub struct Docked(pub Entity);
 
+impl MapEntities for Docked {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        self.0 = entity_mapper.map_entity(self.0);
+    }
+}
+
 /// Updated to determine the nearest dockable structure
 /// Stores a dockable entity in range
 #[derive(Component, Reflect, Serialize, Deserialize, Clone, Copy)]
@@ -44,17 +63,74 @@ pub struct Spawner {
     /// The timestamp of the last tick
     #[serde(skip)]
     pub last_tick: Duration,
+    /// Seconds until a destroyed spawner is rebuilt, or `None` if it stays destroyed
+    #[serde(default)]
+    pub rebuild: Option<f32>,
 }
 
-/// Used to track the maximum created from our spawner
-#[derive(Component, Reflect, Serialize, Deserialize)]
+/// Added to a destroyed [`Spawner`] while it is rebuilt. Spawning resumes once it is finished.
+#[derive(Component, Reflect, Serialize, Deserialize, Clone, Copy)]
 #[reflect(Component, Serialize, Deserialize)]
+pub struct Rebuilding {
+    /// Seconds until the spawner is rebuilt
+    pub remaining: f32,
+}
+
+/// Used to track the maximum created from our spawner
+#[derive(Component, Reflect, Serialize, Deserialize, Clone, Copy)]
+#[reflect(Component, MapEntities, Serialize, Deserialize)]
 pub struct SpawnedFrom(pub Entity);
 
-/// Can dock at this. Maps a docked entity to its constraint entity
+impl MapEntities for SpawnedFrom {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        self.0 = entity_mapper.map_entity(self.0);
+    }
+}
+
+/// Can dock at this. Maps a docked entity to its constraint entity. Constraints are not saved,
+/// so after loading they refer to dead entities until they are rebuilt.
 #[derive(Component, Reflect, Default, Deref, DerefMut, Serialize, Deserialize, Clone)]
+#[reflect(Component, MapEntities, Serialize, Deserialize)]
+pub struct Dockings(pub HashMap<Entity, Entity>);
+
+impl MapEntities for Dockings {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        self.0 = self
+            .0
+            .drain()
+            .map(|(docked, joint)| {
+                (
+                    entity_mapper.map_entity(docked),
+                    entity_mapper.map_entity(joint),
+                )
+            })
+            .collect();
+    }
+}
+
+/// Marks a building as a fabricator that can turn items into other items with a [`Recipe`], if
+/// docked
+#[derive(Component, Reflect, Serialize, Deserialize, Debug, Clone, Copy)]
 #[reflect(Component, Serialize, Deserialize)]
-pub struct Dockings(#[reflect(skip_serializing)] pub HashMap<Entity, Entity>);
+pub struct Fabricator {
+    /// Most recipes a single patron can have queued at once
+    pub queue_size: usize,
+}
+
+/// Recipes being fabricated for an entity, which are delivered into its inventory once finished.
+/// Only the first job in the queue makes progress.
+#[derive(Component, Reflect, Serialize, Deserialize, Debug, Clone, Default, Deref, DerefMut)]
+#[reflect(Component, Serialize, Deserialize)]
+pub struct FabricationQueue(pub Vec<FabricationJob>);
+
+/// A recipe waiting to be finished in a [`FabricationQueue`]
+#[derive(Reflect, Serialize, Deserialize, Debug, Clone)]
+pub struct FabricationJob {
+    /// Name of the recipe
+    pub recipe: String,
+    /// Seconds until the recipe is finished
+    pub remaining: f32,
+}
 
 /// Marks a building as a store that can be traded with, if docked
 #[derive(Component, Default, Reflect)]
@@ -1,12 +1,26 @@
 use std::time::Duration;
 
-use bevy::{asset::AssetPath, prelude::*};
+use bevy::{
+    asset::AssetPath,
+    ecs::{
+        entity::{EntityMapper, MapEntities},
+        reflect::ReflectMapEntities,
+    },
+    prelude::*,
+};
 use serde::{Deserialize, Serialize};
 
 /// Hostile target to attack
-#[derive(Component, Reflect, Serialize, Deserialize)]
+#[derive(Component, Reflect, Serialize, Deserialize, Clone, Copy)]
+#[reflect(Component, MapEntities, Serialize, Deserialize)]
 pub struct Target(pub Entity);
 
+impl MapEntities for Target {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        self.0 = entity_mapper.map_entity(self.0);
+    }
+}
+
 /// Despawns an entity after a specified length of time
 #[derive(Component, Reflect, Serialize, Deserialize)]
 #[reflect(Component, Serialize, Deserialize)]
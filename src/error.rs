@@ -20,6 +20,12 @@ pub enum GameError {
     /// Store error
     #[error(transparent)]
     StoreError(#[from] StoreError),
+    /// Save or load error
+    #[error(transparent)]
+    SaveError(#[from] SaveError),
+    /// Fabrication error
+    #[error(transparent)]
+    FabricationError(#[from] FabricationError),
 }
 /// Energy error
 #[derive(Error, Debug)]
@@ -51,6 +57,12 @@ pub enum InventoryError {
         /// Space over the maximum
         overage: usize,
     },
+    /// Every slot of a slot based inventory is in use
+    #[error("adding item(s) to the inventory would need `{needed}` more slots")]
+    NoSlotsLeft {
+        /// Slots needed beyond the free slots
+        needed: usize,
+    },
     /// More items requested than this inventory contains
     #[error("attempted to remove `{want_to_remove}` when `{exists}` exists")]
     InsufficientItems {
@@ -71,6 +83,9 @@ pub enum InventoryError {
     /// No item found with given handle
     #[error("could not find requested item with handle")]
     ItemNotFound,
+    /// Credits error, from a transaction that also moves credits
+    #[error(transparent)]
+    CreditsError(#[from] CreditsError),
     /// Query entity error
     #[error(transparent)]
     QueryEntityError(#[from] QueryEntityError),
@@ -96,6 +111,34 @@ pub enum StoreError {
     NotEnoughCredits,
 }
 
+/// Fabrication error
+#[derive(Debug, Error)]
+pub enum FabricationError {
+    /// No recipe with the given name
+    #[error("could not find recipe `{0}`")]
+    RecipeNotFound(String),
+    /// Recipes can only be queued while docked with the fabricator
+    #[error("must be docked with the fabricator to queue a recipe")]
+    NotDocked,
+    /// The building is not a fabricator
+    #[error("the building is not a fabricator")]
+    NotAFabricator,
+    /// The recipe belongs to another building
+    #[error("recipe `{recipe}` can only be fabricated at a `{building}`")]
+    WrongBuilding {
+        /// Recipe name
+        recipe: String,
+        /// Name of the building the recipe needs
+        building: String,
+    },
+    /// The patron already has as many recipes queued as the fabricator allows
+    #[error("no more than `{0}` recipes can be queued")]
+    QueueFull(usize),
+    /// Inventory error
+    #[error(transparent)]
+    InventoryError(#[from] InventoryError),
+}
+
 /// Equipment error
 #[derive(Debug, Error)]
 pub enum EquipmentError {
@@ -111,3 +154,45 @@ pub enum EquipmentError {
     #[error("parent of specified equip doesn't exist or is invalid")]
     InvalidEquipParent,
 }
+
+/// Save and load error
+#[derive(Debug, Error)]
+pub enum SaveError {
+    /// The save was written by a newer version of the game than this one understands
+    #[error("save format version `{version}` is newer than the supported version `{supported}`")]
+    TooNew {
+        /// Version found in the save header
+        version: u32,
+        /// Newest version this build can read
+        supported: u32,
+    },
+    /// No migration is registered to upgrade a save from this version
+    #[error("no migration exists to upgrade a save from format version `{version}`")]
+    Unmigratable {
+        /// Version that could not be upgraded
+        version: u32,
+    },
+    /// A migration ran but could not upgrade the save
+    #[error("failed to migrate save from format version `{version}`: {reason}")]
+    MigrationFailed {
+        /// Version the migration was upgrading from
+        version: u32,
+        /// Description of the failure
+        reason: String,
+    },
+    /// A saved component or resource type is not registered with the app
+    #[error("type `{0}` in the save is not registered")]
+    UnregisteredType(String),
+    /// File system error
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// Malformed RON while reading a save
+    #[error(transparent)]
+    Parse(#[from] ron::error::SpannedError),
+    /// RON (de)serialization error
+    #[error(transparent)]
+    Ron(#[from] ron::Error),
+    /// A saved scene could not be written into the world
+    #[error(transparent)]
+    Spawn(#[from] bevy::scene::SceneSpawnError),
+}
@@ -14,7 +14,10 @@ pub struct AiPlugin;
 
 impl Plugin for AiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (update_in_range, move_towards_waypoint))
+        app.register_saveable::<InRange>()
+            .register_saveable::<Target>()
+            .register_saveable::<Waypoint>()
+            .add_systems(Update, (update_in_range, move_towards_waypoint))
             // Scorers
             .add_systems(
                 PreUpdate,
@@ -61,12 +61,7 @@ pub(crate) fn target_in_range_scorer(
             // get the weapon with the longest range by distance
             if let Some(weapons) = maybe_weapons {
                 let longest_range = weapons.fold(0f32, |acc, b| {
-                    let b_range = match b.weapon_type {
-                        WeaponType::ProjectileWeapon { distance, .. }
-                        | WeaponType::LaserWeapon {
-                            range: distance, ..
-                        } => distance,
-                    };
+                    let b_range = b.weapon_type.range();
                     if b_range > acc {
                         b_range
                     } else {
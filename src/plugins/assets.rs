@@ -24,6 +24,8 @@ impl Plugin for AssetsPlugin {
             RonAssetPlugin::<Creature>::new(&["creature.ron"]),
             RonAssetPlugin::<Craft>::new(&["craft.ron"]),
             RonAssetPlugin::<Building>::new(&["building.ron"]),
+            RonAssetPlugin::<Biome>::new(&["biome.ron"]),
+            RonAssetPlugin::<Recipe>::new(&["recipe.ron"]),
         ))
         // Continue to the main game state once everything is loaded in, so
         // we can be sure all assets are loaded first
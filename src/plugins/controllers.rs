@@ -1,6 +1,6 @@
 use crate::prelude::*;
 use avian3d::prelude::{
-    AngularVelocity, ExternalAngularImpulse, ExternalImpulse, LinearDamping, LinearVelocity,
+    AngularVelocity, ExternalAngularImpulse, LinearDamping, LinearVelocity, Mass,
 };
 use bevy::prelude::*;
 
@@ -9,27 +9,113 @@ pub struct ControllersPlugin;
 
 impl Plugin for ControllersPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
+        app.register_saveable::<Controller>().add_systems(
             Update,
             (
+                add_handling,
+                add_power_distribution,
+                // New crafts need their handling and power before they can move in the same frame
+                apply_deferred,
+                update_handling,
                 apply_controller_movement,
-                apply_craft_physics.after(apply_controller_movement),
+                apply_craft_physics,
             )
+                .chain()
                 .run_if(in_state(AppState::main())),
         );
     }
 }
 
-/// Apply input to the controllers
+/// Handling is not saved, so crafts are given one whenever they are spawned or loaded
+fn add_handling(mut cmd: Commands, crafts: Query<(Entity, &Craft, &Mass), Without<Handling>>) {
+    for (entity, craft, mass) in crafts.iter() {
+        cmd.entity(entity)
+            .insert(Handling::new(craft, **mass, std::iter::empty()));
+    }
+}
+
+/// Crafts from saves written before power could be distributed are given an even distribution
+fn add_power_distribution(
+    mut cmd: Commands,
+    crafts: Query<Entity, (With<Craft>, Without<PowerDistribution>)>,
+) {
+    for entity in crafts.iter() {
+        cmd.entity(entity).insert(PowerDistribution::default());
+    }
+}
+
+/// Work out the [`Handling`] of crafts from their [`Engine`]s and mass
+///
+/// # System overview
+///
+/// 1. Find the engines of each craft
+/// 2. Burn afterburners while the craft is thrusting and its controller asks for them. Burning
+///    draws energy for the engines and heats up the engine. Starved afterburners burn with the
+///    fraction of the energy they were given, and overheated engines cannot burn at all.
+/// 3. Add the modifiers of every engine on top of the stats of the craft
+fn update_handling(
+    mut crafts: Query<
+        (
+            &Craft,
+            &Mass,
+            &Controller,
+            &mut Handling,
+            &mut Energy,
+            &mut PowerDistribution,
+            Option<&Children>,
+        ),
+        Without<Destroyed>,
+    >,
+    mut engines: Query<(&Engine, &mut Heat, Option<&Overheated>)>,
+    time: Res<Time>,
+) {
+    let (now, dt) = (time.elapsed(), time.delta_seconds());
+    for (craft, mass, controller, mut handling, mut energy, mut power, maybe_children) in
+        crafts.iter_mut()
+    {
+        let burning = controller.afterburner && controller.thrust > 0f32;
+        let mut equipped = Vec::new();
+        for child in maybe_children
+            .into_iter()
+            .flat_map(|children| children.iter())
+        {
+            let Ok((engine, mut heat, maybe_overheated)) = engines.get_mut(*child) else {
+                continue;
+            };
+            let mut burn = 0f32;
+            if let (true, None, Some(afterburner)) = (burning, maybe_overheated, engine.afterburner)
+            {
+                let demand = afterburner.energy_per_second * dt;
+                burn = match demand > 0f32 {
+                    true => power.draw(&mut energy, PowerSystem::Engines, demand, now) / demand,
+                    false => 1f32,
+                };
+                *heat += afterburner.heat_per_second * burn * dt;
+            }
+            equipped.push((engine.clone(), burn));
+        }
+        handling.set_if_neq(Handling::new(
+            craft,
+            **mass,
+            equipped.iter().map(|(engine, burn)| (engine, *burn)),
+        ));
+    }
+}
+
+/// Apply input to the controllers. Thrust draws [`Energy`] for the engines, and is weaker when
+/// they are starved.
 fn apply_controller_movement(
     mut characters: Query<
         (
             &Transform,
             &Craft,
-            &mut Controller,
-            &mut ExternalImpulse,
+            &Handling,
+            &Controller,
+            &mut LinearVelocity,
             &mut AngularVelocity,
             &mut LinearDamping,
+            &mut Energy,
+            &mut PowerDistribution,
         ),
         Without<Destroyed>,
     >,
@@ -37,10 +123,28 @@ fn apply_controller_movement(
 ) {
     let dt = time.delta_seconds();
     characters.iter_mut().for_each(
-        |(transform, craft, controller, mut velocity, mut angular, mut damping)| {
-            **velocity += controller.thrust * transform.forward() * dt * craft.acceleration;
-            **angular = controller.angular_thrust * -Vec3::Z * dt * craft.rotation;
-            **damping = controller.brake * dt * craft.brake;
+        |(
+            transform,
+            craft,
+            handling,
+            controller,
+            mut velocity,
+            mut angular,
+            mut damping,
+            mut energy,
+            mut power,
+        )| {
+            let demand = controller.thrust * craft.thrust_energy * dt;
+            let supplied = match demand > 0f32 {
+                true => {
+                    power.draw(&mut energy, PowerSystem::Engines, demand, time.elapsed()) / demand
+                }
+                false => 1f32,
+            };
+            **velocity +=
+                controller.thrust * supplied * transform.forward() * dt * handling.acceleration;
+            **angular = controller.angular_thrust * -Vec3::Z * dt * handling.rotation;
+            **damping = controller.brake * dt * handling.brake;
         },
     );
 }
@@ -48,18 +152,18 @@ fn apply_controller_movement(
 /// Apply limitations of a particular craft
 fn apply_craft_physics(
     mut crafts: Query<
-        (&mut LinearVelocity, &mut ExternalAngularImpulse, &Craft),
+        (&mut LinearVelocity, &mut ExternalAngularImpulse, &Handling),
         Without<Destroyed>,
     >,
     time: Res<Time>,
 ) {
     crafts
         .iter_mut()
-        .for_each(|(mut velocity, mut angular_velocity, craft)| {
+        .for_each(|(mut velocity, mut angular_velocity, handling)| {
             // Clamp to max speed
-            **velocity = velocity.clamp_length_max(craft.speed);
+            **velocity = velocity.clamp_length_max(handling.speed);
             // Clamp to max rotation
             **angular_velocity =
-                angular_velocity.clamp_length_max(craft.rotation * time.delta_seconds());
+                angular_velocity.clamp_length_max(handling.rotation * time.delta_seconds());
         });
 }
@@ -7,31 +7,67 @@ use crate::prelude::*;
 pub struct EquipmentPlugin;
 impl Plugin for EquipmentPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            (
-                handle_repairs,
-                handle_energy,
-                manage_overheating,
-                manage_equipped_builders.run_if(resource_exists::<Library>),
+        app.register_saveable::<Energy>()
+            .register_saveable::<PowerDistribution>()
+            .register_saveable::<Heat>()
+            .register_saveable_with::<EquippedBuilder>(save_equipped)
+            .add_systems(
+                Update,
+                (
+                    handle_repairs,
+                    (handle_energy, handle_shields).chain(),
+                    (handle_radiators, manage_overheating).chain(),
+                    manage_equipped_builders.run_if(resource_exists::<Library>),
+                )
+                    .run_if(in_state(AppState::main())),
             )
-                .run_if(in_state(AppState::main())),
-        )
-        .observe(on_equip.pipe(handle_errors::<EquipmentError>))
-        .observe(on_unequip.pipe(handle_errors::<EquipmentError>));
+            .observe(on_equip.pipe(handle_errors::<EquipmentError>))
+            .observe(on_unequip.pipe(handle_errors::<EquipmentError>));
     }
 }
 
-/// Repair damage for entities with a [`RepairBot`] equipped
+/// Save [`Equipped`] as an [`EquippedBuilder`]. Getting equipment is somewhat indirect since
+/// the individual `Equipment` entities contain the name of the equipment.
+fn save_equipped(world: &World, entity: Entity) -> Option<Box<dyn Reflect>> {
+    let equipped = world.get::<Equipped>(entity)?;
+    let (equipped_string, metadata) = equipped
+        .iter()
+        .flat_map(|(_, entities)| entities.iter())
+        .filter_map(|entity| Some((world.get::<Equipment>(*entity)?, world.get::<Heat>(*entity))))
+        .filter_map(|(equipment, heat)| {
+            let path = equipment.handle().path()?.to_string();
+            let name = path.replace("items/", "").replace(".ron", "");
+            // Hot equipment stays hot when loaded
+            let metadata = ItemMetadata {
+                heat: heat.map(Heat::get).filter(|heat| *heat > 0f32),
+                ..equipment.metadata().clone()
+            };
+            Some((name, metadata))
+        })
+        .unzip();
+    Some(Box::new(EquippedBuilder {
+        equipped: equipped_string,
+        metadata,
+        slots: equipped.slots.clone().into_iter().collect(),
+    }))
+}
+
+/// Repair damage for entities with a [`RepairBot`] equipped. Repair bots heat up while
+/// repairing, and do nothing while overheated.
 fn handle_repairs(
     mut damages: Query<(&mut Damage, &Children), Without<Destroyed>>,
-    repairs: Query<&RepairBot>,
+    mut repairs: Query<(&RepairBot, &mut Heat), Without<Overheated>>,
     time: Res<Time>,
 ) {
     for (mut damage, children) in damages.iter_mut() {
         if **damage != 0f32 {
-            let repairs = children.iter().filter_map(|e| repairs.get(*e).ok());
-            let repairs_rate = repairs.fold(0f32, |acc, i| acc + i.rate);
+            let mut repairs_rate = 0f32;
+            for child in children.iter() {
+                if let Ok((repair, mut heat)) = repairs.get_mut(*child) {
+                    repairs_rate += repair.rate;
+                    *heat += repair.heat_per_second * time.delta_seconds();
+                }
+            }
             let new_damage = (**damage - (repairs_rate * time.delta_seconds())).max(0f32); // It's OK if we go over damage as it'll just destroy the entity
             **damage = new_damage;
         }
@@ -46,28 +82,69 @@ fn handle_repairs(
 /// 2. Find all generator and battery children (TODO: change to new equips tracking system)
 /// 3. Charge batteries (parent energy) with the rate specified in the generator (Note that batteries actually just determine
 ///     total potential energy storage, which is what the [`Energy`] component on the parent tracks)
+/// 4. Heat up generators while they are charging. Overheated generators do not generate anything.
+/// 5. Update the capacity that the reserves of the [`PowerDistribution`] are a share of
 fn handle_energy(
-    mut energies: Query<(&mut Energy, &Children), Without<Destroyed>>,
-    generators: Query<&Generator>,
+    mut energies: Query<
+        (&mut Energy, &Children, Option<&mut PowerDistribution>),
+        Without<Destroyed>,
+    >,
+    mut generators: Query<(&Generator, &mut Heat), Without<Overheated>>,
     batteries: Query<&Battery>,
     time: Res<Time>,
 ) {
     // Find base entities with energy components so we can loop through children (items)
-    for (mut energy, children) in energies.iter_mut() {
-        let generators = children.iter().filter_map(|e| generators.get(*e).ok());
+    for (mut energy, children, maybe_power) in energies.iter_mut() {
         let batteries = children.iter().filter_map(|e| batteries.get(*e).ok());
 
         // Find our maximum energy. If no batteries exist, we'll use the generator per second instead
-        let recharge_rate = generators.fold(0f32, |acc, i| acc + i.recharge_rate);
+        let recharge_rate = children
+            .iter()
+            .filter_map(|e| generators.get(*e).ok())
+            .fold(0f32, |acc, (i, _)| acc + i.recharge_rate);
         let mut max_energy = batteries.fold(0f32, |acc, i| acc + i.capacity());
         if max_energy == 0f32 {
             // ugly imperative code :(
             max_energy = recharge_rate;
         }
 
+        // Generators only heat up while there is room left to charge
+        if energy.charge() < max_energy {
+            for child in children.iter() {
+                if let Ok((generator, mut heat)) = generators.get_mut(*child) {
+                    *heat += generator.heat_per_second * time.delta_seconds();
+                }
+            }
+        }
+
         // Add the charge to our `Energy` component
         *energy += (recharge_rate * time.delta_seconds()).into();
         energy.clamp(max_energy);
+
+        if let Some(mut power) = maybe_power {
+            power.set_capacity(max_energy);
+        }
+    }
+}
+
+/// Recharge [`Shield`]s with the [`Energy`] their parent distributes to them
+fn handle_shields(
+    mut energies: Query<(&mut Energy, &mut PowerDistribution, &Children), Without<Destroyed>>,
+    mut shields: Query<&mut Shield>,
+    time: Res<Time>,
+) {
+    let (now, delta) = (time.elapsed(), time.delta_seconds());
+    for (mut energy, mut power, children) in energies.iter_mut() {
+        for child in children.iter() {
+            if let Ok(mut shield) = shields.get_mut(*child) {
+                let demand = shield.demand(now, delta);
+                let mut supply =
+                    Energy::from(power.draw(&mut energy, PowerSystem::Shields, demand, now));
+                shield.recharge(&mut supply, now, delta);
+                // Return anything the shield did not use
+                *energy += supply;
+            }
+        }
     }
 }
 
@@ -75,12 +152,14 @@ fn on_equip(
     trigger: Trigger<triggers::Equip>,
     mut cmd: Commands,
     mut inventories: Query<&mut Inventory>,
+    mut credits: Query<&mut Credits>,
     items: Res<Assets<Item>>,
     equipped: Query<&Equipped>,
 ) -> Result<(), EquipmentError> {
     let triggers::Equip {
         entity: parent_entity,
         item,
+        metadata,
         transfer_from_inventory,
     } = trigger.event();
 
@@ -93,15 +172,17 @@ fn on_equip(
     if equipped.slots_remaining(&id) != 0 {
         if *transfer_from_inventory {
             // Remove item from inventory
-            let mut inventory = inventories.get_mut(*parent_entity)?;
-            inventory.remove(item, retrieved_item.size, 1)?;
+            let stack = ItemStack::new(item.clone(), 1).with_metadata(metadata.clone());
+            InventoryTransaction::default()
+                .remove(*parent_entity, stack)
+                .commit(&mut inventories, &mut credits, &items)?;
         }
         // Add equip as a child
         let equipment_entity = cmd.spawn(()).id();
         cmd.entity(*parent_entity).add_child(equipment_entity);
         cmd.entity(equipment_entity)
             .set_parent(*parent_entity)
-            .insert(Equipment::new(item.clone()));
+            .insert(Equipment::new(item.clone()).with_metadata(metadata.clone()));
     } else {
         return Err(EquipmentError::SlotNotAvailable);
     }
@@ -112,6 +193,7 @@ fn on_unequip(
     trigger: Trigger<triggers::Unequip>,
     mut cmd: Commands,
     mut inventories: Query<&mut Inventory>,
+    mut credits: Query<&mut Credits>,
     equipments: Query<&Equipment>,
     items: Res<Assets<Item>>,
     parents: Query<&Parent>,
@@ -122,13 +204,20 @@ fn on_unequip(
     } = trigger.event();
     if *transfer_into_inventory {
         let eq = equipments.get(*equipment)?;
-        let retrieved_item = items
-            .get(&eq.handle())
-            .ok_or(InventoryError::ItemNotFound)?;
         // get parent entity
         let entity = parents.get(*equipment)?.get();
-        let mut inventory = inventories.get_mut(entity)?;
-        inventory.add(eq.handle(), retrieved_item.size, 1)?;
+        // Fire settings and heat only apply while the item is equipped
+        let metadata = ItemMetadata {
+            fire_control: None,
+            heat: None,
+            ..eq.metadata().clone()
+        };
+        let stack = ItemStack::new(eq.handle(), 1).with_metadata(metadata);
+        InventoryTransaction::default().add(entity, stack).commit(
+            &mut inventories,
+            &mut credits,
+            &items,
+        )?;
     }
     cmd.entity(*equipment).despawn_recursive();
     Ok(())
@@ -151,7 +240,7 @@ fn manage_equipped_builders(
         // Child hooks will automatically update the equipment slots,
         // but we do assume the `EquippedBuilder` defines a valid
         // configuration
-        for item in &builder.equipped {
+        for (i, item) in builder.equipped.iter().enumerate() {
             // Spawn the equipment entity
             let equipment_entity = cmd.spawn(()).id();
             // get the item from the string
@@ -160,14 +249,42 @@ fn manage_equipped_builders(
             // it won't work, see: https://github.com/bevyengine/bevy/issues/14545
             cmd.entity(parent_entity).add_child(equipment_entity);
             cmd.entity(equipment_entity).set_parent(parent_entity);
+            let metadata = builder.metadata.get(i).cloned().unwrap_or_default();
             cmd.entity(equipment_entity)
-                .insert(Equipment::new(item_handle.clone()));
+                .insert(Equipment::new(item_handle.clone()).with_metadata(metadata));
         }
 
         cmd.entity(parent_entity).remove::<EquippedBuilder>();
     }
 }
 
+/// Cool the equipment of every entity passively and with its [`Radiator`]s. Equipment that is not
+/// hot is left alone, so its heat is not needlessly changed.
+fn handle_radiators(
+    parents: Query<&Children, (With<Equipped>, Without<Destroyed>)>,
+    radiators: Query<&Radiator>,
+    mut heats: Query<&mut Heat, Without<Radiator>>,
+    time: Res<Time>,
+) {
+    for children in parents.iter() {
+        let hot = children
+            .iter()
+            .filter(|child| heats.get(**child).is_ok_and(|heat| heat.get() > 0f32))
+            .copied()
+            .collect::<Vec<_>>();
+        let radiators = children
+            .iter()
+            .filter_map(|child| radiators.get(*child).ok());
+        let cooling = Heat::PASSIVE_COOLING * time.delta_seconds()
+            + Radiator::share(radiators, hot.len(), time.delta_seconds());
+        for child in hot {
+            if let Ok(mut heat) = heats.get_mut(child) {
+                *heat += -cooling;
+            }
+        }
+    }
+}
+
 fn manage_overheating(
     mut cmd: Commands,
     heats: Query<(Entity, &Heat, Option<&Overheated>), Changed<Heat>>,
@@ -182,3 +299,46 @@ fn manage_overheating(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use std::time::Duration;
+
+    #[test]
+    fn test_handle_radiators() {
+        let mut world = World::new();
+        let mut time = Time::<()>::default();
+        time.advance_by(Duration::from_secs(1));
+        world.insert_resource(time);
+
+        let spawn_heat = |world: &mut World, heat: f32| world.spawn(Heat::from(heat)).id();
+        let hot = [spawn_heat(&mut world, 0.5), spawn_heat(&mut world, 0.8)];
+        let cold = spawn_heat(&mut world, 0f32);
+        let radiator = world
+            .spawn((Radiator { dissipation: 0.3 }, Heat::from(0.5)))
+            .id();
+        let crafts = [
+            world.spawn(Equipped::default()).id(),
+            world.spawn(Equipped::default()).id(),
+        ];
+        world
+            .entity_mut(crafts[0])
+            .push_children(&[hot[0], hot[1], cold, radiator]);
+        // Without a radiator, equipment still cools passively
+        let alone = spawn_heat(&mut world, 0.5);
+        world.entity_mut(crafts[1]).push_children(&[alone]);
+
+        world.run_system_once(handle_radiators);
+        let heat = |entity: Entity| world.get::<Heat>(entity).unwrap().get();
+        // The radiator is shared by the two hot pieces of equipment
+        let cooling = Heat::PASSIVE_COOLING + 0.3 / 2f32;
+        assert!((heat(hot[0]) - (0.5 - cooling)).abs() < 1e-5);
+        assert!((heat(hot[1]) - (0.8 - cooling)).abs() < 1e-5);
+        assert_eq!(heat(cold), 0f32);
+        assert!((heat(alone) - (0.5 - Heat::PASSIVE_COOLING)).abs() < 1e-5);
+        // Radiators do not cool themselves
+        assert_eq!(heat(radiator), 0.5);
+    }
+}
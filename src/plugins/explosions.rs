@@ -0,0 +1,186 @@
+use std::time::Duration;
+
+use super::weapons::{manage_hits, manage_projectile_collisions};
+use crate::prelude::*;
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use bevy_hanabi::prelude::*;
+
+/// Damage, knockback and effects for [`events::Explosion`]s
+pub struct ExplosionsPlugin;
+
+impl Plugin for ExplosionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<events::Explosion>()
+            .register_saveable::<Explosive>()
+            .add_systems(Startup, setup_explosion_effect)
+            .add_systems(
+                Update,
+                (explode_destroyed, manage_explosions)
+                    .chain()
+                    // Explosive shots explode, and their hits are applied, in the same frame
+                    .after(manage_projectile_collisions)
+                    .before(manage_hits)
+                    .run_if(in_state(AppState::main())),
+            );
+    }
+}
+
+/// Particle effect spawned at every explosion
+#[derive(Resource)]
+struct ExplosionEffect(Handle<EffectAsset>);
+
+/// Seconds an explosion effect is kept before it is despawned
+const EFFECT_LIFETIME: f32 = 1.5;
+
+fn setup_explosion_effect(mut cmd: Commands, mut effects: ResMut<Assets<EffectAsset>>) {
+    let mut color = Gradient::new();
+    color.add_key(0.0, Vec4::new(8.0, 4.0, 1.0, 1.0));
+    color.add_key(0.4, Vec4::new(4.0, 1.0, 0.2, 0.8));
+    color.add_key(1.0, Vec4::new(0.5, 0.1, 0.0, 0.0));
+
+    let mut size = Gradient::new();
+    size.add_key(0.0, Vec2::splat(0.2));
+    size.add_key(1.0, Vec2::splat(0.0));
+
+    let writer = ExprWriter::new();
+    let init_pos = SetPositionSphereModifier {
+        center: writer.lit(Vec3::ZERO).expr(),
+        radius: writer.lit(0.1).expr(),
+        dimension: ShapeDimension::Volume,
+    };
+    // Particles fly out to roughly the radius of a unit blast before fading
+    let init_vel = SetVelocitySphereModifier {
+        center: writer.lit(Vec3::ZERO).expr(),
+        speed: (writer.rand(ScalarType::Float) * writer.lit(2.) + writer.lit(1.)).expr(),
+    };
+    let init_lifetime = SetAttributeModifier::new(
+        Attribute::LIFETIME,
+        writer.lit(0.5).uniform(writer.lit(1.0)).expr(),
+    );
+    let update_drag = LinearDragModifier::new(writer.lit(3.).expr());
+
+    let effect = EffectAsset::new(
+        vec![512],
+        // The crate prelude has a `Spawner` of its own
+        bevy_hanabi::prelude::Spawner::once(128.0.into(), true),
+        writer.finish(),
+    )
+    .with_name("explosion")
+    .init(init_pos)
+    .init(init_vel)
+    .init(init_lifetime)
+    .update(update_drag)
+    .render(ColorOverLifetimeModifier { gradient: color })
+    .render(SizeOverLifetimeModifier {
+        gradient: size,
+        screen_space_size: false,
+    });
+
+    cmd.insert_resource(ExplosionEffect(effects.add(effect)));
+}
+
+/// Explode [`Explosive`] entities when they are destroyed. These explosions harm everything.
+fn explode_destroyed(
+    mut explosions: EventWriter<events::Explosion>,
+    destroyed: Query<(&Transform, &Explosive), Added<Destroyed>>,
+) {
+    for (transform, explosive) in destroyed.iter() {
+        explosions.send(events::Explosion {
+            translation: transform.translation,
+            damage: explosive.damage,
+            blast: explosive.blast,
+            alliegance: None,
+        });
+    }
+}
+
+/// Damage and push away everything caught in an explosion, and show its effect
+///
+/// # System overview
+///
+/// 1. Find everything within the radius of each explosion with a spatial query
+/// 2. Skip anything that is not an enemy of the explosion's alliegance, if it has one
+/// 3. Scale damage and knockback by the distance from the center of the explosion
+fn manage_explosions(
+    mut cmd: Commands,
+    mut explosions: EventReader<events::Explosion>,
+    mut hits: EventWriter<events::Hit>,
+    mut caught: Query<
+        (
+            &Transform,
+            Option<&Alliegance>,
+            Option<&mut ExternalImpulse>,
+        ),
+        (With<Damage>, Without<Destroyed>),
+    >,
+    query: SpatialQuery,
+    effect: Res<ExplosionEffect>,
+    time: Res<Time>,
+) {
+    for events::Explosion {
+        translation,
+        damage,
+        blast,
+        alliegance,
+    } in explosions.read()
+    {
+        let intersections = query.shape_intersections(
+            &Collider::cylinder(blast.radius, 1f32),
+            *translation,
+            Transform::default_z().rotation,
+            SpatialQueryFilter {
+                mask: LayerMask::from([
+                    PhysicsCategory::Craft,
+                    PhysicsCategory::Structure,
+                    PhysicsCategory::Missile,
+                ]),
+                ..default()
+            },
+        );
+        for hit in intersections {
+            let Ok((transform, maybe_alliegance, maybe_impulse)) = caught.get_mut(hit) else {
+                continue;
+            };
+            if let Some(alliegance) = alliegance {
+                let other_faction = maybe_alliegance.cloned().unwrap_or_default().faction;
+                if !alliegance.enemies.contains(&other_faction) {
+                    continue;
+                }
+            }
+
+            let offset = (transform.translation - *translation).truncate();
+            let scale = blast.scale(offset.length());
+            hits.send(events::Hit {
+                target: hit,
+                amount: damage * scale,
+                damage_type: DamageType::Explosive,
+            });
+
+            // Push away from the center of the explosion
+            let impulse = (offset.normalize_or_zero() * blast.knockback * scale).extend(0f32);
+            match maybe_impulse {
+                Some(mut external_impulse) => {
+                    external_impulse.apply_impulse(impulse);
+                }
+                None => {
+                    cmd.entity(hit).insert(ExternalImpulse::new(impulse));
+                }
+            }
+        }
+
+        cmd.spawn((
+            Name::new("explosion"),
+            ParticleEffectBundle {
+                effect: ParticleEffect::new(effect.0.clone()),
+                transform: Transform::from_translation(*translation)
+                    .with_scale(Vec3::splat(blast.radius.max(0.1))),
+                ..default()
+            },
+            Lifetime {
+                created: time.elapsed(),
+                lifetime: Duration::from_secs_f32(EFFECT_LIFETIME),
+            },
+        ));
+    }
+}
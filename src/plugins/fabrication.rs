@@ -0,0 +1,286 @@
+use crate::prelude::*;
+use bevy::prelude::*;
+
+/// Turns items into other items at buildings with a [`Fabricator`]
+pub struct FabricationPlugin;
+
+impl Plugin for FabricationPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_saveable::<Fabricator>()
+            .register_saveable::<FabricationQueue>()
+            .observe(on_fabricate.pipe(handle_errors::<FabricationError>))
+            .add_systems(
+                Update,
+                advance_fabrication
+                    .pipe(handle_errors::<FabricationError>)
+                    .run_if(in_state(AppState::main())),
+            );
+    }
+}
+
+/// Consume the inputs of a recipe and add it to the queue of the patron
+fn on_fabricate(
+    trigger: Trigger<triggers::Fabricate>,
+    mut cmd: Commands,
+    mut inventories: Query<&mut Inventory>,
+    mut credits: Query<&mut Credits>,
+    mut queues: Query<&mut FabricationQueue>,
+    fabricators: Query<(&Fabricator, &Name)>,
+    docked: Query<&Docked>,
+    library: Res<Library>,
+    recipes: Res<Assets<Recipe>>,
+    items: Res<Assets<Item>>,
+) -> Result<(), FabricationError> {
+    let triggers::Fabricate {
+        patron,
+        fabricator,
+        recipe: name,
+    } = trigger.event();
+    let recipe = library
+        .recipe(name)
+        .and_then(|recipe| recipes.get(&recipe))
+        .ok_or_else(|| FabricationError::RecipeNotFound(name.clone()))?;
+
+    if docked.get(*patron).ok().map(|docked| **docked) != Some(*fabricator) {
+        return Err(FabricationError::NotDocked);
+    }
+    let (Fabricator { queue_size }, building) = fabricators
+        .get(*fabricator)
+        .map_err(|_| FabricationError::NotAFabricator)?;
+    if building.as_str() != recipe.building {
+        return Err(FabricationError::WrongBuilding {
+            recipe: recipe.name.clone(),
+            building: recipe.building.clone(),
+        });
+    }
+    if queues.get(*patron).map_or(0, |queue| queue.len()) >= *queue_size {
+        return Err(FabricationError::QueueFull(*queue_size));
+    }
+
+    // Take the inputs and payment together, so nothing is taken if either is missing
+    let mut transaction = recipe.inputs.iter().try_fold(
+        InventoryTransaction::default(),
+        |transaction, (item, amount)| {
+            let item = library.item(item).ok_or(InventoryError::ItemNotFound)?;
+            Ok::<_, InventoryError>(transaction.remove_any(*patron, item, *amount))
+        },
+    )?;
+    if recipe.cost > 0 {
+        transaction = transaction.pay(*patron, *fabricator, recipe.cost);
+    }
+    transaction.commit(&mut inventories, &mut credits, &items)?;
+
+    let job = FabricationJob {
+        recipe: name.clone(),
+        remaining: recipe.time,
+    };
+    match queues.get_mut(*patron) {
+        Ok(mut queue) => queue.push(job),
+        Err(_) => {
+            cmd.entity(*patron).insert(FabricationQueue(vec![job]));
+        }
+    }
+    Ok(())
+}
+
+/// Make progress on the first job of every queue, delivering the output of finished jobs. Jobs
+/// whose recipe or output no longer exists can never be delivered, and are dropped.
+fn advance_fabrication(
+    mut queues: Query<(Entity, &mut FabricationQueue)>,
+    mut inventories: Query<&mut Inventory>,
+    mut credits: Query<&mut Credits>,
+    library: Res<Library>,
+    recipes: Res<Assets<Recipe>>,
+    items: Res<Assets<Item>>,
+    time: Res<Time>,
+) -> Result<(), FabricationError> {
+    let mut result = Ok(());
+    for (entity, mut queue) in queues.iter_mut() {
+        let Some(job) = queue.first_mut() else {
+            continue;
+        };
+        let was_running = job.remaining > 0f32;
+        job.remaining = (job.remaining - time.delta_seconds()).max(0f32);
+        if job.remaining > 0f32 {
+            continue;
+        }
+
+        let transaction = library
+            .recipe(&job.recipe)
+            .and_then(|recipe| recipes.get(&recipe))
+            .ok_or_else(|| FabricationError::RecipeNotFound(job.recipe.clone()))
+            .and_then(|recipe| {
+                recipe
+                    .outputs
+                    .iter()
+                    .try_fold(
+                        InventoryTransaction::default(),
+                        |transaction, (item, amount)| {
+                            let item = library.item(item).ok_or(InventoryError::ItemNotFound)?;
+                            Ok::<_, InventoryError>(
+                                transaction.add(entity, ItemStack::new(item, *amount)),
+                            )
+                        },
+                    )
+                    .map_err(FabricationError::from)
+            });
+        let transaction = match transaction {
+            Ok(transaction) => transaction,
+            Err(e) => {
+                // Drop the job so it is only reported once, and the rest of the queue can go on
+                queue.remove(0);
+                result = Err(e);
+                continue;
+            }
+        };
+        match transaction.commit(&mut inventories, &mut credits, &items) {
+            Ok(()) => {
+                queue.remove(0);
+            }
+            // Finished jobs wait until there is room for their output. This is only reported
+            // when the job first finishes, rather than every frame.
+            Err(e) if was_running => result = Err(e.into()),
+            Err(_) => (),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::{ecs::system::RunSystemOnce, utils::HashMap};
+    use std::time::Duration;
+
+    /// A world with a `widget` recipe that makes two widgets, and a patron for every capacity
+    fn setup(capacities: &[usize]) -> (World, Handle<Item>, Vec<Entity>) {
+        let mut world = World::new();
+        let mut items = Assets::<Item>::default();
+        let widget = items.add(Item {
+            name: "widget".to_string(),
+            size: 1,
+            ..default()
+        });
+        let mut recipes = Assets::<Recipe>::default();
+        let recipe = recipes.add(Recipe {
+            name: "widget".to_string(),
+            inputs: Vec::new(),
+            outputs: vec![("widget".to_string(), 2)],
+            time: 1f32,
+            cost: 0,
+            building: "fabricator".to_string(),
+        });
+        world.insert_resource(Library {
+            settings: default(),
+            items: HashMap::from_iter([("items/widget.ron".to_string(), widget.clone())]),
+            creatures: default(),
+            crafts: default(),
+            buildings: default(),
+            biomes: default(),
+            recipes: HashMap::from_iter([("recipes/widget.recipe.ron".to_string(), recipe)]),
+            models: default(),
+        });
+        world.insert_resource(items);
+        world.insert_resource(recipes);
+        world.insert_resource(Time::<()>::default());
+        let patrons = capacities
+            .iter()
+            .map(|capacity| world.spawn(Inventory::with_capacity(*capacity)).id())
+            .collect();
+        (world, widget, patrons)
+    }
+
+    fn job(recipe: &str, remaining: f32) -> FabricationJob {
+        FabricationJob {
+            recipe: recipe.to_string(),
+            remaining,
+        }
+    }
+
+    /// Advance time by a second and run fabrication once
+    fn advance(world: &mut World) -> Result<(), FabricationError> {
+        world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs(1));
+        world.run_system_once(advance_fabrication)
+    }
+
+    #[test]
+    fn test_advance_queue() {
+        let (mut world, widget, patrons) = setup(&[10]);
+        world.entity_mut(patrons[0]).insert(FabricationQueue(vec![
+            job("widget", 1.5),
+            job("widget", 1f32),
+        ]));
+
+        // Only the first job makes progress
+        advance(&mut world).unwrap();
+        let queue = world.get::<FabricationQueue>(patrons[0]).unwrap();
+        assert_eq!(queue[0].remaining, 0.5);
+        assert_eq!(queue[1].remaining, 1f32);
+
+        advance(&mut world).unwrap();
+        assert_eq!(world.get::<FabricationQueue>(patrons[0]).unwrap().len(), 1);
+        let inventory = world.get::<Inventory>(patrons[0]).unwrap();
+        assert_eq!(inventory.count(&widget), 2);
+    }
+
+    #[test]
+    fn test_output_blocked() {
+        let (mut world, widget, patrons) = setup(&[1]);
+        world
+            .entity_mut(patrons[0])
+            .insert(FabricationQueue(vec![job("widget", 1f32)]));
+
+        // The finished job is reported once, then waits for room without repeating the error
+        assert!(advance(&mut world).is_err());
+        assert!(advance(&mut world).is_ok());
+        assert_eq!(world.get::<FabricationQueue>(patrons[0]).unwrap().len(), 1);
+        assert_eq!(
+            world.get::<Inventory>(patrons[0]).unwrap().count(&widget),
+            0
+        );
+
+        *world.get_mut::<Inventory>(patrons[0]).unwrap() = Inventory::with_capacity(2);
+        advance(&mut world).unwrap();
+        assert!(world
+            .get::<FabricationQueue>(patrons[0])
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            world.get::<Inventory>(patrons[0]).unwrap().count(&widget),
+            2
+        );
+    }
+
+    #[test]
+    fn test_missing_recipe() {
+        let (mut world, widget, patrons) = setup(&[10, 10]);
+        world.entity_mut(patrons[0]).insert(FabricationQueue(vec![
+            job("missing", 0.5),
+            job("widget", 1f32),
+        ]));
+        world
+            .entity_mut(patrons[1])
+            .insert(FabricationQueue(vec![job("widget", 1f32)]));
+
+        // The missing recipe is dropped without holding up any other queue
+        assert!(matches!(
+            advance(&mut world),
+            Err(FabricationError::RecipeNotFound(_))
+        ));
+        let queue = world.get::<FabricationQueue>(patrons[0]).unwrap();
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue[0].recipe, "widget");
+        assert_eq!(
+            world.get::<Inventory>(patrons[1]).unwrap().count(&widget),
+            2
+        );
+
+        advance(&mut world).unwrap();
+        assert_eq!(
+            world.get::<Inventory>(patrons[0]).unwrap().count(&widget),
+            2
+        );
+    }
+}
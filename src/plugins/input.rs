@@ -1,5 +1,11 @@
+use std::time::Duration;
+
 use crate::prelude::*;
-use bevy::{prelude::*, window::PrimaryWindow};
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+    window::PrimaryWindow,
+};
 use events::DockEvent;
 use leafwing_input_manager::prelude::*;
 
@@ -75,21 +81,37 @@ fn update_app_action_bindings(
 ) {
     InputManagerPlugin::<AppAction>::default();
     // Resource level input
-    *app_actions = InputMap::default().with(
-        AppAction::Console,
-        settings
-            .get(&library.settings)
-            .unwrap()
-            .controls
-            .keyboard
-            .console,
-    );
+    let keyboard = &settings.get(&library.settings).unwrap().controls.keyboard;
+    *app_actions = InputMap::default()
+        .with(AppAction::Console, keyboard.console)
+        .with(AppAction::Quicksave, keyboard.quicksave)
+        .with(AppAction::Quickload, keyboard.quickload);
 }
 
-fn apply_app_input(mut draw_inspector: ResMut<DrawInspector>, input: Res<ActionState<AppAction>>) {
+fn apply_app_input(
+    mut draw_inspector: ResMut<DrawInspector>,
+    mut next_state: ResMut<NextState<AppState>>,
+    input: Res<ActionState<AppAction>>,
+    state: Res<State<AppState>>,
+    save_path: Res<SavePath>,
+) {
     if input.just_pressed(&AppAction::Console) {
         **draw_inspector = !**draw_inspector;
     }
+
+    // Quicksaves sit next to the current save
+    if let (AppState::Main, Some(save_path)) = (state.get(), &save_path.0) {
+        let quicksave_path = quicksave_path(save_path);
+        if input.just_pressed(&AppAction::Quicksave) {
+            next_state.set(AppState::SaveGame {
+                save_path: quicksave_path,
+            });
+        } else if input.just_pressed(&AppAction::Quickload) && quicksave_path.exists() {
+            next_state.set(AppState::LoadGame {
+                path: quicksave_path,
+            });
+        }
+    }
 }
 
 /// Apply desired input to the player controller
@@ -105,14 +127,18 @@ fn apply_player_input(
             &ChestsInRange,
             &DockInRange,
             Option<&Docked>,
+            &mut PowerDistribution,
         ),
-        With<Player>,
+        (With<Player>, Without<Destroyed>),
     >,
-    mut weapons: Query<&mut Weapon>,
+    mut weapons: Query<(&mut Weapon, Option<&Handle<Item>>)>,
     mut dock_events: EventWriter<DockEvent>,
     mut credits: Query<&mut Credits>,
     camera: Query<(&Camera, &GlobalTransform)>,
     window: Query<&Window, With<PrimaryWindow>>,
+    library: Res<Library>,
+    settings: Res<Assets<Settings>>,
+    time: Res<Time>,
 ) {
     for (
         player_entity,
@@ -123,6 +149,7 @@ fn apply_player_input(
         chests_in_range,
         dock_in_range,
         maybe_docked,
+        mut power,
     ) in players.iter_mut()
     {
         controller.angular_thrust = actions.clamped_value(&Action::Turn);
@@ -142,6 +169,22 @@ fn apply_player_input(
             }
         }
 
+        controller.afterburner = actions.pressed(&Action::Afterburner);
+
+        // Move power between systems
+        for (action, system) in [
+            (Action::PowerEngines, PowerSystem::Engines),
+            (Action::PowerShields, PowerSystem::Shields),
+            (Action::PowerWeapons, PowerSystem::Weapons),
+        ] {
+            if actions.just_pressed(&action) {
+                power.increase(system);
+            }
+        }
+        if actions.just_pressed(&Action::PowerBalance) {
+            power.balance();
+        }
+
         // Get cursor position
         let cursor_position = match camera.get_single() {
             Ok((camera, camera_transform)) => match window.single().cursor_position() {
@@ -160,18 +203,60 @@ fn apply_player_input(
             Err(_) => None,
         };
 
-        // Get all weapons attached to the player
+        // Get all weapons attached to the player, and fire each group with its own action
         if let Some(children) = maybe_children {
+            // Alternating weapons take turns with identical weapons in their group, so only the
+            // one in turn fires
+            let mut alternating =
+                HashMap::<(WeaponGroup, AssetId<Item>), Vec<(Entity, Duration)>>::new();
+            let mut recoils = HashMap::<(WeaponGroup, AssetId<Item>), f32>::new();
             for child in children.iter() {
-                if let Ok(mut weapon) = weapons.get_mut(*child) {
-                    weapon.wants_to_fire = actions.pressed(&Action::Fire);
+                if let Ok((mut weapon, maybe_item)) = weapons.get_mut(*child) {
+                    let action = group_action(weapon.fire_control.group);
+                    weapon.apply_trigger(
+                        actions.pressed(&action),
+                        actions.just_pressed(&action),
+                        time.elapsed(),
+                    );
                     weapon.target = cursor_position;
+
+                    if let (FireMode::Alternating, true, Some(item)) =
+                        (weapon.fire_control.mode, weapon.wants_to_fire, maybe_item)
+                    {
+                        let key = (weapon.fire_control.group, item.id());
+                        alternating
+                            .entry(key)
+                            .or_default()
+                            .push((*child, weapon.last_fired));
+                        recoils.insert(key, weapon.weapon_type.recoil());
+                    }
+                }
+            }
+            let in_turn = alternating
+                .iter()
+                .filter_map(|(key, turns)| {
+                    let last_fired = turns.iter().map(|(_, last_fired)| *last_fired);
+                    FireMode::next_in_turn(
+                        &last_fired.collect::<Vec<_>>(),
+                        Duration::from_secs_f32(recoils[key].max(0f32)),
+                        time.elapsed(),
+                    )
+                    .map(|next| turns[next].0)
+                })
+                .collect::<HashSet<_>>();
+            for child in children.iter() {
+                if let Ok((mut weapon, Some(_))) = weapons.get_mut(*child) {
+                    if weapon.fire_control.mode == FireMode::Alternating && !in_turn.contains(child)
+                    {
+                        weapon.wants_to_fire = false;
+                    }
                 }
             }
         }
 
-        // Take all nearby items
+        // Take all nearby items that pass the loot filter and fit
         if actions.just_pressed(&Action::Take) {
+            let loot = &settings.get(&library.settings).unwrap().loot;
             for chest in chests_in_range.chests.iter() {
                 if let Ok([mut player_credits, mut chest_credits]) =
                     credits.get_many_mut([player_entity, *chest])
@@ -184,7 +269,7 @@ fn apply_player_input(
                     cmd.trigger(triggers::InventoryTransfer {
                         from: *chest,
                         to: player_entity,
-                        transfer: triggers::InventoryTransferSettings::All,
+                        transfer: triggers::InventoryTransferSettings::Filter(loot.clone()),
                     });
                 }
             }
@@ -213,3 +298,11 @@ fn apply_player_input(
         }
     }
 }
+
+/// Action that fires a group of weapons
+fn group_action(group: WeaponGroup) -> Action {
+    match group {
+        WeaponGroup::Primary => Action::Fire,
+        WeaponGroup::Secondary => Action::FireSecondary,
+    }
+}
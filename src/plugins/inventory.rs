@@ -1,13 +1,17 @@
 use crate::prelude::*;
 use avian3d::prelude::*;
 use bevy::prelude::*;
-use rand::Rng;
+use bevy_turborand::prelude::*;
 
 pub struct InventoryPlugin;
 
 impl Plugin for InventoryPlugin {
     fn build(&self, app: &mut App) {
-        app.observe(on_transfer.pipe(handle_errors::<InventoryError>))
+        app.register_saveable_builder::<InventoryBuilder>()
+            .register_saveable_builder::<DropsBuilder>()
+            .register_saveable::<ChestsInRange>()
+            .add_event::<events::LeftBehind>()
+            .observe(on_transfer.pipe(handle_errors::<InventoryError>))
             .observe(on_toss_overboard.pipe(handle_errors::<InventoryError>))
             .add_systems(
                 Update,
@@ -26,28 +30,83 @@ impl Plugin for InventoryPlugin {
 fn on_transfer(
     trigger: Trigger<triggers::InventoryTransfer>,
     mut inventories: Query<&mut Inventory>,
+    mut credits: Query<&mut Credits>,
+    mut left_behind: EventWriter<events::LeftBehind>,
     items: Res<Assets<Item>>,
 ) -> Result<(), InventoryError> {
     let triggers::InventoryTransfer { from, to, transfer } = trigger.event();
-    let [mut from, mut to] = inventories
-        .get_many_mut([*from, *to])
-        .map_err(|_| InventoryError::Unqueriable)?;
 
-    match transfer {
-        triggers::InventoryTransferSettings::Item { item, quantity } => {
-            from.transfer(item.clone(), &mut to, *quantity, &items)?;
+    let transaction = match transfer {
+        triggers::InventoryTransferSettings::Item {
+            item,
+            quantity,
+            metadata,
+        } => {
+            let stack = ItemStack::new(item.clone(), *quantity).with_metadata(metadata.clone());
+            InventoryTransaction::default().transfer(*from, *to, stack)
         }
         triggers::InventoryTransferSettings::All => {
-            from.transfer_all(&mut to)?;
+            InventoryTransaction::default().transfer_all(*from, *to)
         }
+        triggers::InventoryTransferSettings::Fit => {
+            take_fitting(*from, *to, &inventories, &items, |_| true)?
+        }
+        triggers::InventoryTransferSettings::Category(category) => {
+            take_fitting(*from, *to, &inventories, &items, |item| {
+                item.equipment_type_str() == category
+            })?
+        }
+        triggers::InventoryTransferSettings::Filter(filter) => {
+            take_fitting(*from, *to, &inventories, &items, |item| {
+                filter.matches(item)
+            })?
+        }
+    };
+    transaction.commit(&mut inventories, &mut credits, &items)?;
+
+    // Partial transfers report whatever they did not take
+    let partial = !matches!(
+        transfer,
+        triggers::InventoryTransferSettings::Item { .. } | triggers::InventoryTransferSettings::All
+    );
+    let stacks = inventories.get(*from)?.iter().cloned().collect::<Vec<_>>();
+    if partial && !stacks.is_empty() {
+        left_behind.send(events::LeftBehind {
+            from: *from,
+            to: *to,
+            stacks,
+        });
     }
     Ok(())
 }
 
+/// Transfer the stacks of `from` whose item passes `filter`, as many as fit in `to`
+fn take_fitting(
+    from: Entity,
+    to: Entity,
+    inventories: &Query<&mut Inventory>,
+    items: &Assets<Item>,
+    filter: impl Fn(&Item) -> bool,
+) -> Result<InventoryTransaction, InventoryError> {
+    let stacks = inventories
+        .get(from)?
+        .iter()
+        .filter(|stack| items.get(&stack.item).is_some_and(&filter))
+        .cloned()
+        .collect::<Vec<_>>();
+    let fitting = inventories.get(to)?.fitting(stacks, items)?;
+    Ok(fitting
+        .into_iter()
+        .fold(InventoryTransaction::default(), |transaction, stack| {
+            transaction.transfer(from, to, stack)
+        }))
+}
+
 fn on_toss_overboard(
     trigger: Trigger<triggers::TossItemOverboard>,
     mut cmd: Commands,
     mut inventories: Query<&mut Inventory>,
+    mut credits: Query<&mut Credits>,
     items: Res<Assets<Item>>,
     transforms: Query<&Transform>,
 ) -> Result<(), InventoryError> {
@@ -55,17 +114,23 @@ fn on_toss_overboard(
         entity,
         item,
         quantity,
+        metadata,
     } = trigger.event();
-    let mut inventory = inventories.get_mut(*entity)?;
     let retrieved_item = items.get(item).ok_or(InventoryError::ItemNotFound)?;
     let transform = transforms.get(*entity)?;
 
-    inventory.remove(item, retrieved_item.size, *quantity)?;
+    // The chest does not exist yet, so fill it before removing the items from the inventory
+    let stack = ItemStack::new(item.clone(), *quantity).with_metadata(metadata.clone());
+    let mut chest = Inventory::max_capacity();
+    chest.add(stack.clone(), retrieved_item)?;
+    InventoryTransaction::default()
+        .remove(*entity, stack)
+        .commit(&mut inventories, &mut credits, &items)?;
 
     // Spawn tossed stuff in a chest
     cmd.spawn((
         Chest,
-        Inventory::max_capacity().with(item.clone(), *quantity, &items)?,
+        chest,
         *transform,
         Collider::cuboid(0.5, 0.5, 0.5),
         CollisionLayers {
@@ -82,25 +147,42 @@ fn manage_drops(
     drops: Query<(&Drops, Option<&Credits>, &Transform), Added<Destroyed>>,
     library: Res<Library>,
     items: Res<Assets<Item>>,
+    mut rng: ResMut<GlobalRng>,
 ) -> Result<(), InventoryError> {
     for (drops, maybe_credits, transform) in drops.iter() {
         let mut inv = Inventory::default();
-        // Filter with probabilities to find the items we will actually drop
-        let mut rng = rand::thread_rng();
-
-        let items_to_drop = drops.iter().filter_map(|(it, p)| {
-            if rng.gen_ratio(1, p.d as u32) {
-                Some((it, p))
-            } else {
-                None
-            }
-        });
+        // Filter with probabilities to find the items we will actually drop. Drops come from the
+        // seeded generator so that they can be reproduced.
+        let items_to_drop = drops
+            .iter()
+            .filter(|(it, p)| {
+                let rarity = items.get(*it).map(|item| item.rarity).unwrap_or_default();
+                rng.usize(..p.odds(rarity)) == 0
+            })
+            .collect::<Vec<_>>();
 
-        let mut rng = rand::thread_rng();
         for (item, amount) in items_to_drop {
-            let amount = rng.gen_range(amount.min..=amount.max);
+            let amount = rng.usize(amount.min..=amount.max);
             let retrieved_item = items.get(item).ok_or(InventoryError::ItemNotFound)?;
-            inv.add(item.clone(), retrieved_item.size, amount).unwrap();
+            match &retrieved_item.equipment {
+                // Every piece of equipment rolls its own modifiers
+                Some(equipment) => {
+                    for _ in 0..amount {
+                        let metadata = ItemMetadata {
+                            modifiers: Modifier::roll(equipment, retrieved_item.rarity, &mut *rng),
+                            ..default()
+                        };
+                        inv.add(
+                            ItemStack::new(item.clone(), 1).with_metadata(metadata),
+                            retrieved_item,
+                        )
+                        .unwrap();
+                    }
+                }
+                None => inv
+                    .add(ItemStack::new(item.clone(), amount), retrieved_item)
+                    .unwrap(),
+            }
         }
 
         // Spawn drops in a chest
@@ -116,15 +198,11 @@ fn manage_drops(
                 },
                 RigidBody::Dynamic,
                 AngularVelocity(Vec3::new(
-                    rng.gen::<f32>() - 0.5f32,
-                    rng.gen::<f32>() - 0.5f32,
-                    rng.gen::<f32>() - 0.5f32,
-                )),
-                LinearVelocity(Vec3::new(
-                    rng.gen::<f32>() - 0.5f32,
-                    rng.gen::<f32>() - 0.5f32,
-                    0f32,
+                    rng.f32() - 0.5f32,
+                    rng.f32() - 0.5f32,
+                    rng.f32() - 0.5f32,
                 )),
+                LinearVelocity(Vec3::new(rng.f32() - 0.5f32, rng.f32() - 0.5f32, 0f32)),
                 Model::new(library.model("items/chest").unwrap()).with_offset(-Vec3::Y * 2f32),
             ));
         }
@@ -140,11 +218,7 @@ fn manage_drops(
                     memberships: PhysicsCategory::Item.into(),
                     filters: LayerMask::NONE,
                 },
-                LinearVelocity(Vec3::new(
-                    rng.gen::<f32>() - 0.5f32,
-                    rng.gen::<f32>() - 0.5f32,
-                    0f32,
-                )),
+                LinearVelocity(Vec3::new(rng.f32() - 0.5f32, rng.f32() - 0.5f32, 0f32)),
                 Model::new(library.model("items/credits").unwrap()).with_offset(-Vec3::Y * 2f32),
             ));
         }
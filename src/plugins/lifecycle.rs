@@ -0,0 +1,214 @@
+use crate::prelude::*;
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use events::DockEvent;
+
+/// What happens to things after they are [`Destroyed`]. Crafts become wrecks, spawners are
+/// rebuilt and the player respawns.
+pub struct LifecyclePlugin;
+
+impl Plugin for LifecyclePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_saveable::<Wreck>()
+            .register_saveable::<Rebuilding>()
+            .register_saveable::<RespawnPoint>()
+            .observe(on_respawn)
+            .add_systems(
+                Update,
+                (
+                    salvage_wrecks,
+                    decay_wrecks,
+                    rebuild_spawners,
+                    update_respawn_points,
+                )
+                    .run_if(in_state(AppState::main())),
+            )
+            // Everything else reacts to the destruction first, such as dropping credits
+            .add_systems(
+                PostUpdate,
+                (
+                    wreck_destroyed_crafts,
+                    start_rebuilding,
+                    drop_player_cargo.pipe(handle_errors::<InventoryError>),
+                )
+                    .run_if(in_state(AppState::main()).and_then(resource_exists::<Library>)),
+            );
+    }
+}
+
+/// Turn destroyed crafts into wrecks. Wrecks no longer collide with anything, and their cargo can
+/// be taken like a chest.
+fn wreck_destroyed_crafts(
+    mut cmd: Commands,
+    destroyed: Query<Entity, (With<Craft>, Without<Player>, Added<Destroyed>)>,
+    library: Res<Library>,
+    settings: Res<Assets<Settings>>,
+) {
+    let settings = settings.get(&library.settings).unwrap();
+    for entity in destroyed.iter() {
+        // Credits were already dropped, so they cannot be taken from the wreck as well
+        cmd.entity(entity).remove::<Credits>().insert((
+            Wreck {
+                remaining: settings.death.wreck_lifetime,
+            },
+            CollisionLayers {
+                memberships: PhysicsCategory::Item.into(),
+                filters: LayerMask::NONE,
+            },
+        ));
+    }
+}
+
+/// Chests are not saved, so wrecks are made into chests whenever they are spawned or loaded
+fn salvage_wrecks(mut cmd: Commands, wrecks: Query<Entity, (With<Wreck>, Without<Chest>)>) {
+    for entity in wrecks.iter() {
+        cmd.entity(entity).insert(Chest);
+    }
+}
+
+fn decay_wrecks(mut cmd: Commands, mut wrecks: Query<(Entity, &mut Wreck)>, time: Res<Time>) {
+    for (entity, mut wreck) in wrecks.iter_mut() {
+        wreck.remaining -= time.delta_seconds();
+        if wreck.remaining <= 0f32 {
+            cmd.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+/// Start rebuilding destroyed spawners that can be rebuilt
+fn start_rebuilding(mut cmd: Commands, destroyed: Query<(Entity, &Spawner), Added<Destroyed>>) {
+    for (entity, spawner) in destroyed.iter() {
+        if let Some(rebuild) = spawner.rebuild {
+            cmd.entity(entity).insert(Rebuilding { remaining: rebuild });
+        }
+    }
+}
+
+/// Repair spawners that have finished rebuilding so they start spawning again
+fn rebuild_spawners(
+    mut cmd: Commands,
+    mut rebuilding: Query<(Entity, &mut Rebuilding, &mut Damage)>,
+    time: Res<Time>,
+) {
+    for (entity, mut rebuild, mut damage) in rebuilding.iter_mut() {
+        rebuild.remaining -= time.delta_seconds();
+        if rebuild.remaining <= 0f32 {
+            **damage = 0f32;
+            cmd.entity(entity).remove::<(Destroyed, Rebuilding)>();
+        }
+    }
+}
+
+/// The player respawns where it last docked
+fn update_respawn_points(
+    mut cmd: Commands,
+    docked: Query<(Entity, &Transform), (With<Player>, Changed<Docked>)>,
+) {
+    for (entity, transform) in docked.iter() {
+        cmd.entity(entity)
+            .insert(RespawnPoint(transform.translation.truncate()));
+    }
+}
+
+/// Drop some of the cargo and credits of a destroyed player into a chest, as set in the death
+/// [`Settings`]
+fn drop_player_cargo(
+    mut cmd: Commands,
+    mut inventories: Query<&mut Inventory>,
+    mut credits: Query<&mut Credits>,
+    mut dock_events: EventWriter<DockEvent>,
+    players: Query<(Entity, &Transform, Option<&Docked>), (With<Player>, Added<Destroyed>)>,
+    library: Res<Library>,
+    settings: Res<Assets<Settings>>,
+    items: Res<Assets<Item>>,
+) -> Result<(), InventoryError> {
+    let settings = &settings.get(&library.settings).unwrap().death;
+    for (player, transform, maybe_docked) in players.iter() {
+        if maybe_docked.is_some() {
+            dock_events.send(DockEvent::Undock { to_undock: player });
+        }
+
+        // Fill the chest before removing the items from the player, as it does not exist yet
+        let stacks = inventories
+            .get(player)
+            .map(|inventory| inventory.iter().cloned().collect::<Vec<_>>())
+            .unwrap_or_default();
+        let mut chest = Inventory::max_capacity();
+        let mut transaction = InventoryTransaction::default();
+        for stack in stacks {
+            let item = items.get(&stack.item).ok_or(InventoryError::ItemNotFound)?;
+            let amount = settings.items_dropped(item, stack.amount);
+            if amount == 0 {
+                continue;
+            }
+            let dropped = ItemStack { amount, ..stack };
+            chest.add(dropped.clone(), item)?;
+            transaction = transaction.remove(player, dropped);
+        }
+        transaction.commit(&mut inventories, &mut credits, &items)?;
+
+        let mut dropped_credits = Credits::default();
+        if let Ok(mut player_credits) = credits.get_mut(player) {
+            let amount = settings.credits_dropped(player_credits.get());
+            player_credits.transfer(&mut dropped_credits, amount)?;
+        }
+
+        if !chest.is_empty() {
+            cmd.spawn((
+                Chest,
+                chest,
+                TransformBundle::from_transform(*transform),
+                Collider::cuboid(0.5, 0.5, 0.5),
+                CollisionLayers {
+                    memberships: PhysicsCategory::Item.into(),
+                    filters: LayerMask::NONE,
+                },
+                Model::new(library.model("items/chest").unwrap()).with_offset(-Vec3::Y * 2f32),
+            ));
+        }
+        if dropped_credits.get() > 0 {
+            cmd.spawn((
+                Chest,
+                dropped_credits,
+                TransformBundle::from_transform(*transform),
+                Collider::cuboid(0.5, 0.5, 0.5),
+                CollisionLayers {
+                    memberships: PhysicsCategory::Item.into(),
+                    filters: LayerMask::NONE,
+                },
+                Model::new(library.model("items/credits").unwrap()).with_offset(-Vec3::Y * 2f32),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Repair a destroyed player and move it to its [`RespawnPoint`], or the center of the zone if it
+/// has none
+fn on_respawn(
+    trigger: Trigger<triggers::Respawn>,
+    mut cmd: Commands,
+    mut players: Query<
+        (
+            &mut Transform,
+            &mut Damage,
+            Option<&mut LinearVelocity>,
+            Option<&RespawnPoint>,
+        ),
+        (With<Player>, With<Destroyed>),
+    >,
+) {
+    let triggers::Respawn { player } = trigger.event();
+    let Ok((mut transform, mut damage, maybe_velocity, maybe_respawn_point)) =
+        players.get_mut(*player)
+    else {
+        return;
+    };
+    **damage = 0f32;
+    let respawn_point = maybe_respawn_point.map(|point| **point).unwrap_or_default();
+    transform.translation = respawn_point.extend(transform.translation.z);
+    if let Some(mut velocity) = maybe_velocity {
+        velocity.0 = Vec3::ZERO;
+    }
+    cmd.entity(*player).remove::<Destroyed>();
+}
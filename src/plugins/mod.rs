@@ -3,8 +3,11 @@ mod assets;
 mod controllers;
 mod debug;
 mod equipment;
+mod explosions;
+mod fabrication;
 mod input;
 mod inventory;
+mod lifecycle;
 mod settings;
 mod state;
 mod structures;
@@ -13,9 +16,13 @@ mod utility;
 mod weapons;
 mod world;
 
+#[cfg(test)]
+pub(crate) use structures::restore_docking_joints;
+
 use avian3d::{prelude::Gravity, PhysicsPlugins};
 use bevy::{app::PluginGroupBuilder, prelude::*};
 use bevy_egui::EguiPlugin;
+use bevy_hanabi::HanabiPlugin;
 use bevy_turborand::prelude::RngPlugin;
 use big_brain::BigBrainPlugin;
 
@@ -35,6 +42,7 @@ impl PluginGroup for ClientPlugins {
             // 3rd party
             .add_group(PhysicsPlugins::default())
             .add(EguiPlugin)
+            .add(HanabiPlugin)
             .add(RngPlugin::default())
             .add(ClientInitPlugin)
             .add(BigBrainPlugin::new(PreUpdate))
@@ -48,10 +56,13 @@ impl PluginGroup for ClientPlugins {
             .add(ai::AiPlugin)
             .add(inventory::InventoryPlugin)
             .add(weapons::WeaponsPlugin)
+            .add(explosions::ExplosionsPlugin)
+            .add(lifecycle::LifecyclePlugin)
             .add(utility::UtilityPlugin)
             .add(ui::UiPlugin)
             .add(equipment::EquipmentPlugin)
             .add(structures::StructuresPlugin)
+            .add(fabrication::FabricationPlugin)
             .add(state::StatePlugin)
     }
 }
@@ -1,27 +1,20 @@
-use std::{fs::File, io::Write};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use avian3d::prelude::{
-    AngularVelocity, Collider, ColliderConstructor, CollisionLayers, ExternalImpulse, Friction,
-    LinearDamping, LinearVelocity, LockedAxes, Mass, RigidBody,
-};
+use avian3d::prelude::{AngularVelocity, ExternalImpulse, LinearDamping};
 use bevy::{
-    asset::{AssetPath, LoadState},
     core_pipeline::bloom::BloomSettings,
-    ecs::{
-        observer::ObserverState,
-        query::{QueryData, QueryEntityError, ROQueryItem},
-    },
+    ecs::observer::ObserverState,
     pbr::{
         CascadeShadowConfig, Cascades, CascadesVisibleEntities, NotShadowCaster, NotShadowReceiver,
         VolumetricLight,
     },
     prelude::*,
     render::primitives::CascadesFrusta,
-    scene::DynamicEntity,
-    tasks::IoTaskPool,
+    tasks::{block_on, futures_lite::future, IoTaskPool, Task},
     window::PrimaryWindow,
 };
 use bevy_etcetera::Directories;
+use bevy_turborand::prelude::{GlobalRng, Rng};
 use leafwing_input_manager::{
     prelude::{InputMap, KeyboardVirtualAxis},
     InputManagerBundle,
@@ -35,13 +28,42 @@ impl Plugin for StatePlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<events::Save>()
             .init_resource::<SavePath>()
-            .add_systems(OnEnter(AppState::load_game()), (enter_load_game,))
+            .init_resource::<PlayTime>()
+            .init_resource::<SinceSave>()
+            .init_resource::<SaveMigrations>()
+            .init_resource::<SaveRegistry>()
+            .register_saveable::<Persistent>()
+            .register_saveable::<Player>()
+            .register_saveable::<Name>()
+            .register_saveable::<Transform>()
+            .register_saveable::<GlobalTransform>()
+            .register_saveable::<DirectionalLight>()
+            .register_saveable::<PointLight>()
+            .register_saveable::<SpotLight>()
+            .register_saveable::<CascadeShadowConfig>()
+            .register_saveable::<VolumetricLight>()
+            .add_systems(
+                OnEnter(AppState::load_game()),
+                (enter_load_game.pipe(handle_errors::<SaveError>),),
+            )
             .add_systems(OnEnter(AppState::new_game()), (enter_new_game,))
-            .add_systems(OnEnter(AppState::main()), (spawn_camera, finalize_player))
+            .add_systems(
+                OnEnter(AppState::main()),
+                (spawn_camera, finalize_player, finalize_lights),
+            )
             .add_systems(
                 Update,
                 (continue_loading,).run_if(in_state(AppState::load_game())),
             )
+            .add_systems(
+                Update,
+                (finish_writing_save.pipe(handle_errors::<SaveError>),),
+            )
+            .add_systems(
+                Update,
+                (autosave, track_play_time)
+                    .run_if(in_state(AppState::main()).and_then(resource_exists::<Library>)),
+            )
             .add_systems(OnEnter(AppState::save_game()), (enter_save_game,));
     }
 }
@@ -57,219 +79,237 @@ fn enter_save_game(world: &mut World) {
     scene
         .resources
         .push(Box::new(world.resource::<Factions>().clone()));
+    scene
+        .resources
+        .push(Box::new(*world.resource::<WorldSeed>()));
+    scene
+        .resources
+        .push(Box::new(world.resource::<Chunks>().clone()));
+    scene
+        .resources
+        .push(Box::new(world.resource::<Universe>().clone()));
 
-    let entities = world
-        .archetypes()
-        .iter()
-        .flat_map(|a| a.entities().iter().map(|e| e.id()))
-        .collect::<Vec<_>>();
-
-    for entity in entities.into_iter() {
-        struct W<'a> {
-            world: &'a mut World,
-            entity: Entity,
-        }
-        impl<'a> W<'a> {
-            fn resource<T: Resource>(&self) -> Option<&T> {
-                self.world.get_resource::<T>()
-            }
-            fn get<T: Component>(&mut self) -> Option<&T> {
-                self.world.query::<&T>().get(self.world, self.entity).ok()
-            }
-            fn extract<T: Component + Reflect + Clone>(
-                &mut self,
-                dynamic_entity: &mut DynamicEntity,
-            ) {
-                if let Some(component) = self.get::<T>() {
-                    dynamic_entity.components.push(Box::new(component.clone()));
-                }
-            }
-            fn has<T: Component>(&mut self) -> bool {
-                self.get::<T>().is_some()
-            }
-            fn query<T: QueryData>(
-                &mut self,
-                entity: Entity,
-            ) -> Result<ROQueryItem<T>, QueryEntityError> {
-                self.world.query::<T>().get(self.world, entity)
-            }
-        }
-
-        let mut w = W { world, entity };
-
-        // Skip certain entities from serialization
-        if !w.has::<Persistent>() {
-            continue;
-        }
-
-        // Extract potential components to serialize
-        let mut dynamic_entity = DynamicEntity {
-            entity,
-            components: Vec::new(),
-        };
-
-        #[allow(clippy::manual_map)]
-        if let Some(collider) = w.get::<Collider>().cloned() {
-            let shape = collider.shape();
-            let maybe_collider = if let Some(ball) = shape.as_ball() {
-                Some(ColliderConstructor::Sphere {
-                    radius: ball.radius,
-                })
-            } else if let Some(cuboid) = shape.as_cuboid() {
-                let length = cuboid.half_extents * 2f32;
-                Some(ColliderConstructor::Cuboid {
-                    x_length: length.x,
-                    y_length: length.y,
-                    z_length: length.z,
-                })
-            } else if let Some(cylinder) = shape.as_cylinder() {
-                Some(ColliderConstructor::Cylinder {
-                    radius: cylinder.radius,
-                    height: cylinder.half_height * 2f32,
-                })
-            } else {
-                None
-            };
-
-            if let Some(cc) = maybe_collider {
-                dynamic_entity.components.push(Box::new(cc));
-            }
-        }
-
-        if let Some(equipped) = w.get::<Equipped>().cloned() {
-            // Getting equipment is somewhat indirect since the individual `Equipment` entities
-            // contain the name of the equipment
-            let equipped_string = equipped
-                .iter()
-                .flat_map(|(_, entities)| {
-                    entities
-                        .iter()
-                        .copied()
-                        .filter_map(|entity| {
-                            w.query::<&Handle<Item>>(entity)
-                                .cloned()
-                                .map(|handle| {
-                                    handle
-                                        .path()
-                                        .unwrap()
-                                        .to_string()
-                                        .replace("items/", "")
-                                        .replace(".ron", "")
-                                })
-                                .ok()
-                        })
-                        .collect::<Vec<_>>()
-                })
-                .collect::<Vec<_>>();
-            dynamic_entity.components.push(Box::new(EquippedBuilder {
-                equipped: equipped_string,
-                slots: equipped.slots.into_iter().collect(),
-            }));
-        }
-
-        if let Some(inventory) = w.get::<Inventory>().cloned() {
-            dynamic_entity
-                .components
-                .push(Box::new(InventoryBuilder::from_output(inventory)));
-        }
-
-        if let Some(drops) = w.get::<Drops>().cloned() {
-            dynamic_entity
-                .components
-                .push(Box::new(DropsBuilder::from_output(drops)));
-        }
+    // Extract every registered component from persistent entities
+    scene.entities = world.resource::<SaveRegistry>().extract_persistent(world);
 
-        w.extract::<Player>(&mut dynamic_entity);
-        w.extract::<Transform>(&mut dynamic_entity);
-        w.extract::<Name>(&mut dynamic_entity);
-        w.extract::<Alliegance>(&mut dynamic_entity);
-        w.extract::<Craft>(&mut dynamic_entity);
-        w.extract::<RigidBody>(&mut dynamic_entity);
-        w.extract::<Credits>(&mut dynamic_entity);
-        w.extract::<Damage>(&mut dynamic_entity);
-        w.extract::<Destroyed>(&mut dynamic_entity);
-        w.extract::<Energy>(&mut dynamic_entity);
-        w.extract::<Model>(&mut dynamic_entity);
-        w.extract::<Structure>(&mut dynamic_entity);
-        w.extract::<GlobalTransform>(&mut dynamic_entity);
-        w.extract::<Health>(&mut dynamic_entity);
-        w.extract::<Persistent>(&mut dynamic_entity);
-        w.extract::<Spawner>(&mut dynamic_entity);
-        w.extract::<Dockings>(&mut dynamic_entity);
-        w.extract::<CollisionLayers>(&mut dynamic_entity);
-        w.extract::<LinearVelocity>(&mut dynamic_entity);
-        w.extract::<LockedAxes>(&mut dynamic_entity);
-        w.extract::<Controller>(&mut dynamic_entity);
-        w.extract::<ChestsInRange>(&mut dynamic_entity);
-        w.extract::<DockInRange>(&mut dynamic_entity);
-        w.extract::<Friction>(&mut dynamic_entity);
-        w.extract::<SpotLight>(&mut dynamic_entity);
-        w.extract::<PointLight>(&mut dynamic_entity);
-        w.extract::<DirectionalLight>(&mut dynamic_entity);
-        w.extract::<CascadesFrusta>(&mut dynamic_entity);
-        w.extract::<Cascades>(&mut dynamic_entity);
-        w.extract::<CascadeShadowConfig>(&mut dynamic_entity);
-        w.extract::<CascadesVisibleEntities>(&mut dynamic_entity);
-        w.extract::<InheritedVisibility>(&mut dynamic_entity);
-        w.extract::<ViewVisibility>(&mut dynamic_entity);
-        w.extract::<VolumetricLight>(&mut dynamic_entity);
-        w.extract::<Mass>(&mut dynamic_entity);
-
-        if !dynamic_entity.components.is_empty() {
-            scene.entities.push(dynamic_entity);
-        }
-    }
-
-    let serialized_scene = {
+    let metadata = save_metadata(world);
+    let serialized_save = {
         let registry = world.resource::<AppTypeRegistry>().read();
-        scene.serialize(&registry).unwrap()
+        SaveFile::from_scene(&scene, &registry).and_then(|mut save| {
+            save.header.metadata = metadata;
+            save.to_ron()
+        })
+    };
+    let serialized_save = match serialized_save {
+        Ok(serialized_save) => serialized_save,
+        Err(e) => {
+            world.send_event(GameError::from(e));
+            world
+                .resource_mut::<NextState<AppState>>()
+                .set(AppState::Main);
+            return;
+        }
     };
 
     // // Create save path if it doesn't already exist
     // create_dir_all(save_path.clone().0).ok();
 
+    // Quicksaves still belong to the save they were made from
+    let primary_save_path = primary_save_path(&save_path);
     if let Ok(mut window) = world
         .query_filtered::<&mut Window, With<PrimaryWindow>>()
         .get_single_mut(world)
     {
-        window.title = save_path.clone().to_string_lossy().to_string();
+        window.title = primary_save_path.to_string_lossy().to_string();
     }
 
-    *world.resource_mut::<SavePath>() = SavePath(Some(save_path.clone()));
+    *world.resource_mut::<SavePath>() = SavePath(Some(primary_save_path));
+
+    let backups = {
+        let library = world.resource::<Library>();
+        let settings = world.resource::<Assets<Settings>>();
+        settings.get(&library.settings).unwrap().save.backups
+    };
+
+    // Let the previous save finish first, so two writes never race on the same files
+    if let Some(WritingSave(task)) = world.remove_resource::<WritingSave>() {
+        if let Err(e) = block_on(task) {
+            world.send_event(GameError::from(e));
+        }
+    }
+
+    // Chunks unloaded so far are saved along with the rest of the world
+    if let Err(e) = snapshot_chunks(&save_path) {
+        world.send_event(GameError::from(SaveError::from(e)));
+        world
+            .resource_mut::<NextState<AppState>>()
+            .set(AppState::Main);
+        return;
+    }
 
     // Save this scene handle to disk
-    IoTaskPool::get()
-        .spawn(async move {
-            // Write the scene RON data to file
-            File::create(save_path.clone())
-                .and_then(|mut file| file.write(serialized_scene.as_bytes()))
-                .expect("Error while writing scene to file");
-        })
-        .detach();
+    let task = IoTaskPool::get().spawn(async move {
+        // Write the save RON data to file, keeping the previous saves as backups
+        write_save(&save_path, &serialized_save, backups)?;
+        commit_chunks(&save_path).map_err(SaveError::from)
+    });
+    world.insert_resource(WritingSave(task));
 
     world
         .resource_mut::<NextState<AppState>>()
         .set(AppState::Main);
 }
 
-/// Load an entire save game
+/// Report the outcome of the save being written once it finishes
+fn finish_writing_save(
+    mut cmd: Commands,
+    mut since_save: ResMut<SinceSave>,
+    writing: Option<ResMut<WritingSave>>,
+) -> Result<(), SaveError> {
+    let Some(mut writing) = writing else {
+        return Ok(());
+    };
+    match block_on(future::poll_once(&mut writing.0)) {
+        Some(result) => {
+            cmd.remove_resource::<WritingSave>();
+            if result.is_ok() {
+                **since_save = Duration::ZERO;
+            }
+            result
+        }
+        None => Ok(()),
+    }
+}
+
+/// Summarize the world for the save browser
+fn save_metadata(world: &mut World) -> SaveMetadata {
+    let (player_name, credits) = world
+        .query_filtered::<(Option<&Name>, Option<&Credits>), With<Player>>()
+        .iter(world)
+        .next()
+        .map(|(name, credits)| {
+            (
+                name.map(ToString::to_string).unwrap_or_default(),
+                credits.map(Credits::get).unwrap_or_default(),
+            )
+        })
+        .unwrap_or_default();
+    SaveMetadata {
+        player_name,
+        credits,
+        play_time: **world.resource::<PlayTime>(),
+        chunks: world
+            .get_resource::<Chunks>()
+            .map(Chunks::len)
+            .unwrap_or_default(),
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|since| since.as_secs())
+            .unwrap_or_default(),
+        game_version: env!("CARGO_PKG_VERSION").to_string(),
+    }
+}
+
+fn track_play_time(mut play_time: ResMut<PlayTime>, time: Res<Time>) {
+    **play_time += time.delta();
+}
+
+/// Periodically save to the current save path. The interval counts from the last save that was
+/// written successfully.
+fn autosave(
+    mut next_state: ResMut<NextState<AppState>>,
+    mut since_save: ResMut<SinceSave>,
+    mut last_attempt: Local<Option<Duration>>,
+    writing: Option<Res<WritingSave>>,
+    save_path: Res<SavePath>,
+    library: Res<Library>,
+    settings: Res<Assets<Settings>>,
+    time: Res<Time>,
+) {
+    let Some(interval) = settings
+        .get(&library.settings)
+        .unwrap()
+        .save
+        .autosave_interval
+    else {
+        return;
+    };
+    // Time spent writing a save does not count towards the next one
+    if writing.is_some() {
+        return;
+    }
+    **since_save += time.delta();
+
+    // A failed autosave is only retried once another interval has passed
+    let retry = last_attempt.map_or(true, |at| {
+        time.elapsed().saturating_sub(at).as_secs_f32() >= interval
+    });
+    if since_save.as_secs_f32() >= interval && retry {
+        if let Some(save_path) = save_path.0.clone() {
+            *last_attempt = Some(time.elapsed());
+            next_state.set(AppState::SaveGame { save_path });
+        }
+    }
+}
+
+/// Load an entire save game, upgrading it from older save versions if needed
 fn enter_load_game(
     mut cmd: Commands,
-    assets: Res<AssetServer>,
+    mut scenes: ResMut<Assets<DynamicScene>>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut errors: EventWriter<GameError>,
     entities: Query<Entity, (Without<ObserverState>, Without<Window>)>,
+    writing: Option<ResMut<WritingSave>>,
     state: Res<State<AppState>>,
-) {
+    migrations: Res<SaveMigrations>,
+    registry: Res<AppTypeRegistry>,
+) -> Result<(), SaveError> {
     let AppState::LoadGame { path } = state.get() else {
         unreachable!()
     };
 
+    // Finish writing the previous save, so it is complete before it is read back
+    if let Some(mut writing) = writing {
+        if let Err(e) = block_on(&mut writing.0) {
+            errors.send(e.into());
+        }
+        cmd.remove_resource::<WritingSave>();
+    }
+
+    // Read and migrate the save before cleaning up, so a bad save leaves the world intact
+    let loaded = {
+        let registry = registry.read();
+        std::fs::read_to_string(path)
+            .map_err(SaveError::from)
+            .and_then(|text| SaveFile::from_ron(&text, &migrations, &registry))
+            .and_then(|save| {
+                let play_time = save.header.metadata.play_time;
+                Ok((save.into_scene(&registry)?, play_time))
+            })
+            .and_then(|loaded| {
+                // Saves from before zones existed keep their chunks where the starting zone now
+                // looks for them
+                move_legacy_chunks(&primary_save_path(path))?;
+                // Play from the chunks that were written along with this save
+                stage_chunks(path)?;
+                Ok(loaded)
+            })
+    };
+    let (scene, play_time) = match loaded {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            next_state.set(AppState::menu());
+            return Err(e);
+        }
+    };
+
     // Clean up
     for entity in entities.iter() {
         cmd.entity(entity).despawn_recursive();
     }
-    // Begin load the given path
-    let scene = assets.load::<DynamicScene>(AssetPath::from_path(path));
-    cmd.insert_resource(CurrentlyLoading(scene));
+    cmd.insert_resource(CurrentlyLoading(scenes.add(scene)));
+    cmd.insert_resource(PlayTime(play_time));
+    Ok(())
 }
 
 /// Wait for the loaded savegame to deserialize, then spawn the world
@@ -277,11 +317,14 @@ fn continue_loading(
     mut cmd: Commands,
     mut next_state: ResMut<NextState<AppState>>,
     state: Res<State<AppState>>,
-    loading: Res<CurrentlyLoading>,
-    asset_server: Res<AssetServer>,
+    loading: Option<Res<CurrentlyLoading>>,
+    scenes: Res<Assets<DynamicScene>>,
 ) {
-    if LoadState::Loaded == asset_server.load_state(loading.0.id()) {
-        // The asset is fully loaded. Spawn the scene then transition states
+    let Some(loading) = loading else {
+        return;
+    };
+    if scenes.contains(loading.0.id()) {
+        // The scene is ready. Spawn it then transition states
         cmd.spawn(DynamicSceneBundle {
             scene: loading.0.clone(),
             ..Default::default()
@@ -289,19 +332,20 @@ fn continue_loading(
         let AppState::LoadGame { path } = state.get() else {
             unreachable!()
         };
-        cmd.insert_resource(SavePath(Some(path.clone())));
+        cmd.remove_resource::<CurrentlyLoading>();
+        cmd.insert_resource(SavePath(Some(primary_save_path(path))));
         next_state.set(AppState::Main);
     }
 }
 
-/// Add some special components to the player each time
+/// Add some special components to the player when it is first spawned or loaded
 pub fn finalize_player(
     mut cmd: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<BackgroundMaterial>>,
     library: Res<Library>,
     settings: Res<Assets<Settings>>,
-    players: Query<Entity, With<Player>>,
+    players: Query<Entity, (With<Player>, Without<InputMap<Action>>)>,
 ) {
     let settings = settings.get(&library.settings).unwrap();
     for player_entity in players.iter() {
@@ -327,9 +371,30 @@ pub fn finalize_player(
                             settings.controls.keyboard.thrust,
                         ),
                     )
+                    .with(Action::Afterburner, settings.controls.keyboard.afterburner)
                     .with(Action::Fire, settings.controls.keyboard.fire)
+                    .with(
+                        Action::FireSecondary,
+                        settings.controls.keyboard.fire_secondary,
+                    )
                     .with(Action::Take, settings.controls.keyboard.take)
-                    .with(Action::Interact, settings.controls.keyboard.interact),
+                    .with(Action::Interact, settings.controls.keyboard.interact)
+                    .with(
+                        Action::PowerEngines,
+                        settings.controls.keyboard.power_engines,
+                    )
+                    .with(
+                        Action::PowerShields,
+                        settings.controls.keyboard.power_shields,
+                    )
+                    .with(
+                        Action::PowerWeapons,
+                        settings.controls.keyboard.power_weapons,
+                    )
+                    .with(
+                        Action::PowerBalance,
+                        settings.controls.keyboard.power_balance,
+                    ),
             ))
             .with_children(|cmd| {
                 cmd.spawn((
@@ -348,6 +413,23 @@ pub fn finalize_player(
     }
 }
 
+/// Rebuild the render-only components of loaded directional lights, which are not saved
+pub fn finalize_lights(
+    mut cmd: Commands,
+    lights: Query<Entity, (With<DirectionalLight>, Without<CascadesFrusta>)>,
+) {
+    for entity in lights.iter() {
+        cmd.entity(entity).insert((
+            CascadesFrusta::default(),
+            Cascades::default(),
+            CascadesVisibleEntities::default(),
+            Visibility::default(),
+            InheritedVisibility::default(),
+            ViewVisibility::default(),
+        ));
+    }
+}
+
 pub fn spawn_camera(mut cmd: Commands, camera: Query<(), With<Camera>>) {
     if camera.is_empty() {
         // Spawn camera
@@ -385,11 +467,26 @@ pub fn enter_new_game(
     mut save_path: ResMut<SavePath>,
     mut next_state: ResMut<NextState<AppState>>,
     mut factions: ResMut<Factions>,
+    state: Res<State<AppState>>,
     directories: Res<Directories>,
     crafts: Res<Assets<Craft>>,
+    settings: Res<Assets<Settings>>,
     library: Res<Library>,
 ) {
+    let AppState::NewGame { seed } = state.get() else {
+        unreachable!()
+    };
+    let seed = seed.unwrap_or_else(WorldSeed::random);
+    cmd.insert_resource(seed);
+    cmd.insert_resource(GlobalRng::with_seed(seed.0));
     cmd.insert_resource(Chunks::default());
+    let settings = settings.get(&library.settings).unwrap();
+    cmd.insert_resource(Universe::generate(
+        settings.zones.depth,
+        settings.zones.width,
+        &mut Rng::with_seed(seed.0),
+    ));
+    cmd.insert_resource(PlayTime::default());
     // Set the new save path
     let save_name = format!(
         "{}-{}.save.ron",
@@ -397,15 +494,11 @@ pub fn enter_new_game(
         random_word::gen(random_word::Lang::En)
     );
     **save_path = Some(directories.data_dir().join(save_name));
+    // Generate every chunk the player can see from the start
     cmd.trigger(triggers::GenerateChunks {
-        // Spawn in a cross shape
-        chunk_indicies: vec![
-            (0, 0).into(),
-            (0, 1).into(),
-            (1, 0).into(),
-            (-1, 0).into(),
-            (-1, -0).into(),
-        ],
+        chunk_indicies: ChunkIndex::from((0, 0))
+            .around(settings.chunks.load_radius)
+            .collect(),
     });
 
     let player_faction = factions
@@ -429,6 +522,7 @@ pub fn enter_new_game(
     // Spawn player
     cmd.spawn((
         Player(0), // TODO: handle IDs for multiplayer
+        RespawnPoint::default(),
         Persistent,
         Name::new("player"),
         ChestsInRange {
@@ -452,15 +546,22 @@ pub fn enter_new_game(
                     "ion.battery",
                     "iron.armor",
                     "iron.armor",
+                    "buckler.shield",
+                    "heatsink.radiator",
+                    "sprint.engine",
                 ]
                 .map(ToString::to_string)
                 .into(),
+                metadata: Vec::new(),
                 slots: [
-                    (EquipmentTypeId::Weapon, 1),
+                    (EquipmentTypeId::Weapon, 2),
                     (EquipmentTypeId::RepairBot, 1),
                     (EquipmentTypeId::Generator, 1),
                     (EquipmentTypeId::Battery, 3),
                     (EquipmentTypeId::Armor, 3),
+                    (EquipmentTypeId::Shield, 1),
+                    (EquipmentTypeId::Radiator, 1),
+                    (EquipmentTypeId::Engine, 1),
                 ]
                 .into(),
             },
@@ -490,3 +591,11 @@ pub fn enter_new_game(
 /// Local-ish resource
 #[derive(Resource)]
 struct CurrentlyLoading(pub Handle<DynamicScene>);
+
+/// Save that is being written to disk in the background
+#[derive(Resource)]
+struct WritingSave(Task<Result<(), SaveError>>);
+
+/// Time since a save was last written successfully
+#[derive(Resource, Default, Deref, DerefMut)]
+struct SinceSave(Duration);
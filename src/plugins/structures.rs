@@ -13,11 +13,15 @@ impl Plugin for StructuresPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<DockEvent>()
             .add_event::<StoreEvent>()
+            .register_saveable::<Dockings>()
+            .register_saveable::<DockInRange>()
+            .register_saveable::<Docked>()
             .add_systems(
                 Update,
                 (
                     update_dock_in_ranges,
                     update_dockings,
+                    restore_docking_joints,
                     handle_store_events.pipe(handle_errors::<StoreError>),
                 )
                     .run_if(in_state(AppState::main())),
@@ -61,13 +65,7 @@ fn update_dockings(
         match event {
             DockEvent::Dock { to_dock, dock } => {
                 cmd.entity(*to_dock).insert(Docked(*dock));
-                let joint = cmd
-                    .spawn((DistanceJoint::new(*to_dock, *dock)
-                        .with_limits(0.0, 0.5)
-                        .with_linear_velocity_damping(0.05)
-                        .with_angular_velocity_damping(0.05)
-                        .with_compliance(0.005),))
-                    .id();
+                let joint = cmd.spawn((docking_joint(*to_dock, *dock),)).id();
                 dockings.get_mut(*dock).unwrap().insert(*to_dock, joint);
             }
             DockEvent::Undock { to_undock } => {
@@ -87,6 +85,33 @@ fn update_dockings(
     }
 }
 
+/// The constraint that holds a docked craft to its dock
+fn docking_joint(to_dock: Entity, dock: Entity) -> DistanceJoint {
+    DistanceJoint::new(to_dock, dock)
+        .with_limits(0.0, 0.5)
+        .with_linear_velocity_damping(0.05)
+        .with_angular_velocity_damping(0.05)
+        .with_compliance(0.005)
+}
+
+/// Joints are not saved, so rebuild any that are missing once a dock is loaded. Dockings whose
+/// craft no longer considers itself docked here are dropped.
+pub(crate) fn restore_docking_joints(
+    mut cmd: Commands,
+    mut dockings: Query<(Entity, &mut Dockings), Added<Dockings>>,
+    docked: Query<&Docked>,
+    joints: Query<(), With<DistanceJoint>>,
+) {
+    for (dock, mut dockings) in dockings.iter_mut() {
+        dockings.retain(|to_dock, _| docked.get(*to_dock).is_ok_and(|docked| **docked == dock));
+        for (to_dock, joint) in dockings.iter_mut() {
+            if !joints.contains(*joint) {
+                *joint = cmd.spawn((docking_joint(*to_dock, dock),)).id();
+            }
+        }
+    }
+}
+
 fn handle_store_events(
     mut events: EventReader<StoreEvent>,
     mut credits: Query<&mut Credits>,
@@ -107,52 +132,24 @@ fn handle_store_events(
                 item,
                 quantity,
             } => {
-                let is_buy_event = matches!(event, StoreEvent::Buy { .. });
-                dbg!("a");
-                let [patron_credits, store_credits] =
-                    credits.get_many_mut([*patron, *store_entity])?;
-                dbg!("b");
-                let mut inventory = inventories.get_mut(*patron)?;
-                let retrieved_item = items.get(item).unwrap();
-                let (mut from_credits, mut to_credits) = {
-                    match is_buy_event {
-                        // Moving into the player inventory. ensure enough space
-                        true => {
-                            if inventory.space_remaining() >= retrieved_item.size * quantity {
-                                (patron_credits, store_credits)
-                            } else {
-                                return Err(InventoryError::NoSpaceLeft {
-                                    overage: (retrieved_item.size * quantity)
-                                        - inventory.space_remaining(),
-                                }
-                                .into());
-                            }
-                        }
-                        // Moving out of the player inventory. ensure items exist
-                        false => {
-                            if inventory.count(item) >= *quantity {
-                                (store_credits, patron_credits)
-                            } else {
-                                return Err(StoreError::NotEnoughItems);
-                            }
-                        }
-                    }
-                };
-
-                let Item { size, value, .. } = items
-                    .get(item)
-                    .expect("item should exist with given handle");
+                let Item { value, .. } = items.get(item).ok_or(InventoryError::ItemNotFound)?;
                 let total_cost = value * quantity;
-                if from_credits.get() < total_cost {
-                    return Err(StoreError::NotEnoughCredits);
-                }
+                let transaction = match event {
+                    StoreEvent::Buy { .. } => InventoryTransaction::default()
+                        .add(*patron, ItemStack::new(item.clone(), *quantity))
+                        .pay(*patron, *store_entity, total_cost),
+                    StoreEvent::Sell { .. } => InventoryTransaction::default()
+                        .remove_any(*patron, item.clone(), *quantity)
+                        .pay(*store_entity, *patron, total_cost),
+                };
                 // Commit inventory and credit transfer
-                from_credits.transfer(&mut to_credits, total_cost)?;
-                if is_buy_event {
-                    inventory.add(item.clone(), *size, *quantity)?;
-                } else {
-                    inventory.remove(item, *size, *quantity)?;
-                }
+                transaction
+                    .commit(&mut inventories, &mut credits, &items)
+                    .map_err(|e| match e {
+                        InventoryError::InsufficientItems { .. } => StoreError::NotEnoughItems,
+                        InventoryError::CreditsError(_) => StoreError::NotEnoughCredits,
+                        e => e.into(),
+                    })?;
             }
         }
     }
@@ -5,22 +5,50 @@ use bevy_egui::{
     egui::{self, Align2, Color32, Frame, RichText, Stroke},
     EguiContexts,
 };
+use petgraph::graph::NodeIndex;
 use widgets::Bar;
 
+/// State of the cargo panel that is kept between frames
+#[derive(Default)]
+pub(super) struct CargoPanel {
+    /// Item shown in the inspector, along with the metadata of its stack
+    selected_item: Option<(Item, ItemMetadata)>,
+    /// Only items whose name, category or tags contain this text are listed
+    filter: String,
+    /// Order the items are listed in
+    sort: CargoSort,
+}
+
+/// Order of the items in the cargo panel
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+enum CargoSort {
+    /// The order of the inventory slots
+    #[default]
+    Slot,
+    /// Rarest first
+    Rarity,
+    /// Most valuable first
+    Value,
+    /// Alphabetical
+    Name,
+}
+
 pub(super) fn draw_ui(
     mut cmd: Commands,
     mut contexts: EguiContexts,
-    mut selected_item: Local<Option<Item>>,
+    mut cargo: Local<CargoPanel>,
     mut store_events: EventWriter<events::StoreEvent>,
     mut next_state: ResMut<NextState<AppState>>,
     mut current_save: ResMut<SavePath>,
-    heat: Query<&Heat>,
+    heat: Query<(&Heat, Option<&Overheated>)>,
     item_handles: Query<&Handle<Item>>,
     items: Res<Assets<Item>>,
     inventories: Query<&Inventory>,
     batteries: Query<&Battery>,
     children: Query<&Children>,
     stores: Query<&Store>,
+    gates: Query<&Gate>,
+    universe: Res<Universe>,
     player: Query<
         (
             Entity,
@@ -36,6 +64,11 @@ pub(super) fn draw_ui(
         With<Player>,
     >,
 ) {
+    let CargoPanel {
+        selected_item,
+        filter,
+        sort,
+    } = &mut *cargo;
     if let Ok((
         player_entity,
         energy,
@@ -121,14 +154,22 @@ pub(super) fn draw_ui(
                                                         }
                                                     });
 
-                                                    // Show heat if applicable
-                                                    if let Ok(heat) = heat.get(*equip_entity) {
+                                                    // Show heat if applicable. Overheated
+                                                    // equipment is disabled until it cools down.
+                                                    if let Ok((heat, maybe_overheated)) =
+                                                        heat.get(*equip_entity)
+                                                    {
                                                         ui.add(Bar {
                                                             size: (100f32, 3f32).into(),
                                                             range: 0f32..=1f32,
                                                             value: heat.get(),
                                                             stroke: Stroke::NONE,
-                                                            fill: Color32::RED,
+                                                            fill: match maybe_overheated {
+                                                                Some(_) => Color32::RED,
+                                                                None => {
+                                                                    Color32::from_rgb(255, 140, 0)
+                                                                }
+                                                            },
                                                             ..Default::default()
                                                         });
                                                     }
@@ -188,24 +229,64 @@ pub(super) fn draw_ui(
                     player_inventory.space_occupied(),
                     player_inventory.capacity()
                 ));
+                ui.horizontal(|ui| {
+                    ui.label("Filter");
+                    ui.text_edit_singleline(filter);
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Sort");
+                    ui.selectable_value(sort, CargoSort::Slot, "slot");
+                    ui.selectable_value(sort, CargoSort::Rarity, "rarity");
+                    ui.selectable_value(sort, CargoSort::Value, "value");
+                    ui.selectable_value(sort, CargoSort::Name, "name");
+                });
                 {
-                    for (item, count) in player_inventory.iter() {
-                        let retrieved_item =
-                            items.get(item).ok_or(InventoryError::ItemNotFound).unwrap();
+                    let filter = filter.trim().to_lowercase();
+                    let mut cargo = player_inventory
+                        .iter()
+                        .map(|stack| {
+                            let retrieved_item = items
+                                .get(&stack.item)
+                                .ok_or(InventoryError::ItemNotFound)
+                                .unwrap();
+                            (stack, retrieved_item)
+                        })
+                        .filter(|(_, item)| {
+                            filter.is_empty()
+                                || item.name.to_lowercase().contains(&filter)
+                                || item.equipment_type_str().contains(&filter)
+                                || item
+                                    .tags
+                                    .iter()
+                                    .any(|tag| tag.to_lowercase().contains(&filter))
+                        })
+                        .collect::<Vec<_>>();
+                    match sort {
+                        CargoSort::Slot => (),
+                        CargoSort::Rarity => {
+                            cargo.sort_by(|(_, a), (_, b)| b.rarity.cmp(&a.rarity))
+                        }
+                        CargoSort::Value => cargo.sort_by(|(_, a), (_, b)| b.value.cmp(&a.value)),
+                        CargoSort::Name => cargo.sort_by(|(_, a), (_, b)| a.name.cmp(&b.name)),
+                    }
+
+                    for (stack, retrieved_item) in cargo {
                         ui.vertical(|ui| {
                             ui.horizontal(|ui| {
-                                ui.label(format!("{} {}", retrieved_item.name, count));
+                                ui.label(stack_label(stack, retrieved_item));
 
                                 if ui.button("Toss").clicked() {
                                     cmd.trigger(triggers::TossItemOverboard {
                                         entity: player_entity,
-                                        item: item.clone(),
+                                        item: stack.item.clone(),
                                         quantity: 1,
+                                        metadata: stack.metadata.clone(),
                                     });
                                 }
 
                                 if ui.button("Inspect").clicked() {
-                                    *selected_item = Some(retrieved_item.clone());
+                                    *selected_item =
+                                        Some((retrieved_item.clone(), stack.metadata.clone()));
                                 }
 
                                 if retrieved_item.equipment.is_some()
@@ -213,13 +294,20 @@ pub(super) fn draw_ui(
                                 {
                                     cmd.trigger(triggers::Equip {
                                         entity: player_entity,
-                                        item: item.clone(),
+                                        item: stack.item.clone(),
+                                        metadata: stack.metadata.clone(),
                                         transfer_from_inventory: true,
                                     });
                                 }
                             });
                         });
                     }
+                    // Slot based inventories also show their empty slots
+                    if let Some(slots) = player_inventory.slots() {
+                        for _ in player_inventory.iter().len()..slots {
+                            ui.label("Empty slot");
+                        }
+                    }
                 }
 
                 ui.separator();
@@ -228,23 +316,55 @@ pub(super) fn draw_ui(
                     ui.heading("Chests");
                     for chest in &chests_in_range.chests {
                         if let Ok(chest_inventory) = inventories.get(*chest) {
-                            for (item, amount) in chest_inventory.iter() {
-                                let retrieved_item =
-                                    items.get(item).ok_or(InventoryError::ItemNotFound).unwrap();
+                            ui.horizontal(|ui| {
+                                if ui.button("Take what fits").clicked() {
+                                    cmd.trigger(triggers::InventoryTransfer {
+                                        from: *chest,
+                                        to: player_entity,
+                                        transfer: triggers::InventoryTransferSettings::Fit,
+                                    });
+                                }
+                                // Take everything of one category in the chest, such as weapons
+                                let mut categories = chest_inventory
+                                    .iter()
+                                    .filter_map(|stack| items.get(&stack.item))
+                                    .map(Item::equipment_type_str)
+                                    .collect::<Vec<_>>();
+                                categories.sort();
+                                categories.dedup();
+                                for category in categories {
+                                    if ui.button(format!("Take {category}")).clicked() {
+                                        cmd.trigger(triggers::InventoryTransfer {
+                                            from: *chest,
+                                            to: player_entity,
+                                            transfer: triggers::InventoryTransferSettings::Category(
+                                                category.to_string(),
+                                            ),
+                                        });
+                                    }
+                                }
+                            });
+                            for stack in chest_inventory.iter() {
+                                let retrieved_item = items
+                                    .get(&stack.item)
+                                    .ok_or(InventoryError::ItemNotFound)
+                                    .unwrap();
                                 ui.horizontal(|ui| {
-                                    ui.label(format!("{} {}", retrieved_item.name, amount));
+                                    ui.label(stack_label(stack, retrieved_item));
                                     if ui.button("Take").clicked() {
                                         cmd.trigger(triggers::InventoryTransfer {
                                             from: *chest,
                                             to: player_entity,
                                             transfer: triggers::InventoryTransferSettings::Item {
-                                                item: item.clone(),
-                                                quantity: *amount,
+                                                item: stack.item.clone(),
+                                                quantity: stack.amount,
+                                                metadata: stack.metadata.clone(),
                                             },
                                         });
                                     }
                                     if ui.button("Inspect").clicked() {
-                                        *selected_item = Some(retrieved_item.clone());
+                                        *selected_item =
+                                            Some((retrieved_item.clone(), stack.metadata.clone()));
                                     }
                                 });
                             }
@@ -295,20 +415,50 @@ pub(super) fn draw_ui(
                                     });
                                 }
                             }
+
+                            // A gate only leads to its own destination
+                            if let Some((destination, zone)) =
+                                gates.get(***docked).ok().and_then(|gate| {
+                                    let destination = gate.destination;
+                                    universe
+                                        .zone(NodeIndex::new(destination))
+                                        .map(|zone| (destination, zone))
+                                })
+                            {
+                                ui.heading("Destination");
+                                ui.horizontal(|ui| {
+                                    ui.label(format!("{} (depth {})", zone.name, zone.depth));
+                                    if ui.button("Jump").clicked() {
+                                        next_state.set(AppState::Travel { destination });
+                                    }
+                                });
+                            }
                         });
                     });
                 }
             }
         });
-        if let Some(selected) = selected_item.as_ref() {
+        if let Some((selected, metadata)) = selected_item.as_ref() {
             egui::Window::new(&selected.name).show(contexts.ctx_mut(), |ui| {
                 ui.vertical(|ui| {
                     ui.label(format!("type: {}", selected.equipment_type_str(),));
+                    ui.label(format!("rarity: {}", selected.rarity));
+                    if !selected.tags.is_empty() {
+                        ui.label(format!("tags: {}", selected.tags.join(", ")));
+                    }
                     ui.label(format!("value: {}", selected.value));
                     ui.label(format!("size: {}", selected.size));
                     ui.label(format!("mass: {}", selected.mass));
+                    for modifier in metadata.modifiers.iter() {
+                        ui.label(RichText::new(modifier.to_string()).color(Color32::LIGHT_GREEN));
+                    }
 
-                    if let Some(eq) = &selected.equipment {
+                    // Stats are shown with modifiers applied
+                    let equipment = selected
+                        .equipment
+                        .clone()
+                        .map(|eq| eq.with_modifiers(&metadata.modifiers));
+                    if let Some(eq) = &equipment {
                         match eq {
                             EquipmentType::Weapon(w) => match &w.weapon_type {
                                 WeaponType::ProjectileWeapon {
@@ -318,14 +468,20 @@ pub(super) fn draw_ui(
                                     spread,
                                     shots,
                                     damage,
+                                    damage_type,
                                     radius,
                                     lifetime,
                                     energy,
                                     projectile_model: _,
                                     distance,
+                                    blast,
+                                    heat_per_shot,
                                 } => {
                                     ui.heading("projectile weapon");
-                                    ui.label(format!("damage: {}", damage));
+                                    ui.label(format!("damage: {} {}", damage, damage_type));
+                                    if let Some(blast) = blast {
+                                        ui.label(format!("blast radius: {}", blast.radius));
+                                    }
                                     ui.label(format!("energy: {}", energy));
                                     ui.label(format!("recoil: {}s", recoil));
                                     ui.label(format!("shots: {}", shots));
@@ -335,10 +491,15 @@ pub(super) fn draw_ui(
                                     ui.label(format!("speed: {}", speed));
                                     ui.label(format!("size: {}", radius * 2f32));
                                     ui.label(format!("tracking: {}", tracking));
+                                    ui.label(format!(
+                                        "heat generated: {:.0}%/shot",
+                                        heat_per_shot * 100.0
+                                    ));
                                 }
                                 WeaponType::LaserWeapon {
                                     tracking,
                                     damage_per_second,
+                                    damage_type,
                                     energy_per_second,
                                     range,
                                     width,
@@ -349,32 +510,108 @@ pub(super) fn draw_ui(
                                 } => {
                                     ui.heading("laser");
                                     ui.label(format!("range: {range}"));
-                                    ui.label(format!("damage: {damage_per_second}/s"));
+                                    ui.label(format!(
+                                        "damage: {damage_per_second}/s {damage_type}"
+                                    ));
                                     ui.label(format!("energy: {energy_per_second}/s"));
                                     ui.label(format!("width: {width}"));
                                     ui.label(format!("tracking: {tracking}"));
                                     ui.label(format!("activation energy: {activation_energy}"));
                                     ui.label(format!(
-                                        "heat generated: {:2}%/s",
-                                        heat_per_second * 10.0
+                                        "heat generated: {:.0}%/s",
+                                        heat_per_second * 100.0
                                     ));
                                     ui.label(format!(
-                                        "cooling: {:2}%/s",
-                                        cooling_per_second * 10.0
+                                        "cooling: {:.0}%/s",
+                                        cooling_per_second * 100.0
                                     ));
                                 }
+                                WeaponType::MissileWeapon {
+                                    tracking,
+                                    lock_on,
+                                    speed,
+                                    max_speed,
+                                    acceleration,
+                                    turn_rate,
+                                    recoil,
+                                    fuel,
+                                    damage,
+                                    proximity,
+                                    blast,
+                                    health,
+                                    radius: _,
+                                    energy,
+                                    projectile_model: _,
+                                } => {
+                                    ui.heading("missile launcher");
+                                    ui.label(format!("damage: {damage}"));
+                                    ui.label(format!("blast radius: {}", blast.radius));
+                                    ui.label(format!("proximity: {proximity}"));
+                                    ui.label(format!("energy: {energy}"));
+                                    ui.label(format!("recoil: {recoil}s"));
+                                    ui.label(format!("lock on: {lock_on}s"));
+                                    ui.label(format!("tracking: {tracking}"));
+                                    ui.label(format!("speed: {speed} to {max_speed}"));
+                                    ui.label(format!("acceleration: {acceleration}"));
+                                    ui.label(format!("turn rate: {turn_rate}/s"));
+                                    ui.label(format!("fuel: {fuel}s"));
+                                    ui.label(format!("health: {health}"));
+                                }
                             },
                             EquipmentType::RepairBot(r) => {
                                 ui.label(format!("repair rate: {}/s", r.rate));
+                                ui.label(format!(
+                                    "heat generated: {:.0}%/s",
+                                    r.heat_per_second * 100.0
+                                ));
                             }
                             EquipmentType::Generator(e) => {
                                 ui.label(format!("recharge rate: {}/s", e.recharge_rate));
+                                ui.label(format!(
+                                    "heat generated: {:.0}%/s",
+                                    e.heat_per_second * 100.0
+                                ));
                             }
                             EquipmentType::Battery(b) => {
                                 ui.label(format!("capacity: {}", b.capacity()));
                             }
                             EquipmentType::Armor(a) => {
                                 ui.label(format!("armor: {}", a.health));
+                                for (damage_type, resistance) in a.resistances.iter() {
+                                    ui.label(format!(
+                                        "{damage_type} resistance: {:.0}%",
+                                        resistance * 100f32
+                                    ));
+                                }
+                            }
+                            EquipmentType::Shield(s) => {
+                                ui.label(format!("capacity: {}", s.capacity));
+                                ui.label(format!("recharge rate: {}/s", s.recharge_rate));
+                                ui.label(format!("energy: {} per charge", s.energy_per_charge));
+                                ui.label(format!("recharge delay: {}s", s.recharge_delay));
+                            }
+                            EquipmentType::Radiator(r) => {
+                                ui.label(format!("cooling: {:.0}%/s", r.dissipation * 100.0));
+                            }
+                            EquipmentType::Engine(e) => {
+                                ui.label(format!("thrust: {:+.0}%", e.thrust * 100.0));
+                                ui.label(format!("turn rate: {:+.0}%", e.turn * 100.0));
+                                ui.label(format!("top speed: {:+.0}%", e.top_speed * 100.0));
+                                if let Some(a) = e.afterburner {
+                                    ui.label(format!(
+                                        "afterburner: {:+.0}% thrust, {:+.0}% top speed",
+                                        a.thrust * 100.0,
+                                        a.top_speed * 100.0
+                                    ));
+                                    ui.label(format!(
+                                        "afterburner energy: {}/s",
+                                        a.energy_per_second
+                                    ));
+                                    ui.label(format!(
+                                        "afterburner heat: {:.0}%/s",
+                                        a.heat_per_second * 100.0
+                                    ));
+                                }
                             }
                         }
                     }
@@ -388,6 +625,7 @@ pub(super) fn draw_minimaps(
     mut contexts: EguiContexts,
     query: Query<(&'static GlobalTransform, &'static Collider)>,
     player_transform: Query<&Transform, With<Player>>,
+    universe: Res<Universe>,
 ) {
     if let Ok(player_transform) = player_transform.get_single() {
         let translation = player_transform.translation.truncate();
@@ -395,6 +633,8 @@ pub(super) fn draw_minimaps(
             .interactable(false)
             .anchor(Align2::RIGHT_TOP, (0f32, 0f32))
             .show(contexts.ctx_mut(), |ui| {
+                let zone = universe.current_zone();
+                ui.label(format!("{} (depth {})", zone.name, zone.depth));
                 ui.add(widgets::ZoneMap {
                     size: egui::Vec2::new(240f32, 240f32),
                     scale: 4f32,
@@ -404,3 +644,162 @@ pub(super) fn draw_minimaps(
             });
     }
 }
+
+/// Label of a stack of items, showing the name given by the player and durability if any
+fn stack_label(stack: &ItemStack, item: &Item) -> String {
+    let name = stack.metadata.name.as_ref().unwrap_or(&item.name);
+    match stack.metadata.durability {
+        Some(durability) => format!("{name} {} ({:.0}%)", stack.amount, durability * 100.),
+        None => format!("{name} {}", stack.amount),
+    }
+}
+
+/// Show the recipes of the fabricator the player is docked with, along with the player's queue
+pub(super) fn draw_fabricator(
+    mut cmd: Commands,
+    mut contexts: EguiContexts,
+    player: Query<(Entity, &Docked, Option<&FabricationQueue>), With<Player>>,
+    fabricators: Query<&Name, With<Fabricator>>,
+    library: Res<Library>,
+    recipes: Res<Assets<Recipe>>,
+) {
+    let Ok((player_entity, docked, maybe_queue)) = player.get_single() else {
+        return;
+    };
+    let Ok(building) = fabricators.get(**docked) else {
+        return;
+    };
+
+    // Sort recipes so they are always listed in the same order
+    let mut available = library
+        .recipes
+        .iter()
+        .filter_map(|(path, handle)| {
+            let name = path
+                .trim_start_matches("recipes/")
+                .trim_end_matches(".recipe.ron");
+            Some((name, recipes.get(handle)?))
+        })
+        .filter(|(_, recipe)| recipe.building == building.as_str())
+        .collect::<Vec<_>>();
+    available.sort_by(|(_, a), (_, b)| a.name.cmp(&b.name));
+
+    egui::Window::new("fabricator").show(contexts.ctx_mut(), |ui| {
+        for (name, recipe) in available {
+            ui.horizontal(|ui| {
+                ui.label(format!(
+                    "{}: {} into {} ({} credits, {}s)",
+                    recipe.name,
+                    format_amounts(&recipe.inputs),
+                    format_amounts(&recipe.outputs),
+                    recipe.cost,
+                    recipe.time
+                ));
+                if ui.button("Fabricate").clicked() {
+                    cmd.trigger(triggers::Fabricate {
+                        patron: player_entity,
+                        fabricator: **docked,
+                        recipe: name.to_string(),
+                    });
+                }
+            });
+        }
+
+        if let Some(queue) = maybe_queue.filter(|queue| !queue.is_empty()) {
+            ui.separator();
+            ui.heading("Queue");
+            for job in queue.iter() {
+                ui.label(format!("{} {:.0}s", job.recipe, job.remaining));
+            }
+        }
+    });
+}
+
+/// Fire modes that can be picked for a weapon
+const FIRE_MODES: [FireMode; 4] = [
+    FireMode::Hold,
+    FireMode::Toggle,
+    FireMode::Burst(1f32),
+    FireMode::Alternating,
+];
+
+pub(super) fn draw_weapon_controls(
+    mut cmd: Commands,
+    mut contexts: EguiContexts,
+    player: Query<&Equipped, With<Player>>,
+    weapons: Query<(&Weapon, &Name)>,
+) {
+    let Ok(equipped) = player.get_single() else {
+        return;
+    };
+    let Some(entities) = equipped.equipped.get(&EquipmentTypeId::Weapon) else {
+        return;
+    };
+    // Sort weapons so they are always listed in the same order
+    let mut player_weapons = entities
+        .iter()
+        .filter_map(|entity| Some((*entity, weapons.get(*entity).ok()?)))
+        .collect::<Vec<_>>();
+    player_weapons.sort_by_key(|(entity, _)| *entity);
+
+    egui::Window::new("weapons").show(contexts.ctx_mut(), |ui| {
+        for (entity, (weapon, name)) in player_weapons {
+            let mut fire_control = weapon.fire_control;
+            ui.horizontal(|ui| {
+                ui.label(name.as_str());
+                egui::ComboBox::from_id_source(("group", entity))
+                    .selected_text(fire_control.group.to_string())
+                    .show_ui(ui, |ui| {
+                        for group in [WeaponGroup::Primary, WeaponGroup::Secondary] {
+                            ui.selectable_value(&mut fire_control.group, group, group.to_string());
+                        }
+                    });
+                egui::ComboBox::from_id_source(("mode", entity))
+                    .selected_text(fire_control.mode.to_string())
+                    .show_ui(ui, |ui| {
+                        for mode in FIRE_MODES {
+                            ui.selectable_value(&mut fire_control.mode, mode, mode.to_string());
+                        }
+                    });
+            });
+            if fire_control != weapon.fire_control {
+                cmd.trigger(triggers::ConfigureWeapon {
+                    weapon: entity,
+                    fire_control,
+                });
+            }
+        }
+    });
+}
+
+/// List item names with their amounts, such as `scrap_metal.item 10, ion.battery 1`
+fn format_amounts(amounts: &[(String, usize)]) -> String {
+    amounts
+        .iter()
+        .map(|(name, amount)| format!("{name} {amount}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Let the player respawn once it is destroyed
+pub(super) fn draw_death_screen(
+    mut cmd: Commands,
+    mut contexts: EguiContexts,
+    player: Query<Entity, (With<Player>, With<Destroyed>)>,
+) {
+    let Ok(player_entity) = player.get_single() else {
+        return;
+    };
+    egui::Window::new("destroyed")
+        .anchor(Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .collapsible(false)
+        .resizable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.label("Your craft was destroyed. Some of its cargo was left behind.");
+            if ui.button("Respawn").clicked() {
+                cmd.trigger(triggers::Respawn {
+                    player: player_entity,
+                });
+            }
+        });
+}
@@ -1,11 +1,12 @@
 use crate::prelude::*;
 use bevy::prelude::*;
 use bevy_egui::*;
-use egui::Align2;
+use egui::{Align2, Color32};
 
 pub(super) fn draw_hud(
     mut contexts: EguiContexts,
-    healths: Query<(&Transform, &Health, &Damage), Without<Destroyed>>,
+    healths: Query<(&Transform, &Health, &Damage, Option<&Children>), Without<Destroyed>>,
+    shields: Query<&Shield>,
     camera: Query<(&Camera, &GlobalTransform)>,
 ) {
     if let Ok((camera, global_transform)) = camera.get_single() {
@@ -14,7 +15,7 @@ pub(super) fn draw_hud(
             .anchor(Align2::LEFT_TOP, egui::Vec2::ZERO)
             .default_size(contexts.ctx_mut().screen_rect().size())
             .show(contexts.ctx_mut(), |ui| {
-                for (transform, health, damage) in healths.iter() {
+                for (transform, health, damage, maybe_children) in healths.iter() {
                     if let Some(viewport_position) =
                         camera.world_to_viewport(global_transform, transform.translation)
                     {
@@ -28,8 +29,61 @@ pub(super) fn draw_hud(
                             )),
                             ..Default::default()
                         });
+
+                        // Shields are shown below health, if there are any
+                        let (charge, capacity) = maybe_children
+                            .into_iter()
+                            .flat_map(|children| children.iter())
+                            .filter_map(|child| shields.get(*child).ok())
+                            .fold((0f32, 0f32), |(charge, capacity), shield| {
+                                (charge + shield.charge, capacity + shield.capacity)
+                            });
+                        if capacity > 0f32 {
+                            ui.add(widgets::Bar {
+                                size: (32f32, 4f32).into(),
+                                range: 0f32..=capacity,
+                                value: charge,
+                                fill: Color32::LIGHT_BLUE,
+                                position: Some(egui::Pos2::new(
+                                    viewport_position.x,
+                                    viewport_position.y + 42f32,
+                                )),
+                                ..Default::default()
+                            });
+                        }
                     }
                 }
             });
     }
 }
+
+/// Show how the player's power is distributed between systems. Starved systems are shown in red.
+pub(super) fn draw_power(
+    mut contexts: EguiContexts,
+    player: Query<&PowerDistribution, (With<Player>, Without<Destroyed>)>,
+    time: Res<Time>,
+) {
+    let Ok(power) = player.get_single() else {
+        return;
+    };
+    egui::Area::new("power".into())
+        .interactable(false)
+        .anchor(Align2::LEFT_BOTTOM, (16f32, -16f32))
+        .show(contexts.ctx_mut(), |ui| {
+            for system in PowerSystem::ALL {
+                ui.horizontal(|ui| {
+                    ui.add(widgets::Bar {
+                        size: (64f32, 8f32).into(),
+                        range: 0f32..=PowerDistribution::MAX_PIPS as f32,
+                        value: power.pips(system) as f32,
+                        fill: match power.is_starved(system, time.elapsed()) {
+                            true => Color32::RED,
+                            false => Color32::YELLOW,
+                        },
+                        ..Default::default()
+                    });
+                    ui.label(system.to_string());
+                });
+            }
+        });
+}
@@ -1,30 +1,172 @@
+use std::{
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
 use crate::prelude::*;
 use bevy::prelude::*;
 use bevy_egui::*;
 use bevy_etcetera::Directories;
+use egui::{Color32, RichText};
+
+/// State of the save browser that is kept between frames
+#[derive(Default)]
+pub(super) struct SaveBrowser {
+    /// Saves in the data directory. `None` when the directory should be read again.
+    saves: Option<Vec<SaveListing>>,
+    /// The save being renamed, paired with its new name
+    renaming: Option<(PathBuf, String)>,
+    /// The save waiting for deletion to be confirmed
+    deleting: Option<PathBuf>,
+    /// Seed typed for a new game. A random seed is used when empty.
+    seed: String,
+}
+
+/// Something to do once the browser is drawn
+enum SaveAction {
+    New(Option<WorldSeed>),
+    Load(PathBuf),
+    Rename(PathBuf, String),
+    Duplicate(PathBuf),
+    Delete(PathBuf),
+}
 
 pub(super) fn draw_main_menu(
     mut contexts: EguiContexts,
     mut state: ResMut<NextState<AppState>>,
+    mut errors: EventWriter<GameError>,
+    mut browser: Local<SaveBrowser>,
     directories: Res<Directories>,
 ) {
+    let SaveBrowser {
+        saves,
+        renaming,
+        deleting,
+        seed,
+    } = &mut *browser;
+    let saves = saves.get_or_insert_with(|| {
+        list_saves(directories.data_dir()).unwrap_or_else(|e| {
+            errors.send(e.into());
+            Vec::new()
+        })
+    });
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs())
+        .unwrap_or_default();
+    let mut action = None;
+
     egui::CentralPanel::default().show(contexts.ctx_mut(), |ui| {
-        //
-        if ui.button("New").clicked() {
-            state.set(AppState::NewGame);
-        }
+        ui.horizontal(|ui| {
+            if ui.button("New").clicked() {
+                action = Some(SaveAction::New(
+                    (!seed.trim().is_empty()).then(|| WorldSeed::from_text(seed)),
+                ));
+            }
+            ui.label("Seed");
+            ui.text_edit_singleline(seed);
+        });
+
+        ui.separator();
+        ui.heading("Saves");
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for save in saves.iter() {
+                ui.group(|ui| {
+                    ui.heading(save.name());
+                    match &save.header {
+                        Ok(header) => {
+                            let metadata = &header.metadata;
+                            ui.label(format!(
+                                "{} - {} credits",
+                                metadata.player_name, metadata.credits
+                            ));
+                            ui.label(format!(
+                                "Played for {}, {} chunks explored",
+                                format_duration(metadata.play_time),
+                                metadata.chunks
+                            ));
+                            ui.label(format!(
+                                "Last played {} ago, version {}",
+                                format_duration(Duration::from_secs(
+                                    now.saturating_sub(save.last_played)
+                                )),
+                                metadata.game_version
+                            ));
+                        }
+                        Err(e) => {
+                            ui.label(
+                                RichText::new(format!("Unreadable save: {e}")).color(Color32::RED),
+                            );
+                        }
+                    }
 
-        for dir in directories.data_dir().read_dir().unwrap() {
-            let dir = dir.unwrap();
-            if let Some(extension) = dir.path().extension() {
-                if extension.to_os_string().to_str() == Some("ron")
-                    && ui
-                        .button(format!("Load: {}", dir.file_name().into_string().unwrap()))
-                        .clicked()
-                {
-                    state.set(AppState::LoadGame { path: dir.path() });
-                }
+                    ui.horizontal(|ui| {
+                        if save.header.is_ok() && ui.button("Load").clicked() {
+                            action = Some(SaveAction::Load(save.path.clone()));
+                        }
+
+                        match renaming {
+                            Some((path, name)) if *path == save.path => {
+                                ui.text_edit_singleline(name);
+                                if ui.button("Confirm").clicked() {
+                                    action = Some(SaveAction::Rename(path.clone(), name.clone()));
+                                }
+                                if ui.button("Cancel").clicked() {
+                                    *renaming = None;
+                                }
+                            }
+                            _ => {
+                                if ui.button("Rename").clicked() {
+                                    *renaming = Some((save.path.clone(), save.name()));
+                                }
+                            }
+                        }
+
+                        if ui.button("Duplicate").clicked() {
+                            action = Some(SaveAction::Duplicate(save.path.clone()));
+                        }
+
+                        if deleting.as_ref() == Some(&save.path) {
+                            if ui.button("Confirm delete").clicked() {
+                                action = Some(SaveAction::Delete(save.path.clone()));
+                            }
+                            if ui.button("Cancel").clicked() {
+                                *deleting = None;
+                            }
+                        } else if ui.button("Delete").clicked() {
+                            *deleting = Some(save.path.clone());
+                        }
+                    });
+                });
             }
-        }
+        });
     });
+
+    let result = match action {
+        Some(SaveAction::New(seed)) => {
+            state.set(AppState::NewGame { seed });
+            Ok(())
+        }
+        Some(SaveAction::Load(path)) => {
+            state.set(AppState::LoadGame { path });
+            Ok(())
+        }
+        Some(SaveAction::Rename(path, name)) => rename_save(&path, &name).map(|_| ()),
+        Some(SaveAction::Duplicate(path)) => duplicate_save(&path).map(|_| ()),
+        Some(SaveAction::Delete(path)) => delete_save(&path),
+        None => return,
+    };
+    if let Err(e) = result {
+        errors.send(e.into());
+    }
+
+    // The saves on disk may change, so list them again when the browser is next drawn
+    *browser = SaveBrowser::default();
+}
+
+/// Format a duration as hours and minutes, such as `2h 05m`
+fn format_duration(duration: Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+    format!("{}h {:02}m", minutes / 60, minutes % 60)
 }
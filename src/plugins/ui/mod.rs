@@ -16,17 +16,39 @@ impl Plugin for UiPlugin {
         app.add_systems(
             Update,
             (
-                (draw_ui, draw_toasts, draw_minimaps, draw_hud).run_if(in_state(AppState::main())),
+                (
+                    draw_ui,
+                    draw_fabricator,
+                    draw_weapon_controls,
+                    draw_minimaps,
+                    draw_hud,
+                    draw_power,
+                    draw_death_screen,
+                )
+                    .run_if(in_state(AppState::main())),
                 (draw_main_menu,).run_if(in_state(AppState::menu())),
+                draw_toasts,
             ),
         );
     }
 }
 
-fn draw_toasts(mut contexts: EguiContexts, mut errors: EventReader<GameError>) {
-    let mut toasts = Toasts::new()
-        .anchor(Align2::RIGHT_BOTTOM, (-10.0, -10.0)) // 10 units from the bottom right corner
-        .direction(egui::Direction::BottomUp);
+/// Show errors as toasts in any state, such as when a save in the menu fails to load. Items the
+/// player left behind in a chest are also shown.
+fn draw_toasts(
+    mut contexts: EguiContexts,
+    mut errors: EventReader<GameError>,
+    mut left_behind: EventReader<events::LeftBehind>,
+    mut toasts: Local<Option<Toasts>>,
+    players: Query<(), With<Player>>,
+    items: Res<Assets<Item>>,
+) {
+    // Toasts must be kept between frames so that they stay up for their duration
+    let toasts = toasts.get_or_insert_with(|| {
+        Toasts::new()
+            .anchor(Align2::RIGHT_BOTTOM, (-10.0, -10.0)) // 10 units from the bottom right corner
+            .direction(egui::Direction::BottomUp)
+    });
 
     for error in errors.read() {
         toasts.add(Toast {
@@ -39,6 +61,28 @@ fn draw_toasts(mut contexts: EguiContexts, mut errors: EventReader<GameError>) {
         });
     }
 
+    for events::LeftBehind { to, stacks, .. } in left_behind.read() {
+        if !players.contains(*to) {
+            continue;
+        }
+        let names = stacks
+            .iter()
+            .filter_map(|stack| {
+                let item = items.get(&stack.item)?;
+                let name = stack.metadata.name.as_ref().unwrap_or(&item.name);
+                Some(format!("{name} {}", stack.amount))
+            })
+            .collect::<Vec<_>>();
+        toasts.add(Toast {
+            text: format!("Left behind {}", names.join(", ")).into(),
+            kind: ToastKind::Info,
+            options: ToastOptions::default()
+                .duration_in_seconds(5.0)
+                .show_progress(true),
+            ..Default::default()
+        });
+    }
+
     egui::Area::new("toasts".into())
         .interactable(false)
         .anchor(Align2::RIGHT_BOTTOM, (16f32, 16f32))
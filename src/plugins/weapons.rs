@@ -12,20 +12,45 @@ pub struct WeaponsPlugin;
 
 impl Plugin for WeaponsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            (
-                manage_weapons,
-                manage_projectile_collisions,
-                manage_damage,
-                manage_laser_collisions,
-            )
-                .run_if(in_state(AppState::main())),
-        );
+        app.add_event::<events::Hit>()
+            .observe(on_configure_weapon)
+            .add_systems(
+                Update,
+                (
+                    add_missile_layer,
+                    manage_missile_locks,
+                    manage_weapons,
+                    manage_missiles,
+                    manage_projectile_collisions,
+                    manage_laser_collisions,
+                    manage_hits,
+                    manage_damage,
+                )
+                    // Hits are applied in the frame they are made
+                    .chain()
+                    .run_if(in_state(AppState::main())),
+            );
     }
 }
 
-/// Fire weapons when appropriate
+/// Change the group and fire mode of an equipped weapon, keeping the choice in the metadata of the
+/// item so that it is saved
+fn on_configure_weapon(
+    trigger: Trigger<triggers::ConfigureWeapon>,
+    mut weapons: Query<(&mut Weapon, &mut Equipment)>,
+) {
+    let triggers::ConfigureWeapon {
+        weapon,
+        fire_control,
+    } = trigger.event();
+    if let Ok((mut weapon, mut equipment)) = weapons.get_mut(*weapon) {
+        weapon.fire_control = *fire_control;
+        weapon.toggled = false;
+        equipment.metadata_mut().fire_control = Some(*fire_control);
+    }
+}
+
+/// Fire weapons when appropriate, with the [`Energy`] their parent distributes to them
 fn manage_weapons(
     mut cmd: Commands,
     mut weapons: Query<(
@@ -33,26 +58,42 @@ fn manage_weapons(
         &mut Heat,
         Option<&Overheated>,
         Option<&Children>,
+        Option<&Hardpoint>,
     )>,
     mut parents: Query<
-        (Entity, &mut Energy, &Equipped, &LinearVelocity, &Alliegance),
+        (
+            Entity,
+            &mut Energy,
+            &mut PowerDistribution,
+            &Equipped,
+            &LinearVelocity,
+            &Alliegance,
+        ),
         (Without<Destroyed>, With<Transform>),
     >,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut transforms: Query<&mut Transform>,
     lasers: Query<Entity, (With<Laser>, With<Transform>)>,
+    locks: Query<&LockOn>,
     library: Res<Library>,
     time: Res<Time>,
 ) {
-    for (entity, mut total_energy, equipped, linear_velocity, alliegance) in parents.iter_mut() {
+    for (entity, mut total_energy, mut power, equipped, linear_velocity, alliegance) in
+        parents.iter_mut()
+    {
         let transform = *transforms.get(entity).unwrap();
         // Get all entities that are weapons
         if let Some(entities) = equipped.equipped.get(&EquipmentTypeId::Weapon) {
             for weapon_entity in entities {
                 // Get the weapon component attached
-                if let Ok((mut weapon, mut heat, maybe_overheated, maybe_weapon_children)) =
-                    weapons.get_mut(*weapon_entity)
+                if let Ok((
+                    mut weapon,
+                    mut heat,
+                    maybe_overheated,
+                    maybe_weapon_children,
+                    maybe_hardpoint,
+                )) = weapons.get_mut(*weapon_entity)
                 {
                     // Do stuff depending on the weapon
                     match &weapon.weapon_type {
@@ -60,6 +101,7 @@ fn manage_weapons(
                             speed,
                             recoil,
                             damage,
+                            damage_type,
                             radius,
                             spread,
                             shots,
@@ -68,12 +110,24 @@ fn manage_weapons(
                             energy,
                             projectile_model,
                             distance,
+                            blast,
+                            heat_per_shot,
                         } => {
+                            let (mount, tracking) =
+                                weapon_mount(transform, maybe_hardpoint, *tracking);
                             if weapon.wants_to_fire {
                                 // Check if weapon can fire
-                                if weapon.last_fired + Duration::from_secs_f32(*recoil)
-                                    <= time.elapsed()
-                                    && total_energy.consume(*energy as f32).is_ok()
+                                if maybe_overheated.is_none()
+                                    && weapon.last_fired + Duration::from_secs_f32(*recoil)
+                                        <= time.elapsed()
+                                    && power
+                                        .try_draw(
+                                            &mut total_energy,
+                                            PowerSystem::Weapons,
+                                            *energy as f32,
+                                            time.elapsed(),
+                                        )
+                                        .is_ok()
                                 {
                                     for _ in 0..*shots {
                                         let mut spread_angle = 0f32;
@@ -83,33 +137,23 @@ fn manage_weapons(
                                                 .gen_range(-half_spread..half_spread);
                                         }
 
-                                        // If we have tracking, find the additional angle
-                                        let (direction, added_angle) = {
-                                            match weapon.target {
-                                                Some(target) => transform
-                                                    .calculate_turn_angle(target.truncate()),
-                                                None => (RotationDirection::None, 0f32),
-                                            }
-                                        };
-
-                                        let final_angle =
-                                            (-added_angle.min(*tracking).max(-tracking)
-                                                * (f32::from(direction)))
-                                                + spread_angle;
-
-                                        // Spawn a projectile
+                                        let rot = angle_with_tracking(
+                                            &weapon,
+                                            mount,
+                                            tracking,
+                                            spread_angle,
+                                        );
+
+                                        // Spawn a projectile at the mount
                                         cmd.spawn((
                                             LockedAxes::new().lock_translation_z(),
-                                            TransformBundle::from_transform(transform),
+                                            TransformBundle::from_transform(mount),
                                             RigidBody::Dynamic,
                                             Mass(1f32),
                                             LinearVelocity(
-                                                transform
+                                                mount
                                                     .rotation
-                                                    .mul_quat(Quat::from_axis_angle(
-                                                        Vec3::Y,
-                                                        final_angle,
-                                                    ))
+                                                    .mul_quat(rot)
                                                     .mul_vec3(-Vec3::Z * *speed)
                                                     + linear_velocity.0,
                                             ),
@@ -118,18 +162,25 @@ fn manage_weapons(
                                                 lifetime: Duration::from_secs_f32(*lifetime),
                                             },
                                             DistanceLifetime {
-                                                created: transform.translation,
+                                                created: mount.translation,
                                                 length: *distance,
                                             },
-                                            Projectile { damage: *damage },
+                                            Projectile {
+                                                damage: *damage,
+                                                damage_type: *damage_type,
+                                                blast: *blast,
+                                            },
                                             alliegance.clone(),
                                             Sensor,
                                             Collider::sphere(*radius),
+                                            // Missiles are included so they can be shot down,
+                                            // but projectiles do not hit each other
                                             CollisionLayers {
                                                 memberships: PhysicsCategory::Weapon.into(),
                                                 filters: LayerMask::from([
                                                     PhysicsCategory::Craft,
                                                     PhysicsCategory::Structure,
+                                                    PhysicsCategory::Missile,
                                                 ]),
                                             },
                                         ))
@@ -146,15 +197,101 @@ fn manage_weapons(
                                             },
                                         );
                                     }
+                                    *heat += *heat_per_shot;
                                     // Set the last fired time and set "wants to fire" to false
                                     weapon.last_fired = time.elapsed();
                                     weapon.wants_to_fire = false;
                                 }
                             }
                         }
+                        WeaponType::MissileWeapon {
+                            tracking,
+                            lock_on,
+                            speed,
+                            max_speed,
+                            acceleration,
+                            turn_rate,
+                            recoil,
+                            fuel,
+                            damage,
+                            proximity,
+                            blast,
+                            health,
+                            radius,
+                            energy,
+                            projectile_model,
+                        } => {
+                            let (mount, _) = weapon_mount(transform, maybe_hardpoint, *tracking);
+                            // Missiles are only launched once a lock has been held long enough
+                            let maybe_lock = locks.get(*weapon_entity).ok().filter(|lock| {
+                                lock.since + Duration::from_secs_f32(*lock_on) <= time.elapsed()
+                            });
+                            if let (true, Some(lock)) = (weapon.wants_to_fire, maybe_lock) {
+                                if weapon.last_fired + Duration::from_secs_f32(*recoil)
+                                    <= time.elapsed()
+                                    && power
+                                        .try_draw(
+                                            &mut total_energy,
+                                            PowerSystem::Weapons,
+                                            *energy as f32,
+                                            time.elapsed(),
+                                        )
+                                        .is_ok()
+                                {
+                                    // Launch a missile from the mount
+                                    cmd.spawn((
+                                        LockedAxes::new().lock_translation_z(),
+                                        TransformBundle::from_transform(mount),
+                                        RigidBody::Dynamic,
+                                        Mass(1f32),
+                                        LinearVelocity(
+                                            mount.rotation.mul_vec3(-Vec3::Z * *speed)
+                                                + linear_velocity.0,
+                                        ),
+                                        Lifetime {
+                                            created: time.elapsed(),
+                                            lifetime: Duration::from_secs_f32(*fuel),
+                                        },
+                                        Missile {
+                                            target: Some(lock.target),
+                                            max_speed: *max_speed,
+                                            acceleration: *acceleration,
+                                            turn_rate: *turn_rate,
+                                            damage: *damage,
+                                            proximity: *proximity,
+                                            blast: *blast,
+                                        },
+                                        Health::new(*health),
+                                        Damage::default(),
+                                        alliegance.clone(),
+                                        Sensor,
+                                        Collider::sphere(*radius),
+                                        CollisionLayers {
+                                            memberships: PhysicsCategory::Missile.into(),
+                                            filters: LayerMask::from([
+                                                PhysicsCategory::Craft,
+                                                PhysicsCategory::Structure,
+                                                PhysicsCategory::Weapon,
+                                            ]),
+                                        },
+                                    ))
+                                    .with_children(|cmd| {
+                                        cmd.spawn(SceneBundle {
+                                            scene: library.model(projectile_model.clone()).unwrap(),
+                                            transform: Transform::default_z()
+                                                .with_scale(Vec3::splat(*radius)),
+                                            ..Default::default()
+                                        });
+                                    });
+                                    weapon.last_fired = time.elapsed();
+                                    weapon.wants_to_fire = false;
+                                }
+                            }
+                        }
                         WeaponType::LaserWeapon {
                             tracking,
                             damage_per_second,
+                            damage_type,
                             energy_per_second,
                             range,
                             width,
@@ -164,9 +301,10 @@ fn manage_weapons(
                             cooling_per_second,
                         } => {
                             let energy_to_consume = energy_per_second * time.delta_seconds();
-                            let has_enough_energy = total_energy.charge() >= energy_to_consume;
+                            let available = power.available(PowerSystem::Weapons, &total_energy);
+                            let has_enough_energy = available >= energy_to_consume;
                             let has_enough_activation_energy =
-                                total_energy.charge() >= energy_to_consume + activation_energy;
+                                available >= energy_to_consume + activation_energy;
                             let is_overheated = maybe_overheated.is_some();
                             let wants_to_fire = weapon.wants_to_fire;
 
@@ -195,10 +333,15 @@ fn manage_weapons(
                                 WeaponState::Off
                             };
 
-                            // Rotate the weapon turret
-                            let rot = angle_with_tracking(&weapon, transform, *tracking, 0f32);
+                            // Rotate the weapon turret within the arc of its mount
+                            let (mount, tracking) =
+                                weapon_mount(transform, maybe_hardpoint, *tracking);
+                            let rot = angle_with_tracking(&weapon, mount, tracking, 0f32);
+                            let facing = maybe_hardpoint
+                                .map(|hardpoint| hardpoint.transform().rotation)
+                                .unwrap_or_default();
                             let mut weapon_transform = transforms.get_mut(*weapon_entity).unwrap();
-                            weapon_transform.rotation = rot;
+                            weapon_transform.rotation = facing * rot;
 
                             match state {
                                 WeaponState::Overheated => {
@@ -206,13 +349,23 @@ fn manage_weapons(
                                     cmd.entity(*weapon_entity).despawn_descendants();
                                 }
                                 WeaponState::Firing => {
-                                    total_energy
-                                        .consume(energy_to_consume)
+                                    power
+                                        .try_draw(
+                                            &mut total_energy,
+                                            PowerSystem::Weapons,
+                                            energy_to_consume,
+                                            time.elapsed(),
+                                        )
                                         .expect("should have enough energy");
                                 }
                                 WeaponState::StartFiring => {
-                                    total_energy
-                                        .consume(energy_to_consume)
+                                    power
+                                        .try_draw(
+                                            &mut total_energy,
+                                            PowerSystem::Weapons,
+                                            energy_to_consume,
+                                            time.elapsed(),
+                                        )
                                         .expect("should have enough energy");
                                     // Create laser
                                     cmd.entity(*weapon_entity).with_children(|cmd| {
@@ -220,6 +373,7 @@ fn manage_weapons(
                                             Sensor,
                                             Laser {
                                                 damage_per_second: *damage_per_second,
+                                                damage_type: *damage_type,
                                                 range: *range,
                                                 width: *width,
                                             },
@@ -247,6 +401,9 @@ fn manage_weapons(
                                     });
                                 }
                                 WeaponState::Off => {
+                                    if wants_to_fire {
+                                        power.starve(PowerSystem::Weapons, time.elapsed());
+                                    }
                                     if maybe_lasers.is_some() {
                                         // Remove lasers
                                         cmd.entity(*weapon_entity).despawn_descendants();
@@ -262,6 +419,11 @@ fn manage_weapons(
                                 WeaponState::StartFiring | WeaponState::Firing => {
                                     heat_per_second * time.delta_seconds()
                                 }
+                            };
+
+                            // Alternating lasers take turns each time they start firing
+                            if matches!(state, WeaponState::StartFiring) {
+                                weapon.last_fired = time.elapsed();
                             }
                         }
                     }
@@ -271,12 +433,179 @@ fn manage_weapons(
     }
 }
 
-fn manage_projectile_collisions(
+/// Lock missile weapons on to an enemy that is in range and within their tracking. The target of
+/// the craft is preferred, followed by the enemy closest to where the weapon is aimed.
+/// Crafts and structures saved before missiles existed do not collide with them. Missiles hit
+/// everything that other weapons hit.
+fn add_missile_layer(mut layers: Query<&mut CollisionLayers, Added<CollisionLayers>>) {
+    for mut layers in layers.iter_mut() {
+        let targeted = [PhysicsCategory::Craft, PhysicsCategory::Structure]
+            .into_iter()
+            .any(|category| layers.memberships.has_all(category));
+        if targeted && layers.filters.has_all(PhysicsCategory::Weapon) {
+            layers.filters.add(PhysicsCategory::Missile);
+        }
+    }
+}
+
+fn manage_missile_locks(
     mut cmd: Commands,
-    mut collided: Query<(&mut Damage, Option<&Alliegance>), Without<Destroyed>>,
-    projectile_hits: Query<(Entity, &CollidingEntities, &Projectile, Option<&Alliegance>)>,
+    weapons: Query<(
+        Entity,
+        &Weapon,
+        &Parent,
+        Option<&Hardpoint>,
+        Option<&LockOn>,
+    )>,
+    parents: Query<(&Transform, &Alliegance, Option<&Target>)>,
+    targets: Query<
+        (Entity, &Transform, &Alliegance),
+        (With<Health>, Without<Destroyed>, Without<Missile>),
+    >,
+    time: Res<Time>,
 ) {
-    for (projectile_entity, colliding_entities, projectile, maybe_alliegance) in
+    for (weapon_entity, weapon, parent, maybe_hardpoint, maybe_lock) in weapons.iter() {
+        let WeaponType::MissileWeapon { tracking, .. } = weapon.weapon_type else {
+            continue;
+        };
+        let Ok((transform, alliegance, maybe_target)) = parents.get(parent.get()) else {
+            continue;
+        };
+        let (mount, tracking) = weapon_mount(*transform, maybe_hardpoint, tracking);
+        let range = weapon.weapon_type.range();
+
+        // Only enemies can be locked on to
+        let eligible =
+            |(_, target_transform, target_alliegance): &(Entity, &Transform, &Alliegance)| {
+                let (_, angle) =
+                    mount.calculate_turn_angle(target_transform.translation.truncate());
+                alliegance.enemies.contains(&target_alliegance.faction)
+                    && mount.translation.distance(target_transform.translation) <= range
+                    && angle.abs() <= tracking
+            };
+        let aim = weapon.target.unwrap_or(mount.translation);
+        let locked = maybe_target
+            .and_then(|Target(target)| targets.get(*target).ok())
+            .filter(&eligible)
+            .or_else(|| {
+                targets.iter().filter(&eligible).min_by(|a, b| {
+                    let a = a.1.translation.distance_squared(aim);
+                    let b = b.1.translation.distance_squared(aim);
+                    a.total_cmp(&b)
+                })
+            })
+            .map(|(entity, _, _)| entity);
+
+        match (locked, maybe_lock) {
+            // Keep an existing lock on the same target
+            (Some(target), Some(lock)) if lock.target == target => (),
+            (Some(target), _) => {
+                cmd.entity(weapon_entity).insert(LockOn {
+                    target,
+                    since: time.elapsed(),
+                });
+            }
+            (None, Some(_)) => {
+                cmd.entity(weapon_entity).remove::<LockOn>();
+            }
+            (None, None) => (),
+        }
+    }
+}
+
+/// Steer missiles towards their target, detonating them when they get close to it or hit an enemy.
+/// Missiles that are shot down are removed.
+fn manage_missiles(
+    mut cmd: Commands,
+    mut missiles: Query<
+        (
+            Entity,
+            &Missile,
+            &mut Transform,
+            &mut LinearVelocity,
+            &Alliegance,
+            &CollidingEntities,
+        ),
+        Without<Destroyed>,
+    >,
+    mut explosions: EventWriter<events::Explosion>,
+    damageable: Query<(&Transform, Option<&Alliegance>), (Without<Destroyed>, Without<Missile>)>,
+    shot_down: Query<Entity, (With<Missile>, Added<Destroyed>)>,
+    time: Res<Time>,
+) {
+    for entity in shot_down.iter() {
+        cmd.entity(entity).despawn_recursive();
+    }
+
+    let is_enemy = |alliegance: &Alliegance, other: Option<&Alliegance>| {
+        alliegance
+            .enemies
+            .contains(&other.cloned().unwrap_or_default().faction)
+    };
+
+    for (entity, missile, mut transform, mut linear_velocity, alliegance, colliding_entities) in
+        missiles.iter_mut()
+    {
+        let delta = time.delta_seconds();
+        let velocity = linear_velocity.0.truncate();
+        let direction = velocity
+            .try_normalize()
+            .unwrap_or_else(|| (transform.rotation * -Vec3::Z).truncate());
+        let speed = (velocity.length() + missile.acceleration * delta).min(missile.max_speed);
+
+        // Turn towards the target as fast as the missile allows
+        let maybe_target = missile
+            .target
+            .and_then(|target| damageable.get(target).ok())
+            .map(|(target_transform, _)| target_transform.translation);
+        let direction = match maybe_target {
+            Some(target) => {
+                let to_target = (target - transform.translation).truncate();
+                let max_turn = missile.turn_rate * delta;
+                let turn = direction
+                    .angle_between(to_target)
+                    .clamp(-max_turn, max_turn);
+                Vec2::from_angle(turn).rotate(direction)
+            }
+            None => direction,
+        };
+        linear_velocity.0 = (direction * speed).extend(0f32);
+        transform.look_to(direction.extend(0f32), Vec3::Z);
+
+        let near_target = maybe_target
+            .is_some_and(|target| target.distance(transform.translation) <= missile.proximity);
+        let hit_enemy = colliding_entities.iter().any(|other| {
+            damageable
+                .get(*other)
+                .is_ok_and(|(_, other_alliegance)| is_enemy(alliegance, other_alliegance))
+        });
+        if near_target || hit_enemy {
+            // Damage every enemy caught in the blast
+            explosions.send(events::Explosion {
+                translation: transform.translation,
+                damage: missile.damage as f32,
+                blast: missile.blast,
+                alliegance: Some(alliegance.clone()),
+            });
+            cmd.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+pub(super) fn manage_projectile_collisions(
+    mut cmd: Commands,
+    mut explosions: EventWriter<events::Explosion>,
+    mut hits: EventWriter<events::Hit>,
+    collided: Query<Option<&Alliegance>, (With<Damage>, Without<Destroyed>)>,
+    projectile_hits: Query<(
+        Entity,
+        &CollidingEntities,
+        &Projectile,
+        Option<&Alliegance>,
+        &Transform,
+    )>,
+) {
+    for (projectile_entity, colliding_entities, projectile, maybe_alliegance, transform) in
         projectile_hits.iter()
     {
         for colliding_entity in colliding_entities.iter() {
@@ -292,16 +621,34 @@ fn manage_projectile_collisions(
             };
 
             // Get collisions
-            if let Ok((mut damage, maybe_collided_alliegance)) = collided.get_mut(*colliding_entity)
-            {
+            if let Ok(maybe_collided_alliegance) = collided.get(*colliding_entity) {
                 // Get the alliegance of the other object
                 let other_alliegance = match maybe_collided_alliegance {
                     Some(alliegance) => alliegance,
                     None => &Alliegance::default(),
                 };
                 if alliegance.enemies.contains(&other_alliegance.faction) {
-                    **damage += projectile.damage as f32;
                     cmd.entity(projectile_entity).despawn_recursive();
+                    match projectile.blast {
+                        // Explosive shots damage everything in the blast instead, and only
+                        // explode once
+                        Some(blast) => {
+                            explosions.send(events::Explosion {
+                                translation: transform.translation,
+                                damage: projectile.damage as f32,
+                                blast,
+                                alliegance: Some(alliegance.clone()),
+                            });
+                            break;
+                        }
+                        None => {
+                            hits.send(events::Hit {
+                                target: *colliding_entity,
+                                amount: projectile.damage as f32,
+                                damage_type: projectile.damage_type,
+                            });
+                        }
+                    }
                 }
             }
         }
@@ -309,7 +656,8 @@ fn manage_projectile_collisions(
 }
 
 fn manage_laser_collisions(
-    mut collided: Query<(&mut Damage, Option<&Alliegance>), Without<Destroyed>>,
+    mut hits: EventWriter<events::Hit>,
+    collided: Query<Option<&Alliegance>, (With<Damage>, Without<Destroyed>)>,
     time: Res<Time>,
     laser_hits: Query<(&CollidingEntities, &Laser, Option<&Alliegance>)>,
 ) {
@@ -327,15 +675,18 @@ fn manage_laser_collisions(
             };
 
             // Get collisions
-            if let Ok((mut damage, maybe_collided_alliegance)) = collided.get_mut(*colliding_entity)
-            {
+            if let Ok(maybe_collided_alliegance) = collided.get(*colliding_entity) {
                 // Get the alliegance of the other object
                 let other_alliegance = match maybe_collided_alliegance {
                     Some(alliegance) => alliegance,
                     None => &Alliegance::default(),
                 };
                 if alliegance.enemies.contains(&other_alliegance.faction) {
-                    **damage += laser.damage_per_second * time.delta_seconds();
+                    hits.send(events::Hit {
+                        target: *colliding_entity,
+                        amount: laser.damage_per_second * time.delta_seconds(),
+                        damage_type: laser.damage_type,
+                    });
                     // cmd.entity(projectile_entity).despawn_recursive();
                 }
             }
@@ -362,6 +713,47 @@ fn manage_laser_collisions(
 //     }
 // }
 
+/// Add hits to the [`Damage`] of their target
+///
+/// # System overview
+///
+/// 1. Equipped [`Shield`]s absorb as much of the hit as their charge allows
+/// 2. Equipped [`Armor`] resists the rest depending on its type
+/// 3. Whatever is left is added to the damage of the target
+pub(super) fn manage_hits(
+    mut hits: EventReader<events::Hit>,
+    mut damages: Query<(&mut Damage, Option<&Children>), Without<Destroyed>>,
+    mut shields: Query<&mut Shield>,
+    armors: Query<&Armor>,
+    time: Res<Time>,
+) {
+    for events::Hit {
+        target,
+        amount,
+        damage_type,
+    } in hits.read()
+    {
+        let Ok((mut damage, maybe_children)) = damages.get_mut(*target) else {
+            continue;
+        };
+        let children = maybe_children
+            .map(|children| &**children)
+            .unwrap_or_default();
+
+        let mut amount = *amount;
+        for child in children {
+            if let Ok(mut shield) = shields.get_mut(*child) {
+                amount = shield.absorb(amount, time.elapsed());
+            }
+        }
+        let resistances = children
+            .iter()
+            .filter_map(|child| armors.get(*child).ok())
+            .map(|armor| &armor.resistances);
+        **damage += Resistances::resist(amount, *damage_type, resistances);
+    }
+}
+
 fn manage_damage(
     mut cmd: Commands,
     health_and_damage: Query<(Entity, &Health, &Damage), Changed<Damage>>,
@@ -373,6 +765,22 @@ fn manage_damage(
     }
 }
 
+/// World transform a weapon fires from, along with how far it can turn. Weapons on a
+/// [`Hardpoint`] with an arc turn within it, and others turn within their own tracking.
+fn weapon_mount(
+    transform: Transform,
+    maybe_hardpoint: Option<&Hardpoint>,
+    tracking: f32,
+) -> (Transform, f32) {
+    match maybe_hardpoint {
+        Some(hardpoint) => (
+            transform.mul_transform(hardpoint.transform()),
+            hardpoint.arc.unwrap_or(tracking),
+        ),
+        None => (transform, tracking),
+    }
+}
+
 /// Utility to find the angle, with tracking in a set range
 fn angle_with_tracking(
     weapon: &Weapon,
@@ -393,3 +801,228 @@ fn angle_with_tracking(
         (-added_angle.min(tracking).max(-tracking) * (f32::from(direction))) + spread_angle,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    /// A hornet launcher, as it is shipped with the game
+    fn hornet() -> Weapon {
+        let item: Item =
+            ron::from_str(include_str!("../../assets/items/hornet.weapon.ron")).unwrap();
+        let Some(EquipmentType::Weapon(weapon)) = item.equipment else {
+            panic!("hornet should be a weapon");
+        };
+        weapon
+    }
+
+    fn setup(delta: f32) -> (World, Alliegance) {
+        let mut world = World::new();
+        let mut time = Time::<()>::default();
+        time.advance_by(Duration::from_secs_f32(delta));
+        world.insert_resource(time);
+        world.init_resource::<Events<events::Explosion>>();
+        let (ours, theirs) = (Faction::from_name("ours"), Faction::from_name("theirs"));
+        let alliegance = Alliegance {
+            faction: ours,
+            allies: [ours].into(),
+            enemies: [theirs].into(),
+        };
+        (world, alliegance)
+    }
+
+    fn spawn_target(world: &mut World, x: f32, y: f32, faction: Faction) -> Entity {
+        world
+            .spawn((
+                Transform::from_xyz(x, y, 0f32),
+                Alliegance {
+                    faction,
+                    ..default()
+                },
+                Health::new(10),
+            ))
+            .id()
+    }
+
+    #[test]
+    fn test_manage_missile_locks() {
+        let (mut world, alliegance) = setup(1f32);
+        let craft = world
+            .spawn((Transform::default_z(), alliegance.clone()))
+            .id();
+        let weapon = world.spawn(hornet()).id();
+        world.entity_mut(craft).push_children(&[weapon]);
+        let theirs = Faction::from_name("theirs");
+        // The hornet reaches 27 units, within 0.6 radians of straight ahead
+        let far = spawn_target(&mut world, 20f32, 1f32, theirs);
+        let near = spawn_target(&mut world, 10f32, 0f32, theirs);
+        spawn_target(&mut world, 5f32, 0f32, alliegance.faction);
+        spawn_target(&mut world, -5f32, 0f32, theirs);
+        spawn_target(&mut world, 40f32, 0f32, theirs);
+
+        // The closest enemy in the sights is locked on to
+        world.run_system_once(manage_missile_locks);
+        let lock = |world: &World| {
+            world
+                .get::<LockOn>(weapon)
+                .map(|lock| (lock.target, lock.since))
+        };
+        assert_eq!(lock(&world), Some((near, Duration::from_secs(1))));
+
+        // The target of the craft is preferred, and a lock on it starts over
+        world.entity_mut(craft).insert(Target(far));
+        world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs(1));
+        world.run_system_once(manage_missile_locks);
+        assert_eq!(lock(&world), Some((far, Duration::from_secs(2))));
+        world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs(1));
+        world.run_system_once(manage_missile_locks);
+        assert_eq!(lock(&world), Some((far, Duration::from_secs(2))));
+
+        // Destroyed targets cannot be locked on to
+        world.entity_mut(far).insert(Destroyed);
+        world.entity_mut(near).insert(Destroyed);
+        world.run_system_once(manage_missile_locks);
+        assert_eq!(lock(&world), None);
+    }
+
+    #[test]
+    fn test_manage_missiles() {
+        let (mut world, alliegance) = setup(0.1);
+        let WeaponType::MissileWeapon {
+            max_speed,
+            acceleration,
+            turn_rate,
+            damage,
+            proximity,
+            blast,
+            ..
+        } = hornet().weapon_type
+        else {
+            panic!("hornet should launch missiles");
+        };
+        let enemy = spawn_target(&mut world, 0f32, 10f32, Faction::from_name("theirs"));
+        let ally = spawn_target(&mut world, 1f32, 0f32, alliegance.faction);
+        let mut spawn_missile = |target: Option<Entity>| {
+            world
+                .spawn((
+                    Missile {
+                        target,
+                        max_speed,
+                        acceleration,
+                        turn_rate,
+                        damage,
+                        proximity,
+                        blast,
+                    },
+                    Transform::default_z(),
+                    LinearVelocity(Vec3::X * 3f32),
+                    alliegance.clone(),
+                    CollidingEntities::default(),
+                ))
+                .id()
+        };
+        let guided = spawn_missile(Some(enemy));
+        let unguided = spawn_missile(None);
+        let shot_down = spawn_missile(None);
+        world.entity_mut(shot_down).insert(Destroyed);
+        world
+            .get_mut::<CollidingEntities>(unguided)
+            .unwrap()
+            .insert(ally);
+
+        // Missiles speed up and turn towards their target, as far as they can in a frame
+        world.run_system_once(manage_missiles);
+        let velocity = world.get::<LinearVelocity>(guided).unwrap().0.truncate();
+        assert!((velocity.length() - (3f32 + acceleration * 0.1)).abs() < 1e-4);
+        assert!((Vec2::X.angle_between(velocity) - turn_rate * 0.1).abs() < 1e-4);
+        let velocity = world.get::<LinearVelocity>(unguided).unwrap().0.truncate();
+        assert!(Vec2::X.angle_between(velocity).abs() < 1e-4);
+        // Allies are not hit, and missiles that were shot down are gone
+        assert!(world.get_entity(unguided).is_some());
+        assert!(world.get_entity(shot_down).is_none());
+        assert_eq!(world.resource::<Events<events::Explosion>>().len(), 0);
+
+        // Missiles detonate near their target, or on hitting any enemy
+        let translation = world.get::<Transform>(guided).unwrap().translation;
+        world.get_mut::<Transform>(enemy).unwrap().translation =
+            translation + Vec3::Y * proximity * 0.9;
+        world
+            .get_mut::<CollidingEntities>(unguided)
+            .unwrap()
+            .insert(enemy);
+        world.run_system_once(manage_missiles);
+        assert!(world.get_entity(guided).is_none());
+        assert!(world.get_entity(unguided).is_none());
+        let explosions = world
+            .resource_mut::<Events<events::Explosion>>()
+            .drain()
+            .collect::<Vec<_>>();
+        assert_eq!(explosions.len(), 2);
+        assert!(explosions
+            .iter()
+            .all(|explosion| explosion.damage == damage as f32 && explosion.blast == blast));
+    }
+
+    #[test]
+    fn test_weapon_mount() {
+        let hardpoint = Hardpoint {
+            name: "port".into(),
+            offset: (-0.5, 1f32),
+            facing: FRAC_PI_2,
+            equipment: EquipmentTypeId::Weapon,
+            arc: Some(0.3),
+        };
+        // Craft children face -Z, and the hardpoint turns a quarter counter-clockwise to the left
+        let mount = hardpoint.transform();
+        assert!(mount.translation.distance(Vec3::new(-0.5, 0f32, -1f32)) < 1e-5);
+        assert!(mount.forward().distance(Vec3::NEG_X) < 1e-5);
+
+        // A craft facing +X has its left towards +Y
+        let craft = Transform::default_z().with_translation(Vec3::new(1f32, 2f32, 0f32));
+        let (transform, tracking) = weapon_mount(craft, Some(&hardpoint), 0.6);
+        assert!(transform.translation.distance(Vec3::new(2f32, 2.5, 0f32)) < 1e-5);
+        assert!(transform.forward().distance(Vec3::Y) < 1e-5);
+        assert_eq!(tracking, 0.3);
+        let unlimited = Hardpoint {
+            arc: None,
+            ..hardpoint
+        };
+        assert_eq!(weapon_mount(craft, Some(&unlimited), 0.6).1, 0.6);
+        assert_eq!(weapon_mount(craft, None, 0.6), (craft, 0.6));
+    }
+
+    #[test]
+    fn test_add_missile_layer() {
+        let mut world = World::new();
+        let craft = world
+            .spawn(CollisionLayers {
+                memberships: PhysicsCategory::Craft.into(),
+                filters: PhysicsCategory::Weapon.into(),
+            })
+            .id();
+        let missile = world
+            .spawn(CollisionLayers {
+                memberships: PhysicsCategory::Missile.into(),
+                filters: PhysicsCategory::Weapon.into(),
+            })
+            .id();
+
+        world.run_system_once(add_missile_layer);
+        let hits_missiles = |entity: Entity| {
+            world
+                .get::<CollisionLayers>(entity)
+                .unwrap()
+                .filters
+                .has_all(PhysicsCategory::Missile)
+        };
+        assert!(hits_missiles(craft));
+        assert!(!hits_missiles(missile));
+    }
+}
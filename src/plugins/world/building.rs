@@ -6,7 +6,6 @@ use bevy_turborand::prelude::*;
 pub(super) fn on_spawn_building(
     trigger: Trigger<triggers::SpawnBuilding>,
     mut cmd: Commands,
-    mut rng: ResMut<GlobalRng>,
     library: Res<Library>,
     buildings: Res<Assets<Building>>,
     items: Res<Assets<Item>>,
@@ -16,6 +15,9 @@ pub(super) fn on_spawn_building(
         translation,
         rotation,
         alliegance,
+        seed,
+        danger,
+        gate,
     } = trigger.event();
     let Building {
         name,
@@ -30,6 +32,9 @@ pub(super) fn on_spawn_building(
         store,
         credits,
         store_margin,
+        model,
+        fabricator,
+        explosion,
     } = library
         .building(name)
         .and_then(|building| buildings.get(building.id()))
@@ -59,7 +64,11 @@ pub(super) fn on_spawn_building(
             ),
             CollisionLayers {
                 memberships: LayerMask::from([PhysicsCategory::Structure]),
-                filters: LayerMask::from([PhysicsCategory::Weapon, PhysicsCategory::Structure]),
+                filters: LayerMask::from([
+                    PhysicsCategory::Weapon,
+                    PhysicsCategory::Missile,
+                    PhysicsCategory::Structure,
+                ]),
             },
             LockedAxes::ROTATION_LOCKED,
             Transform::z_from_parts(translation, rotation),
@@ -67,7 +76,8 @@ pub(super) fn on_spawn_building(
         (GlobalTransform::IDENTITY,),
     ));
 
-    if let Some(spawner) = spawner {
+    if let Some(mut spawner) = spawner {
+        spawner.maximum += danger;
         entity.insert((spawner,));
     }
 
@@ -75,22 +85,46 @@ pub(super) fn on_spawn_building(
         entity.insert(Credits::new(credits));
     }
 
+    if let Some(model) = model {
+        entity.insert(Model::new(library.model(model).unwrap()));
+    }
+
+    // Fabricators are docked with to queue recipes
+    if let Some(fabricator) = fabricator {
+        entity.insert((fabricator, Dockings::default()));
+    }
+
+    if let Some(explosive) = explosion {
+        entity.insert(explosive);
+    }
+
+    // Gates are docked with to pick a destination
+    if let Some(destination) = gate {
+        entity.insert((
+            Gate {
+                destination: *destination,
+            },
+            Dockings::default(),
+        ));
+    }
+
     if let (Some(store), Some(margin)) = (store, store_margin) {
         entity.insert((
             Store {
-                items: store
-                    .into_iter()
-                    .filter_map(|(item, (numerator, denominator))| {
-                        // Add sale items in randomly
-                        // TODO: this can be rerolled at will, when it should persist
-                        let value = rng.f32();
-                        let value_to_beat = 1f32 - (numerator as f32 / denominator as f32);
-                        match value >= value_to_beat {
-                            true => library.item(item),
-                            false => None,
-                        }
-                    })
-                    .collect(),
+                items: roll_store_stock(
+                    &store,
+                    |name| {
+                        library
+                            .item(name)
+                            .and_then(|item| items.get(&item))
+                            .map(|item| item.rarity)
+                            .unwrap_or_default()
+                    },
+                    &mut Rng::with_seed(*seed),
+                )
+                .into_iter()
+                .filter_map(|item| library.item(item))
+                .collect(),
                 margin,
             },
             Dockings::default(),
@@ -98,3 +132,60 @@ pub(super) fn on_spawn_building(
         ));
     }
 }
+
+/// Roll which of a store's potential items are in stock. Rarer items are less likely to be stocked.
+pub(super) fn roll_store_stock(
+    store: &[(String, Chance)],
+    rarity: impl Fn(&str) -> Rarity,
+    rng: &mut Rng,
+) -> Vec<String> {
+    store
+        .iter()
+        .filter(|(item, (numerator, denominator))| {
+            // Add sale items in randomly
+            let value = rng.f32();
+            let denominator = denominator * rarity(item).scarcity();
+            let value_to_beat = 1f32 - (*numerator as f32 / denominator as f32);
+            value >= value_to_beat
+        })
+        .map(|(item, _)| item.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roll_store_stock() {
+        let store = [
+            ("always".to_string(), (1, 1)),
+            ("never".to_string(), (0, 1)),
+            ("legendary".to_string(), (1, 1)),
+        ];
+        let rarity = |item: &str| match item {
+            "legendary" => Rarity::Legendary,
+            _ => Rarity::Common,
+        };
+        let mut rng = Rng::with_seed(7);
+        let rolls = (0..1000)
+            .map(|_| roll_store_stock(&store, rarity, &mut rng))
+            .collect::<Vec<_>>();
+        let stocked = |item: &str| {
+            rolls
+                .iter()
+                .filter(|stock| stock.iter().any(|stocked| stocked == item))
+                .count()
+        };
+
+        assert_eq!(stocked("always"), 1000);
+        assert_eq!(stocked("never"), 0);
+        // Legendary items are stocked an eighth as often as common ones
+        assert!((75..175).contains(&stocked("legendary")));
+        // The same seed always stocks the same items
+        assert_eq!(
+            roll_store_stock(&store, rarity, &mut Rng::with_seed(3)),
+            roll_store_stock(&store, rarity, &mut Rng::with_seed(3))
+        );
+    }
+}
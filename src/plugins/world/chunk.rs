@@ -3,6 +3,8 @@ use bevy::prelude::*;
 use bevy_turborand::prelude::*;
 use std::f32::consts::TAU;
 
+use super::gate::gate_translation;
+
 // /// Deserialize and build a zone from a [`ZoneDescription`]
 // pub(super) fn on_load_zone()
 
@@ -11,53 +13,274 @@ pub(super) fn on_generate_chunk(
     trigger: Trigger<triggers::GenerateChunks>,
     mut chunks: ResMut<Chunks>,
     mut cmd: Commands,
-    mut rng: ResMut<GlobalRng>,
-    factions: Res<Factions>,
+    mut factions: ResMut<Factions>,
+    seed: Res<WorldSeed>,
+    universe: Res<Universe>,
+    library: Res<Library>,
+    biomes: Res<Assets<Biome>>,
 ) {
     let triggers::GenerateChunks { chunk_indicies } = trigger.event();
 
-    let player_faction = factions.get_faction("player").unwrap();
-    let enemy_faction = factions.get_faction("enemy").unwrap();
+    // Sort biomes so they are always picked from in the same order
+    let mut templates = library
+        .biomes
+        .values()
+        .filter_map(|biome| biomes.get(biome))
+        .collect::<Vec<_>>();
+    templates.sort_by(|a, b| a.name.cmp(&b.name));
+
+    // Every zone generates differently, and deeper zones are more dangerous
+    let zone = universe.current();
+    let seed = seed.zone(zone);
+    let depth = universe.current_zone().depth;
 
     for chunk_index in chunk_indicies {
         // If the chunk is not already generated...
         if !chunks.is_generated(chunk_index) {
-            // Generate the chunk within a parent with offset determined by the chunk
-            let rand_point = |rng: &mut GlobalRng| -> Vec2 {
-                let mut t = Transform::default_z();
-                t.rotate_z(rng.f32() * TAU);
-                let point = t.forward() * 10f32;
-                // Add chunk offset and return
-                point.truncate() + chunk_index.to_world_coordinates()
-            };
+            for mut building in chunk_buildings(seed, *chunk_index, &templates, &mut factions) {
+                building.danger += depth;
+                cmd.trigger(building);
+            }
 
-            let mut rotation = Transform::default_z();
-            rotation.rotate_z(rng.f32() * TAU);
+            // Gates to neighboring zones are placed around the center of the zone
+            if *chunk_index == ChunkIndex::from((0, 0)) {
+                for destination in universe.gates(zone) {
+                    cmd.trigger(triggers::SpawnBuilding {
+                        name: "gate".into(),
+                        translation: gate_translation(&universe, zone, destination),
+                        rotation: 0f32,
+                        alliegance: Alliegance::default(),
+                        seed: 0,
+                        danger: 0,
+                        gate: Some(destination.index()),
+                    });
+                }
+            }
 
-            cmd.trigger(triggers::SpawnBuilding {
-                name: "nest".into(),
-                translation: rand_point(&mut rng),
-                rotation: 0f32,
-                alliegance: Alliegance {
-                    faction: *enemy_faction,
-                    allies: [*enemy_faction].into(),
-                    enemies: [*player_faction].into(),
-                },
-            });
+            // Add the chunk to loaded chunks
+            chunks.insert(*chunk_index);
+        }
+    }
+}
+
+/// Attempts at finding room for a building before it is skipped
+const PLACEMENT_ATTEMPTS: usize = 16;
+
+/// Buildings in a chunk, which are always the same for the same seed and chunk
+fn chunk_buildings(
+    seed: WorldSeed,
+    chunk_index: ChunkIndex,
+    biomes: &[&Biome],
+    factions: &mut Factions,
+) -> Vec<triggers::SpawnBuilding> {
+    let mut rng = seed.chunk_rng(chunk_index);
+
+    // Pick a biome allowed at this distance from the origin
+    let distance = chunk_index.distance(&(0, 0).into());
+    let allowed = biomes
+        .iter()
+        .filter(|biome| {
+            let (min, max) = biome.distance;
+            distance >= min && max.map_or(true, |max| distance <= max)
+        })
+        .map(|biome| (*biome, biome.weight))
+        .collect::<Vec<_>>();
+    let Some(biome) = pick_weighted(&allowed, &mut rng) else {
+        return Vec::new();
+    };
+    let Placement {
+        radius,
+        min_spacing,
+        cluster,
+    } = biome.placement;
+
+    let mut placed: Vec<Vec2> = Vec::new();
+    let mut buildings = Vec::new();
+    for set in biome.buildings.iter() {
+        let faction = factions.register_or_retrieve(&set.faction);
+        let enemies = set
+            .enemies
+            .iter()
+            .map(|enemy| factions.register_or_retrieve(enemy))
+            .collect::<FactionSet>();
+
+        // Clustered sets are placed around their own point in the chunk
+        let (center, radius) = match cluster {
+            Some(cluster) => (rand_point(&mut rng, radius), cluster),
+            None => (Vec2::ZERO, radius),
+        };
 
-            cmd.trigger(triggers::SpawnBuilding {
-                name: "store".into(),
-                translation: rand_point(&mut rng),
+        let choices = set
+            .buildings
+            .iter()
+            .map(|(name, weight)| (name, *weight))
+            .collect::<Vec<_>>();
+        // A count written the wrong way around is still a valid range
+        let (min, max) = (set.count.0.min(set.count.1), set.count.0.max(set.count.1));
+        for _ in 0..rng.usize(min..=max) {
+            let Some(name) = pick_weighted(&choices, &mut rng) else {
+                break;
+            };
+            // Skip the building if there is no room left for it
+            let Some(point) = (0..PLACEMENT_ATTEMPTS)
+                .map(|_| center + rand_point(&mut rng, radius))
+                .find(|point| {
+                    placed
+                        .iter()
+                        .all(|other| other.distance(*point) >= min_spacing)
+                })
+            else {
+                continue;
+            };
+            placed.push(point);
+
+            buildings.push(triggers::SpawnBuilding {
+                name: name.clone(),
+                // Add chunk offset
+                translation: point + chunk_index.to_world_coordinates(),
                 rotation: 0f32,
                 alliegance: Alliegance {
-                    faction: *player_faction,
-                    allies: [*player_faction].into(),
-                    enemies: [*enemy_faction].into(),
+                    faction,
+                    allies: [faction].into(),
+                    enemies: enemies.clone(),
                 },
+                seed: rng.u64(..),
+                danger: biome.danger,
+                gate: None,
             });
+        }
+    }
+    buildings
+}
 
-            // Add the chunk to loaded chunks
-            chunks.insert(*chunk_index);
+/// Random point within `radius` of the origin
+fn rand_point(rng: &mut Rng, radius: f32) -> Vec2 {
+    let mut t = Transform::default_z();
+    t.rotate_z(rng.f32() * TAU);
+    // Take the square root so points are spread evenly over the area
+    (t.forward() * radius * rng.f32().sqrt()).truncate()
+}
+
+/// Pick one of several choices, each paired with its weight
+fn pick_weighted<T: Copy>(choices: &[(T, u32)], rng: &mut Rng) -> Option<T> {
+    let total = choices.iter().map(|(_, weight)| weight).sum::<u32>();
+    if total == 0 {
+        return None;
+    }
+    let mut roll = rng.u32(..total);
+    for (choice, weight) in choices {
+        if roll < *weight {
+            return Some(*choice);
+        }
+        roll -= weight;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::building::roll_store_stock;
+    use super::*;
+
+    fn biome(name: &str, distance: (i32, Option<i32>), buildings: &[&str]) -> Biome {
+        Biome {
+            name: name.into(),
+            distance,
+            weight: 1,
+            danger: 0,
+            placement: Placement::default(),
+            buildings: buildings
+                .iter()
+                .map(|building| BuildingSet {
+                    buildings: vec![(building.to_string(), 1)],
+                    count: (1, 1),
+                    faction: "enemy".into(),
+                    enemies: vec!["player".into()],
+                })
+                .collect(),
         }
     }
+
+    #[test]
+    fn test_chunks_are_deterministic() {
+        let mut factions = Factions::default();
+        let frontier = biome("frontier", (0, None), &["nest", "store"]);
+        let chunk_indicies: Vec<ChunkIndex> =
+            vec![(0, 0).into(), (0, 1).into(), (1, 0).into(), (-1, 0).into()];
+        let store = vec![
+            ("scrap_metal".to_string(), (1, 2)),
+            ("light_laser".to_string(), (1, 4)),
+            ("ion".to_string(), (3, 4)),
+        ];
+
+        let mut generate = |chunk_indicies: &[ChunkIndex]| {
+            let mut buildings = chunk_indicies
+                .iter()
+                .flat_map(|chunk_index| {
+                    chunk_buildings(WorldSeed(7), *chunk_index, &[&frontier], &mut factions)
+                })
+                .map(|building| {
+                    let stock = roll_store_stock(
+                        &store,
+                        |_| Rarity::Common,
+                        &mut Rng::with_seed(building.seed),
+                    );
+                    (building.name, building.translation.to_array(), stock)
+                })
+                .collect::<Vec<_>>();
+            buildings.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+            buildings
+        };
+
+        // Visiting chunks in a different order gives the same world
+        let buildings = generate(&chunk_indicies);
+        let reversed = chunk_indicies.iter().rev().copied().collect::<Vec<_>>();
+        assert_eq!(buildings, generate(&reversed));
+        assert_eq!(buildings.len(), 8);
+
+        // A different seed gives a different world
+        let other = chunk_buildings(WorldSeed(8), chunk_indicies[0], &[&frontier], &mut factions);
+        assert_ne!(
+            other[0].translation,
+            chunk_buildings(WorldSeed(7), chunk_indicies[0], &[&frontier], &mut factions)[0]
+                .translation
+        );
+    }
+
+    #[test]
+    fn test_biome_templates() {
+        let mut factions = Factions::default();
+        let near = biome("near", (0, Some(1)), &["store"]);
+        let mut far = biome("far", (2, None), &["nest"]);
+        far.danger = 2;
+        far.placement = Placement {
+            radius: 40f32,
+            min_spacing: 5f32,
+            cluster: None,
+        };
+        far.buildings[0].count = (6, 6);
+        let biomes = [&near, &far];
+
+        // Biomes are picked by distance from the origin
+        let buildings = chunk_buildings(WorldSeed(1), (1, -1).into(), &biomes, &mut factions);
+        assert!(buildings.iter().all(|building| building.name == "store"));
+        let buildings = chunk_buildings(WorldSeed(1), (-3, 2).into(), &biomes, &mut factions);
+        assert!(buildings.iter().all(|building| building.name == "nest"));
+        assert!(buildings.iter().all(|building| building.danger == 2));
+
+        // Buildings respect the placement rules
+        let center = ChunkIndex::from((-3, 2)).to_world_coordinates();
+        for (i, a) in buildings.iter().enumerate() {
+            assert!(a.translation.distance(center) <= 40f32);
+            for b in buildings.iter().skip(i + 1) {
+                assert!(a.translation.distance(b.translation) >= 5f32);
+            }
+        }
+
+        // Factions named by biomes are registered
+        let enemy = *factions.get_faction("enemy").unwrap();
+        let player = *factions.get_faction("player").unwrap();
+        assert!(buildings[0].alliegance.faction == enemy);
+        assert!(buildings[0].alliegance.enemies.contains(&player));
+    }
 }
@@ -53,6 +53,7 @@ pub(super) fn on_spawn_creature(
             transform: Transform::z_from_parts(translation, rotation),
             alliegance: alliegance.clone(),
             inventory: Inventory::with_capacity(craft.capacity)
+                .with_slots(craft.slots)
                 .with_many_from_str(
                     inventory.into_iter().collect::<HashMap<String, usize>>(),
                     &items,
@@ -62,7 +63,7 @@ pub(super) fn on_spawn_creature(
             equipped,
             ..default()
         },
-        // Persistent,
+        Persistent,
         Model::new(library.model(&model).unwrap()),
         Drops(drops),
         InRange::new(range),
@@ -78,32 +79,46 @@ pub(super) fn on_spawn_creature(
         ent.insert(Credits::new(credits));
     }
 
-    ent.insert(
-        Thinker::build()
-            .picker(pickers::Highest)
-            // .when(
-            //     AllOrNothing::build(0.01f32)
-            //         .push(scorers::Facing)
-            //         .push(scorers::TargetInRange),
-            //     Concurrently::build()
-            //         .push(actions::Attack)
-            //         .push(actions::Persue),
-            // )
-            // .when(
-            //     AllOrNothing::build(0.01f32)
-            //         .push(EvaluatingScorer::build(
-            //             scorers::Facing,
-            //             LinearEvaluator::new_inversed(),
-            //         ))
-            //         .push(scorers::TargetInRange),
-            //     actions::Persue,
-            // )
-            .otherwise(actions::Idle),
+    ent.insert(creature_thinker());
+}
+
+/// Creatures are saved and unloaded without their AI, so give them a new [`Thinker`] once they
+/// are spawned again
+pub(super) fn finalize_creatures(
+    mut cmd: Commands,
+    creatures: Query<Entity, (With<InRange>, Without<HasThinker>, Without<ThinkerBuilder>)>,
+) {
+    for entity in creatures.iter() {
+        cmd.entity(entity).insert(creature_thinker());
+    }
+}
+
+/// The utility AI that drives every creature
+fn creature_thinker() -> ThinkerBuilder {
+    Thinker::build()
+        .picker(pickers::Highest)
+        // .when(
+        //     AllOrNothing::build(0.01f32)
+        //         .push(scorers::Facing)
+        //         .push(scorers::TargetInRange),
+        //     Concurrently::build()
+        //         .push(actions::Attack)
+        //         .push(actions::Persue),
+        // )
         // .when(
-        //     scorers::Danger {
-        //         radius: 3f32..=15f32,
-        //     },
-        //     actions::Retreat,
-        // ),
-    );
+        //     AllOrNothing::build(0.01f32)
+        //         .push(EvaluatingScorer::build(
+        //             scorers::Facing,
+        //             LinearEvaluator::new_inversed(),
+        //         ))
+        //         .push(scorers::TargetInRange),
+        //     actions::Persue,
+        // )
+        .otherwise(actions::Idle)
+    // .when(
+    //     scorers::Danger {
+    //         radius: 3f32..=15f32,
+    //     },
+    //     actions::Retreat,
+    // )
 }
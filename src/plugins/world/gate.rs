@@ -0,0 +1,164 @@
+use std::f32::consts::TAU;
+
+use crate::prelude::*;
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use petgraph::graph::NodeIndex;
+
+use super::streaming::{try_stream_chunks, unload_chunks};
+
+/// Distance of every gate from the center of its zone
+const GATE_DISTANCE: f32 = 40f32;
+/// Distance from the paired gate that the player arrives at
+const ARRIVAL_DISTANCE: f32 = 12f32;
+
+/// Where the gate from one zone to another is placed. Gates are spread evenly around the center
+/// of their zone.
+pub(super) fn gate_translation(
+    universe: &Universe,
+    zone: NodeIndex,
+    destination: NodeIndex,
+) -> Vec2 {
+    let gates = universe.gates(zone);
+    let index = gates
+        .iter()
+        .position(|gate| *gate == destination)
+        .unwrap_or_default();
+    Vec2::from_angle(TAU * index as f32 / gates.len().max(1) as f32) * GATE_DISTANCE
+}
+
+/// Store the current zone on disk and move the player next to the paired gate of the destination,
+/// which is then loaded or generated
+pub(super) fn enter_travel(world: &mut World) {
+    if let Err(e) = travel(world) {
+        world.send_event(GameError::from(e));
+        world
+            .resource_mut::<NextState<AppState>>()
+            .set(AppState::Main);
+    }
+}
+
+fn travel(world: &mut World) -> Result<(), SaveError> {
+    let AppState::Travel { destination } = world.resource::<State<AppState>>().get().clone() else {
+        unreachable!()
+    };
+    let destination = NodeIndex::new(destination);
+    let save_path = world.resource::<SavePath>().0.clone();
+    let Some(save_path) =
+        save_path.filter(|_| world.resource::<Universe>().zone(destination).is_some())
+    else {
+        world
+            .resource_mut::<NextState<AppState>>()
+            .set(AppState::Main);
+        return Ok(());
+    };
+
+    // Leave the gate
+    let players = world
+        .query_filtered::<Entity, With<Player>>()
+        .iter(world)
+        .collect::<Vec<_>>();
+    for player in players {
+        if let Some(Docked(dock)) = world.entity_mut(player).take::<Docked>() {
+            if let Some(joint) = world
+                .get_mut::<Dockings>(dock)
+                .and_then(|mut dockings| dockings.remove(&player))
+            {
+                if let Some(joint) = world.get_entity_mut(joint) {
+                    joint.despawn_recursive();
+                }
+            }
+        }
+    }
+
+    // Store every chunk of the zone being left, and clean up anything that is not saved with it
+    unload_chunks(world, &save_path, |_| true)?;
+    let unsaved = world
+        .query_filtered::<Entity, (
+            With<Transform>,
+            Without<Persistent>,
+            Without<Parent>,
+            Without<Camera>,
+        )>()
+        .iter(world)
+        .collect::<Vec<_>>();
+    for entity in unsaved {
+        world.entity_mut(entity).despawn_recursive();
+    }
+
+    // Swap to the chunks of the destination
+    let origin = world.resource::<Universe>().current();
+    let chunks = world.remove_resource::<Chunks>().unwrap_or_default();
+    let chunks = world.resource_mut::<Universe>().travel(destination, chunks);
+    world.insert_resource(chunks);
+
+    arrive(world, origin);
+
+    // Load the destination around the player and save the game in its new zone
+    try_stream_chunks(world)?;
+    world
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::SaveGame { save_path });
+    Ok(())
+}
+
+/// Move the player just outside of the gate of the current zone that leads back to `origin`
+fn arrive(world: &mut World, origin: NodeIndex) {
+    let universe = world.resource::<Universe>();
+    let gate = gate_translation(universe, universe.current(), origin);
+    let arrival = gate + gate.normalize_or_zero() * ARRIVAL_DISTANCE;
+    for (mut transform, velocity, mut respawn_point) in world
+        .query_filtered::<(
+            &mut Transform,
+            Option<&mut LinearVelocity>,
+            Option<&mut RespawnPoint>,
+        ), With<Player>>()
+        .iter_mut(world)
+    {
+        transform.translation = arrival.extend(transform.translation.z);
+        if let Some(mut velocity) = velocity {
+            velocity.0 = Vec3::ZERO;
+        }
+        // Stations of the zone being left are out of reach
+        if let Some(respawn_point) = respawn_point.as_mut() {
+            respawn_point.0 = arrival;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_turborand::prelude::*;
+
+    #[test]
+    fn test_arrive() {
+        let mut world = World::new();
+        let mut universe = Universe::generate(3, (2, 2), &mut Rng::with_seed(10));
+        let origin = universe.current();
+        let destination = universe.gates(origin)[0];
+        universe.travel(destination, Chunks::default());
+        world.insert_resource(universe);
+        let player = world
+            .spawn((
+                Player(0),
+                Transform::from_xyz(100f32, -20f32, 1f32),
+                LinearVelocity(Vec3::X * 5f32),
+                RespawnPoint(Vec2::new(100f32, -20f32)),
+            ))
+            .id();
+
+        arrive(&mut world, origin);
+        let gate = gate_translation(world.resource::<Universe>(), destination, origin);
+        let translation = world.get::<Transform>(player).unwrap().translation;
+        // The player arrives outside of the gate leading back, and respawns there from now on
+        assert!((translation.truncate().distance(gate) - ARRIVAL_DISTANCE).abs() < 1e-3);
+        assert!(translation.truncate().length() > gate.length());
+        assert_eq!(translation.z, 1f32);
+        assert_eq!(world.get::<LinearVelocity>(player).unwrap().0, Vec3::ZERO);
+        assert_eq!(
+            world.get::<RespawnPoint>(player).unwrap().0,
+            translation.truncate()
+        );
+    }
+}
@@ -1,15 +1,23 @@
 mod building;
 mod chunk;
 mod creature;
+mod gate;
+mod streaming;
 
 use std::{f32::consts::TAU, time::Duration};
 
 use crate::prelude::*;
-use bevy::prelude::*;
+use avian3d::prelude::{
+    Collider, ColliderConstructor, CollisionLayers, Friction, LinearVelocity, LockedAxes, Mass,
+    RigidBody,
+};
+use bevy::{prelude::*, time::common_conditions::on_timer};
+use bevy_turborand::prelude::*;
 use building::*;
 use chunk::*;
 use creature::*;
-use rand::{seq::*, Rng};
+use gate::*;
+use streaming::*;
 
 pub struct WorldPlugin;
 
@@ -23,6 +31,7 @@ impl Plugin for WorldPlugin {
             .register_type::<components::Craft>()
             .register_type::<components::Credits>()
             .register_type::<components::Damage>()
+            .register_type::<components::DamageType>()
             .register_type::<components::Destroyed>()
             .register_type::<components::DockInRange>()
             .register_type::<components::Drops>()
@@ -30,14 +39,21 @@ impl Plugin for WorldPlugin {
             .register_type::<components::DropRate>()
             .register_type::<components::Docked>()
             .register_type::<components::Dockings>()
+            .register_type::<components::Gate>()
             .register_type::<components::Generator>()
+            .register_type::<components::Handling>()
             .register_type::<components::Energy>()
+            .register_type::<components::Engine>()
             .register_type::<components::Equipped>()
             .register_type::<components::EquippedBuilder>()
             .register_type::<components::InventoryBuilder>()
             .register_type::<components::EquipmentType>()
             .register_type::<components::Faction>()
             .register_type::<Factions>()
+            .register_type::<WorldSeed>()
+            .register_type::<Chunks>()
+            .register_type::<Universe>()
+            .register_type::<components::Hardpoint>()
             .register_type::<components::Health>()
             .register_type::<components::Heat>()
             .register_type::<components::InRange>()
@@ -48,21 +64,52 @@ impl Plugin for WorldPlugin {
             .register_type::<components::Persistent>()
             .register_type::<components::Player>()
             .register_type::<components::Projectile>()
+            .register_type::<components::Radiator>()
             .register_type::<components::RepairBot>()
+            .register_type::<components::Shield>()
             .register_type::<components::Store>()
             .register_type::<components::SpawnedFrom>()
             .register_type::<components::Spawner>()
             .register_type::<components::Structure>()
             .register_type::<components::Weapon>()
             .register_type::<components::WeaponType>()
+            .register_saveable::<components::Alliegance>()
+            .register_saveable::<components::Craft>()
+            .register_saveable::<components::Credits>()
+            .register_saveable::<components::Damage>()
+            .register_saveable::<components::Destroyed>()
+            .register_saveable::<components::Gate>()
+            .register_saveable::<components::Health>()
+            .register_saveable::<components::Model>()
+            .register_saveable::<components::Spawner>()
+            .register_saveable::<components::SpawnedFrom>()
+            .register_saveable::<components::Structure>()
+            .register_saveable::<RigidBody>()
+            .register_saveable::<CollisionLayers>()
+            .register_saveable::<LinearVelocity>()
+            .register_saveable::<LockedAxes>()
+            .register_saveable::<Friction>()
+            .register_saveable::<Mass>()
+            .register_saveable_with::<ColliderConstructor>(save_collider)
+            .init_resource::<WorldSeed>()
+            .init_resource::<Chunks>()
+            .init_resource::<Universe>()
+            .init_resource::<SaveRegistry>()
             .insert_resource(ClearColor(Color::BLACK))
             .insert_resource(AmbientLight {
                 color: Color::WHITE,
                 brightness: 20.,
             })
+            .add_systems(OnEnter(AppState::travel()), enter_travel)
+            .add_systems(
+                Update,
+                stream_chunks
+                    .run_if(in_state(AppState::main()).and_then(on_timer(Duration::from_secs(1)))),
+            )
             .add_systems(
                 Update,
                 (
+                    finalize_creatures,
                     manage_spawners,
                     setup_health,
                     cleanup_empty_chests,
@@ -77,17 +124,42 @@ impl Plugin for WorldPlugin {
     }
 }
 
+/// Colliders cannot be serialized, so save the [`ColliderConstructor`] that rebuilds them
+fn save_collider(world: &World, entity: Entity) -> Option<Box<dyn Reflect>> {
+    let shape = world.get::<Collider>(entity)?.shape();
+    let constructor = if let Some(ball) = shape.as_ball() {
+        ColliderConstructor::Sphere {
+            radius: ball.radius,
+        }
+    } else if let Some(cuboid) = shape.as_cuboid() {
+        let length = cuboid.half_extents * 2f32;
+        ColliderConstructor::Cuboid {
+            x_length: length.x,
+            y_length: length.y,
+            z_length: length.z,
+        }
+    } else if let Some(cylinder) = shape.as_cylinder() {
+        ColliderConstructor::Cylinder {
+            radius: cylinder.radius,
+            height: cylinder.half_height * 2f32,
+        }
+    } else {
+        return None;
+    };
+    Some(Box::new(constructor))
+}
+
 fn manage_spawners(
     mut cmd: Commands,
     mut spawners: Query<(Entity, &mut Spawner, &Transform), Without<Destroyed>>,
     factions: Res<Factions>,
     spawned_from: Query<&SpawnedFrom, Without<Destroyed>>,
+    mut rng: ResMut<GlobalRng>,
     time: Res<Time>,
 ) {
     let enemy_faction = *factions.get_faction("enemy").unwrap();
     let player_faction = *factions.get_faction("player").unwrap();
     for (entity, mut spawner, transform) in spawners.iter_mut() {
-        let mut rng = rand::thread_rng();
         let new_time = spawner.last_tick + Duration::from_secs_f32(spawner.tick);
         if time.elapsed() >= new_time
             && spawned_from.iter().filter(|s| s.0 == entity).count() < spawner.maximum
@@ -95,14 +167,14 @@ fn manage_spawners(
             // Go through our spawnlist and roll until we get a spawn
             let mut spawns = spawner.spawns.clone();
             // Shuffle potential spawns so we don't bias towards the first entries
-            spawns.shuffle(&mut rng);
+            rng.shuffle(&mut spawns);
             for (spawn, d) in spawns.into_iter() {
-                if rng.gen_ratio(1, d as u32) {
+                if rng.u32(..d as u32) == 0 {
                     // Spawn thing
                     cmd.trigger(triggers::SpawnCreature {
                         name: spawn.clone(),
                         translation: transform.translation.truncate(),
-                        rotation: rng.gen_range(0f32..TAU),
+                        rotation: rng.f32() * TAU,
                         alliegance: Alliegance {
                             faction: enemy_faction,
                             allies: [enemy_faction].into(),
@@ -124,8 +196,11 @@ fn manage_spawners(
 /// so we can use this system to apply it
 fn setup_health(mut cmd: Commands, crafts: Query<(Entity, &Craft), Added<Craft>>) {
     for (entity, craft) in crafts.iter() {
-        cmd.entity(entity)
-            .insert((Health::new(craft.health), Damage::default()));
+        let mut entity = cmd.entity(entity);
+        entity.insert((Health::new(craft.health), Damage::default()));
+        if let Some(explosive) = craft.explosion {
+            entity.insert(explosive);
+        }
     }
 }
 
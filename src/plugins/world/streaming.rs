@@ -0,0 +1,375 @@
+use std::{fs, path::Path};
+
+use crate::prelude::*;
+use avian3d::prelude::RigidBody;
+use bevy::{
+    ecs::{entity::EntityHashMap, reflect::ReflectMapEntities},
+    prelude::*,
+    scene::DynamicEntity,
+    utils::HashMap,
+};
+
+/// Generate chunks near the player, store distant chunks on disk, and reload stored chunks once
+/// the player returns to them
+pub(super) fn stream_chunks(world: &mut World) {
+    if let Err(e) = try_stream_chunks(world) {
+        world.send_event(GameError::from(e));
+    }
+}
+
+pub(super) fn try_stream_chunks(world: &mut World) -> Result<(), SaveError> {
+    let Some(save_path) = world.resource::<SavePath>().0.clone() else {
+        return Ok(());
+    };
+    let Some(center) = world
+        .query_filtered::<&Transform, With<Player>>()
+        .iter(world)
+        .next()
+        .map(|transform| ChunkIndex::from_world_coordinates(transform.translation.truncate()))
+    else {
+        return Ok(());
+    };
+    let settings::ChunkSettings {
+        load_radius,
+        unload_radius,
+    } = {
+        let library = world.resource::<Library>();
+        let settings = world.resource::<Assets<Settings>>();
+        settings.get(&library.settings).unwrap().chunks.clone()
+    };
+    let zone = world.resource::<Universe>().current();
+
+    // Store distant chunks on disk
+    unload_chunks(world, &save_path, |chunk_index| {
+        chunk_index.distance(&center) > unload_radius
+    })?;
+
+    // Reload or generate nearby chunks
+    let mut ungenerated = Vec::new();
+    for chunk_index in center.around(load_radius) {
+        let chunks = world.resource::<Chunks>();
+        if !chunks.is_generated(&chunk_index) {
+            ungenerated.push(chunk_index);
+        } else if chunks.is_unloaded(&chunk_index) {
+            let path = chunk_path(&staged_chunk_dir(&save_path), zone, chunk_index);
+            if path.exists() {
+                reload_chunk(world, &path)?;
+            }
+            world
+                .resource_mut::<Chunks>()
+                .set_unloaded(chunk_index, false);
+        }
+    }
+    if !ungenerated.is_empty() {
+        world.trigger(triggers::GenerateChunks {
+            chunk_indicies: ungenerated,
+        });
+    }
+    Ok(())
+}
+
+/// Store every chunk of the current zone that matches `filter` on disk
+pub(super) fn unload_chunks(
+    world: &mut World,
+    save_path: &Path,
+    filter: impl Fn(ChunkIndex) -> bool,
+) -> Result<(), SaveError> {
+    let zone = world.resource::<Universe>().current();
+    let mut unloading = HashMap::<ChunkIndex, Vec<Entity>>::default();
+    for (entity, transform) in world
+        .query_filtered::<(Entity, &Transform), (With<Persistent>, With<RigidBody>, Without<Player>)>()
+        .iter(world)
+    {
+        let chunk_index = ChunkIndex::from_world_coordinates(transform.translation.truncate());
+        if filter(chunk_index) {
+            unloading.entry(chunk_index).or_default().push(entity);
+        }
+    }
+    for (chunk_index, entities) in unloading {
+        let keep_stored = world.resource::<Chunks>().is_unloaded(&chunk_index);
+        unload_chunk(
+            world,
+            &chunk_path(&staged_chunk_dir(save_path), zone, chunk_index),
+            &entities,
+            keep_stored,
+        )?;
+        world
+            .resource_mut::<Chunks>()
+            .set_unloaded(chunk_index, true);
+    }
+    Ok(())
+}
+
+/// Write entities to a chunk file and despawn them. When `keep_stored` is set, entities already
+/// stored in the file are kept, otherwise the file is replaced.
+pub(super) fn unload_chunk(
+    world: &mut World,
+    path: &Path,
+    entities: &[Entity],
+    keep_stored: bool,
+) -> Result<(), SaveError> {
+    // Stored entities are spawned again first, since their IDs may belong to an earlier session
+    let mut entities = entities.to_vec();
+    if keep_stored && path.exists() {
+        let reloaded = reload_chunk(world, path)?;
+        // Entities being unloaded may still refer to stored ones by the IDs they were stored
+        // with in this session, which are no longer alive. A stored ID from an earlier session
+        // can be the ID of a live entity, and references to it are to that live entity, so only
+        // stored IDs that are not alive are remapped and every live entity maps to itself.
+        let mut entity_map = world
+            .iter_entities()
+            .map(|entity| (entity.id(), entity.id()))
+            .collect::<EntityHashMap<_>>();
+        for (stored, spawned) in reloaded.iter() {
+            if world.get_entity(*stored).is_none() {
+                entity_map.insert(*stored, *spawned);
+            }
+        }
+        let registry = world.resource::<AppTypeRegistry>().clone();
+        for registration in registry.read().iter() {
+            if let Some(map_entities) = registration.data::<ReflectMapEntities>() {
+                map_entities.map_entities(world, &mut entity_map, &entities);
+            }
+        }
+        entities.extend(reloaded.into_values());
+    }
+
+    // Players are saved with the rest of the world, so only a stub of each is stored for
+    // entities of the chunk that refer to them
+    let players = world
+        .query::<(Entity, &Player)>()
+        .iter(world)
+        .map(|(entity, player)| DynamicEntity {
+            entity,
+            components: vec![player.clone_value()],
+        })
+        .collect::<Vec<_>>();
+    let scene = DynamicScene {
+        resources: Vec::new(),
+        entities: entities
+            .iter()
+            .map(|entity| world.resource::<SaveRegistry>().extract(world, *entity))
+            .chain(players)
+            .collect(),
+    };
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let save = SaveFile::from_scene(&scene, &registry.read())?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    write_save(path, &save.to_ron()?, 0)?;
+
+    for entity in entities {
+        world.entity_mut(entity).despawn_recursive();
+    }
+    Ok(())
+}
+
+/// Spawn the entities stored in a chunk file as new entities, returning the entity each stored
+/// ID was spawned as. The file is kept, and replaced once the chunk is unloaded again.
+pub(super) fn reload_chunk(
+    world: &mut World,
+    path: &Path,
+) -> Result<EntityHashMap<Entity>, SaveError> {
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let mut scene = SaveFile::from_ron(
+        &fs::read_to_string(path)?,
+        world.resource::<SaveMigrations>(),
+        &registry.read(),
+    )?
+    .into_scene(&registry.read())?;
+    let (stubs, stored) = std::mem::take(&mut scene.entities)
+        .into_iter()
+        .partition::<Vec<_>, _>(|stored| {
+            stored
+                .components
+                .iter()
+                .any(|component| component.represents::<Player>())
+        });
+
+    // References to a player point to the live player with the same ID. References to anything
+    // else outside of the chunk are left dangling.
+    let mut entity_map = EntityHashMap::default();
+    for stub in stubs {
+        let Some(Player(id)) = stub
+            .components
+            .iter()
+            .find_map(|component| Player::from_reflect(component.as_ref()))
+        else {
+            continue;
+        };
+        if let Some((player, _)) = world
+            .query::<(Entity, &Player)>()
+            .iter(world)
+            .find(|(_, player)| ***player == id)
+        {
+            entity_map.insert(stub.entity, player);
+        }
+    }
+
+    // Every stored entity is spawned anew, as its stored ID may already be taken
+    let spawned = stored
+        .iter()
+        .map(|stored| (stored.entity, world.spawn_empty().id()))
+        .collect::<EntityHashMap<_>>();
+    entity_map.extend(spawned.iter().map(|(stored, entity)| (*stored, *entity)));
+    scene.entities = stored;
+    scene.write_to_world(world, &mut entity_map)?;
+    Ok(spawned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::utils::HashSet;
+
+    #[test]
+    fn test_unload_and_reload_chunk() {
+        let mut app = App::new();
+        app.init_resource::<SaveMigrations>()
+            .register_saveable::<Persistent>()
+            .register_saveable::<Player>()
+            .register_saveable::<Credits>()
+            .register_saveable::<SpawnedFrom>()
+            .register_saveable::<Target>();
+        let world = app.world_mut();
+
+        let player = world.spawn((Persistent, Player(0))).id();
+        let nest = world.spawn((Persistent, Credits::new(100))).id();
+        let creature = world
+            .spawn((Persistent, SpawnedFrom(nest), Target(player)))
+            .id();
+
+        let dir = std::env::temp_dir().join(format!("qz-test-chunks-{}", std::process::id()));
+        let path = dir.join("0_0.chunk.ron");
+        unload_chunk(world, &path, &[nest], false).unwrap();
+        unload_chunk(world, &path, &[creature], true).unwrap();
+        assert!(world.get_entity(nest).is_none() && world.get_entity(creature).is_none());
+
+        reload_chunk(world, &path).unwrap();
+        let spawned_from = world
+            .query::<(&SpawnedFrom, &Target)>()
+            .iter(world)
+            .map(|(spawned_from, target)| {
+                // The player was never unloaded, so it is still the target
+                assert_eq!(target.0, player);
+                spawned_from.0
+            })
+            .collect::<HashSet<_>>();
+        let nests = world
+            .query_filtered::<Entity, With<Credits>>()
+            .iter(world)
+            .collect::<HashSet<_>>();
+        assert_eq!(spawned_from, nests);
+        assert_eq!(nests.len(), 1);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_reload_chunk_into_new_world() {
+        let app = || {
+            let mut app = App::new();
+            app.init_resource::<SaveMigrations>()
+                .register_saveable::<Persistent>()
+                .register_saveable::<Player>()
+                .register_saveable::<Credits>()
+                .register_saveable::<Target>();
+            app
+        };
+
+        let dir = std::env::temp_dir().join(format!("qz-test-new-world-{}", std::process::id()));
+        let path = dir.join("0_0.chunk.ron");
+        let mut first = app();
+        let world = first.world_mut();
+        let player = world.spawn((Persistent, Player(0))).id();
+        let creature = world
+            .spawn((Persistent, Credits::new(5), Target(player)))
+            .id();
+        unload_chunk(world, &path, &[creature], false).unwrap();
+
+        // A later session, where the stored IDs belong to unrelated entities
+        let mut second = app();
+        let world = second.world_mut();
+        let occupied = (0..4)
+            .map(|_| world.spawn(Credits::new(1)).id())
+            .collect::<Vec<_>>();
+        let player = world.spawn((Persistent, Player(0))).id();
+        let reloaded = reload_chunk(world, &path)
+            .unwrap()
+            .into_values()
+            .collect::<Vec<_>>();
+        assert_eq!(reloaded.len(), 1);
+        assert!(!occupied.contains(&reloaded[0]));
+        assert_eq!(world.get::<Target>(reloaded[0]).unwrap().0, player);
+        assert_eq!(world.get::<Credits>(reloaded[0]).unwrap().get(), 5);
+        assert!(occupied
+            .iter()
+            .all(|entity| world.get::<Credits>(*entity).unwrap().get() == 1
+                && world.get::<Target>(*entity).is_none()));
+
+        // Entities stored by the earlier session are kept alongside new ones
+        let arrival = world.spawn((Persistent, Credits::new(7))).id();
+        unload_chunk(world, &path, &reloaded, false).unwrap();
+        unload_chunk(world, &path, &[arrival], true).unwrap();
+        let mut credits = reload_chunk(world, &path)
+            .unwrap()
+            .into_values()
+            .map(|entity| world.get::<Credits>(entity).unwrap().get())
+            .collect::<Vec<_>>();
+        credits.sort();
+        assert_eq!(credits, [5, 7]);
+        assert_eq!(
+            world.query::<&Player>().iter(world).count(),
+            1,
+            "player stubs should not be spawned"
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_stored_id_of_live_entity() {
+        let app = || {
+            let mut app = App::new();
+            app.init_resource::<SaveMigrations>()
+                .register_saveable::<Persistent>()
+                .register_saveable::<Player>()
+                .register_saveable::<Credits>()
+                .register_saveable::<Target>();
+            app
+        };
+
+        let dir = std::env::temp_dir().join(format!("qz-test-collision-{}", std::process::id()));
+        let path = dir.join("0_0.chunk.ron");
+        let mut first = app();
+        let world = first.world_mut();
+        let stored = world.spawn((Persistent, Credits::new(5))).id();
+        unload_chunk(world, &path, &[stored], false).unwrap();
+
+        // A later session, where the stored ID is taken by the player
+        let mut second = app();
+        let world = second.world_mut();
+        let live = world.spawn((Persistent, Player(0))).id();
+        assert_eq!(live, stored);
+        let hunter = world
+            .spawn((Persistent, Credits::new(2), Target(live)))
+            .id();
+        unload_chunk(world, &path, &[hunter], true).unwrap();
+        assert!(world.get::<Player>(live).is_some());
+
+        let reloaded = reload_chunk(world, &path).unwrap();
+        let mut credits = Vec::new();
+        for entity in reloaded.into_values() {
+            credits.push(world.get::<Credits>(entity).unwrap().get());
+            if let Some(target) = world.get::<Target>(entity) {
+                // The hunter still targets the player, not the entity stored with the same ID
+                assert_eq!(target.0, live);
+            }
+        }
+        credits.sort();
+        assert_eq!(credits, [2, 5]);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -8,19 +8,39 @@ pub enum Action {
     Turn,
     /// Thrust forwards and brake when negative
     Thrust,
-    /// Fire
+    /// Burn the afterburners of the engines while thrusting
+    Afterburner,
+    /// Fire the primary weapon group
     Fire,
+    /// Fire the secondary weapon group
+    FireSecondary,
     /// Take
     Take,
     /// Interact
     Interact,
+    /// Move a pip of power to the engines
+    PowerEngines,
+    /// Move a pip of power to the shields
+    PowerShields,
+    /// Move a pip of power to the weapons
+    PowerWeapons,
+    /// Share power evenly between every system
+    PowerBalance,
 }
 
 impl Actionlike for Action {
     fn input_control_kind(&self) -> InputControlKind {
         match self {
             Action::Turn | Action::Thrust => InputControlKind::Axis,
-            Action::Fire | Action::Interact | Action::Take => InputControlKind::Button,
+            Action::Afterburner
+            | Action::Fire
+            | Action::FireSecondary
+            | Action::Interact
+            | Action::Take
+            | Action::PowerEngines
+            | Action::PowerShields
+            | Action::PowerWeapons
+            | Action::PowerBalance => InputControlKind::Button,
         }
     }
 }
@@ -30,4 +50,8 @@ impl Actionlike for Action {
 pub enum AppAction {
     /// Show debug tools
     Console,
+    /// Save to the quicksave slot
+    Quicksave,
+    /// Load the quicksave slot
+    Quickload,
 }
@@ -27,6 +27,12 @@ pub struct Library {
     /// All [`Building`]s
     #[asset(key = "buildings", collection(typed, mapped))]
     pub buildings: HashMap<String, Handle<Building>>,
+    /// All [`Biome`]s
+    #[asset(key = "biomes", collection(typed, mapped))]
+    pub biomes: HashMap<String, Handle<Biome>>,
+    /// All [`Recipe`]s
+    #[asset(key = "recipes", collection(typed, mapped))]
+    pub recipes: HashMap<String, Handle<Recipe>>,
     /// All GLTF scenes as models
     #[asset(key = "models", collection(typed, mapped))]
     pub models: HashMap<String, Handle<Scene>>,
@@ -97,6 +103,96 @@ pub struct Building {
     /// Starting credits, if any
     #[serde(default)]
     pub credits: Option<usize>,
+    /// Model of the building, if any
+    #[serde(default)]
+    pub model: Option<String>,
+    /// If a fabricator, the [`Recipe`]s that use this building can be fabricated when docked
+    #[serde(default)]
+    pub fabricator: Option<Fabricator>,
+    /// Explosion when the building is destroyed, if any
+    #[serde(default)]
+    pub explosion: Option<Explosive>,
+}
+
+/// Turns items into other items at a [`Building`] with a [`Fabricator`]
+#[derive(Debug, Clone, Reflect, Asset, Serialize, Deserialize)]
+pub struct Recipe {
+    /// Recipe name string
+    pub name: String,
+    /// Items consumed from the inventory of the patron, paired with their amount
+    pub inputs: Vec<(String, usize)>,
+    /// Items delivered into the inventory of the patron once finished, paired with their amount
+    pub outputs: Vec<(String, usize)>,
+    /// Seconds until the recipe is finished
+    pub time: f32,
+    /// Credits paid to the fabricator
+    #[serde(default)]
+    pub cost: usize,
+    /// Name of the building that can fabricate this recipe
+    pub building: String,
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+/// Biomes describe what is generated in a chunk. Each chunk picks one biome from those allowed at
+/// its distance from the origin.
+#[derive(Debug, Clone, Reflect, Asset, Serialize, Deserialize)]
+pub struct Biome {
+    /// Biome name string
+    pub name: String,
+    /// Chunk distance from the origin where this biome can be picked, from a minimum to an
+    /// optional maximum
+    pub distance: (i32, Option<i32>),
+    /// Likelihood of picking this biome over other biomes allowed at the same distance
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+    /// Danger level, which is added to the maximum of every spawner in this biome
+    #[serde(default)]
+    pub danger: usize,
+    /// Where buildings are placed within the chunk
+    #[serde(default)]
+    pub placement: Placement,
+    /// Sets of buildings generated in this biome
+    pub buildings: Vec<BuildingSet>,
+}
+
+/// Buildings generated together in a [`Biome`]
+#[derive(Debug, Clone, Reflect, Serialize, Deserialize)]
+pub struct BuildingSet {
+    /// Names of the building assets that can be picked, paired with their weight
+    pub buildings: Vec<(String, u32)>,
+    /// Range of how many buildings are generated, from a minimum to maximum
+    pub count: (usize, usize),
+    /// Faction that owns these buildings
+    pub faction: String,
+    /// Factions these buildings are hostile to
+    #[serde(default)]
+    pub enemies: Vec<String>,
+}
+
+/// Placement rules for the buildings of a [`Biome`]
+#[derive(Debug, Clone, Reflect, Serialize, Deserialize)]
+pub struct Placement {
+    /// Buildings are placed within this distance of the chunk center
+    pub radius: f32,
+    /// Minimum distance between any two buildings. Buildings without room are not generated.
+    #[serde(default)]
+    pub min_spacing: f32,
+    /// If set, the buildings of each set are clustered within this distance of a random point
+    #[serde(default)]
+    pub cluster: Option<f32>,
+}
+
+impl Default for Placement {
+    fn default() -> Self {
+        Self {
+            radius: 10f32,
+            min_spacing: 0f32,
+            cluster: None,
+        }
+    }
 }
 
 /// Background material
@@ -113,27 +209,6 @@ impl Material for BackgroundMaterial {
     }
 }
 
-pub struct Zone {
-    /// The name identifier of this zone
-    pub name: String,
-    /// The depth of this `Zone` in the [`Universe`]. This can help dictate difficulty
-    pub depth: usize,
-    pub scene: Option<Handle<DynamicScene>>,
-}
-
-impl Zone {
-    pub fn from_depth(depth: usize) -> Self {
-        Self {
-            name: (0..2)
-                .map(|_| random_word::gen(random_word::Lang::En).to_string())
-                .reduce(|acc, e| acc + " " + &e)
-                .unwrap(),
-            depth,
-            scene: None,
-        }
-    }
-}
-
 impl Library {
     pub fn creature(&self, name: impl AsRef<str>) -> Option<Handle<Creature>> {
         self.creatures
@@ -153,6 +228,18 @@ impl Library {
             .cloned()
     }
 
+    pub fn biome(&self, name: impl AsRef<str>) -> Option<Handle<Biome>> {
+        self.biomes
+            .get(&format!("biomes/{}.biome.ron", name.as_ref()))
+            .cloned()
+    }
+
+    pub fn recipe(&self, name: impl AsRef<str>) -> Option<Handle<Recipe>> {
+        self.recipes
+            .get(&format!("recipes/{}.recipe.ron", name.as_ref()))
+            .cloned()
+    }
+
     pub fn item(&self, name: impl AsRef<str>) -> Option<Handle<Item>> {
         self.items
             .get(&format!("items/{}.ron", name.as_ref()))
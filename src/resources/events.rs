@@ -47,8 +47,45 @@ pub enum StoreEvent {
     },
 }
 
+/// Items that stayed in an inventory after taking as much as fits from it
+#[derive(Event)]
+pub struct LeftBehind {
+    /// Inventory the items were taken from
+    pub from: Entity,
+    /// Inventory the items were taken into
+    pub to: Entity,
+    /// Every stack still in the `from` inventory
+    pub stacks: Vec<ItemStack>,
+}
+
 /// Save the universe
 #[derive(Event, Clone, Copy)]
 pub struct Save {
     pub node: NodeIndex,
 }
+
+/// Damage dealt to an entity. Shields and armor of the target reduce it before it is added to the
+/// [`Damage`] of the target.
+#[derive(Event, Clone, Copy)]
+pub struct Hit {
+    /// Entity that is hit
+    pub target: Entity,
+    /// Damage before it is reduced
+    pub amount: f32,
+    /// Type of the damage
+    pub damage_type: DamageType,
+}
+
+/// An explosion that damages and pushes away everything within its blast. Explosions inflict
+/// [`DamageType::Explosive`] damage.
+#[derive(Event, Clone)]
+pub struct Explosion {
+    /// Center of the explosion
+    pub translation: Vec3,
+    /// Damage at the center of the explosion
+    pub damage: f32,
+    /// Shape of the explosion
+    pub blast: Blast,
+    /// Only enemies of this alliegance are harmed, or everything when `None`
+    pub alliegance: Option<Alliegance>,
+}
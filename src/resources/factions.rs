@@ -19,10 +19,11 @@ impl Factions {
             None => self.register(name.as_ref()),
         }
     }
-    /// Register a name and return a given ID that will represent that faction. If a faction with this name already exists,
-    /// it will be overwritten and replaced with a new ID
+    /// Register a name and return a given ID that will represent that faction. The ID is derived from the name, so the
+    /// same name is always given the same ID
     pub fn register(&mut self, name: impl Into<String>) -> Faction {
-        let faction = Faction::new();
+        let name = name.into();
+        let faction = Faction::from_name(&name);
         self.insert(name, faction);
         faction
     }
@@ -6,11 +6,19 @@ mod assets;
 pub mod events;
 /// Factions resource
 mod factions;
+/// Versioned save files
+mod save;
+/// World generation seed
+mod seed;
 /// Game settings
 pub mod settings;
 /// Triggers
 pub mod triggers;
+/// Zones and the gates between them
+mod universe;
 /// Utility resources
 mod util;
 
-pub use {action::*, assets::*, factions::*, settings::Settings, util::*};
+pub use {
+    action::*, assets::*, factions::*, save::*, seed::*, settings::Settings, universe::*, util::*,
+};
@@ -0,0 +1,447 @@
+use std::{
+    ffi::OsString,
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use bevy::math::IVec2;
+use petgraph::graph::NodeIndex;
+
+use crate::prelude::*;
+
+/// Suffix of every regular save
+const SAVE_SUFFIX: &str = ".save.ron";
+/// Suffix of the quicksave slot that belongs to a save
+const QUICKSAVE_SUFFIX: &str = ".quicksave.ron";
+
+/// Write a save without ever leaving a partially written file at `path`. The data is first
+/// written to a temporary file which is then renamed over the save. Up to `backups` older
+/// copies are kept as `<path>.1` (newest) through `<path>.<backups>` (oldest).
+pub fn write_save(path: &Path, contents: &str, backups: usize) -> io::Result<()> {
+    let temp_path = with_suffix(path, "tmp");
+    {
+        let mut file = File::create(&temp_path)?;
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()?;
+    }
+
+    if backups > 0 && path.exists() {
+        // Shift older backups along, dropping the oldest
+        for n in (1..backups).rev() {
+            let from = backup_path(path, n);
+            if from.exists() {
+                fs::rename(from, backup_path(path, n + 1))?;
+            }
+        }
+        fs::rename(path, backup_path(path, 1))?;
+    }
+
+    fs::rename(temp_path, path)
+}
+
+/// A save found in the save directory
+#[derive(Debug)]
+pub struct SaveListing {
+    /// Path of the save
+    pub path: PathBuf,
+    /// Header of the save, or the error encountered while reading it
+    pub header: Result<SaveHeader, SaveError>,
+    /// Seconds since the Unix epoch when the save was last played
+    pub last_played: u64,
+}
+
+impl SaveListing {
+    /// Read the header of a save
+    pub fn read(path: PathBuf) -> Self {
+        let header = fs::read_to_string(&path)
+            .map_err(SaveError::from)
+            .and_then(|text| SaveFile::read_header(&text));
+        // Saves without metadata fall back to when the file was last written
+        let last_played = match &header {
+            Ok(header) if header.metadata.timestamp != 0 => header.metadata.timestamp,
+            _ => fs::metadata(&path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                .map(|since| since.as_secs())
+                .unwrap_or_default(),
+        };
+        Self {
+            path,
+            header,
+            last_played,
+        }
+    }
+
+    /// File name of the save, without its suffix
+    pub fn name(&self) -> String {
+        save_name(&self.path)
+    }
+}
+
+/// List every save in a directory, most recently played first. A missing directory has no saves.
+/// Quicksaves are not listed, they belong to their save.
+pub fn list_saves(dir: &Path) -> Result<Vec<SaveListing>, SaveError> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut saves = entries
+        .map(|entry| entry.map(|entry| entry.path()))
+        .filter(|path| {
+            path.as_ref().map_or(true, |path| {
+                path.is_file()
+                    && path.extension().is_some_and(|ext| ext == "ron")
+                    && !path.to_string_lossy().ends_with(QUICKSAVE_SUFFIX)
+            })
+        })
+        .map(|path| path.map(SaveListing::read))
+        .collect::<Result<Vec<_>, _>>()?;
+    saves.sort_by(|a, b| b.last_played.cmp(&a.last_played));
+    Ok(saves)
+}
+
+/// Delete a save along with its quicksave, their backups and their unloaded chunks
+pub fn delete_save(path: &Path) -> Result<(), SaveError> {
+    fs::remove_file(path)?;
+    let quicksave = quicksave_path(path);
+    if quicksave.exists() {
+        fs::remove_file(&quicksave)?;
+    }
+    for backup in backups(path).into_iter().chain(backups(&quicksave)) {
+        fs::remove_file(backup)?;
+    }
+    for dir in [
+        chunk_dir(path),
+        chunk_dir(&quicksave),
+        staged_chunk_dir(path),
+    ] {
+        if dir.exists() {
+            fs::remove_dir_all(dir)?;
+        }
+    }
+    Ok(())
+}
+
+/// Rename a save along with its quicksave and their backups, returning the new path of the save
+pub fn rename_save(path: &Path, name: &str) -> Result<PathBuf, SaveError> {
+    let name = name.trim();
+    if name.is_empty() || name.contains(['/', '\\']) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid save name").into());
+    }
+    let new_path = path.with_file_name(format!("{name}{SAVE_SUFFIX}"));
+    if new_path.exists() || quicksave_path(&new_path).exists() {
+        return Err(
+            io::Error::new(io::ErrorKind::AlreadyExists, "a save with that name exists").into(),
+        );
+    }
+    fs::rename(path, &new_path)?;
+    let quicksave = quicksave_path(path);
+    if quicksave.exists() {
+        fs::rename(&quicksave, quicksave_path(&new_path))?;
+    }
+    for (from, to) in [
+        (path.to_path_buf(), new_path.clone()),
+        (quicksave, quicksave_path(&new_path)),
+    ] {
+        for (n, backup) in backups(&from).into_iter().enumerate() {
+            fs::rename(backup, backup_path(&to, n + 1))?;
+        }
+        if chunk_dir(&from).exists() {
+            fs::rename(chunk_dir(&from), chunk_dir(&to))?;
+        }
+    }
+    Ok(new_path)
+}
+
+/// Copy a save and its quicksave to a new, unused name, returning the path of the copy
+pub fn duplicate_save(path: &Path) -> Result<PathBuf, SaveError> {
+    let name = save_name(path);
+    let new_path = (1..)
+        .map(|n| path.with_file_name(format!("{name}-copy{n}{SAVE_SUFFIX}")))
+        .find(|new_path| !new_path.exists() && !quicksave_path(new_path).exists())
+        .unwrap();
+    fs::copy(path, &new_path)?;
+    let quicksave = quicksave_path(path);
+    if quicksave.exists() {
+        fs::copy(&quicksave, quicksave_path(&new_path))?;
+    }
+    for (from, to) in [
+        (path.to_path_buf(), new_path.clone()),
+        (quicksave, quicksave_path(&new_path)),
+    ] {
+        if chunk_dir(&from).exists() {
+            copy_dir(&chunk_dir(&from), &chunk_dir(&to))?;
+        }
+    }
+    Ok(new_path)
+}
+
+/// Copy a directory and everything within it
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        if entry.path().is_dir() {
+            copy_dir(&entry.path(), &to.join(entry.file_name()))?;
+        } else {
+            fs::copy(entry.path(), to.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Directory holding the unloaded chunks of a save as they were when the save was written.
+/// Quicksaves have their own chunks, separate from those of their save.
+pub fn chunk_dir(path: &Path) -> PathBuf {
+    with_suffix(path, "chunks")
+}
+
+/// Directory holding the unloaded chunks of the game being played from a save. Chunks are only
+/// copied into the [`chunk_dir`] of a save once it is written.
+pub fn staged_chunk_dir(path: &Path) -> PathBuf {
+    with_suffix(&primary_save_path(path), "chunks.staged")
+}
+
+/// Path of an unloaded chunk in a zone within a chunk directory
+pub fn chunk_path(dir: &Path, zone: NodeIndex, chunk_index: ChunkIndex) -> PathBuf {
+    let IVec2 { x, y } = chunk_index.into();
+    dir.join(zone.index().to_string())
+        .join(format!("{x}_{y}.chunk.ron"))
+}
+
+/// Start playing from the chunks of a save by replacing the staged chunks with them. Quicksaves
+/// written before they had their own chunks use the chunks of their save.
+pub fn stage_chunks(path: &Path) -> io::Result<()> {
+    let staged = staged_chunk_dir(path);
+    if staged.exists() {
+        fs::remove_dir_all(&staged)?;
+    }
+    let from = Some(chunk_dir(path))
+        .filter(|dir| dir.exists())
+        .unwrap_or_else(|| chunk_dir(&primary_save_path(path)));
+    if from.exists() {
+        copy_dir(&from, &staged)?;
+    } else {
+        fs::create_dir_all(&staged)?;
+    }
+    Ok(())
+}
+
+/// Copy the staged chunks next to a save that is about to be written. They only replace the
+/// chunks of the save once [`commit_chunks`] is called after the save was written.
+pub fn snapshot_chunks(path: &Path) -> io::Result<()> {
+    let snapshot = with_suffix(&chunk_dir(path), "tmp");
+    if snapshot.exists() {
+        fs::remove_dir_all(&snapshot)?;
+    }
+    let staged = staged_chunk_dir(path);
+    if staged.exists() {
+        copy_dir(&staged, &snapshot)
+    } else {
+        fs::create_dir_all(&snapshot)
+    }
+}
+
+/// Replace the chunks of a save with the snapshot taken by [`snapshot_chunks`]
+pub fn commit_chunks(path: &Path) -> io::Result<()> {
+    let dir = chunk_dir(path);
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+    fs::rename(with_suffix(&dir, "tmp"), dir)
+}
+
+/// Saves written before zones existed kept their chunks directly in the chunk directory. Move
+/// them into the directory of the starting zone.
+pub fn move_legacy_chunks(path: &Path) -> Result<(), SaveError> {
+    let dir = chunk_dir(path);
+    if !dir.exists() {
+        return Ok(());
+    }
+    for entry in fs::read_dir(&dir)? {
+        let entry = entry?;
+        if entry.path().is_file() {
+            let to = chunk_path(&dir, NodeIndex::new(0), (0, 0).into())
+                .with_file_name(entry.file_name());
+            fs::create_dir_all(to.parent().unwrap())?;
+            fs::rename(entry.path(), to)?;
+        }
+    }
+    Ok(())
+}
+
+/// Name of a save, which is its file name without the save suffix
+pub fn save_name(path: &Path) -> String {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    name.strip_suffix(SAVE_SUFFIX)
+        .or_else(|| name.strip_suffix(".ron"))
+        .unwrap_or(&name)
+        .to_string()
+}
+
+/// Every backup of a save that exists, newest first
+fn backups(path: &Path) -> Vec<PathBuf> {
+    (1..)
+        .map(|n| backup_path(path, n))
+        .take_while(|backup| backup.exists())
+        .collect()
+}
+
+/// Path of the `n`th most recent backup of a save
+pub fn backup_path(path: &Path, n: usize) -> PathBuf {
+    with_suffix(path, &n.to_string())
+}
+
+/// Path of the quicksave slot for a save
+pub fn quicksave_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let stem = name.strip_suffix(SAVE_SUFFIX).unwrap_or(&name);
+    path.with_file_name(format!("{stem}{QUICKSAVE_SUFFIX}"))
+}
+
+/// Path of the save that a quicksave slot belongs to. Regular saves are returned unchanged.
+pub fn primary_save_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    match name.strip_suffix(QUICKSAVE_SUFFIX) {
+        Some(stem) => path.with_file_name(format!("{stem}{SAVE_SUFFIX}")),
+        None => path.to_path_buf(),
+    }
+}
+
+/// Append `.<suffix>` to a path
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = OsString::from(path);
+    path.push(".");
+    path.push(suffix);
+    path.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_save_rotates_backups() {
+        let dir = std::env::temp_dir().join(format!("qz-test-rotation-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.save.ron");
+
+        for n in 0..5 {
+            write_save(&path, &n.to_string(), 2).unwrap();
+        }
+
+        let read = |path: PathBuf| fs::read_to_string(path).unwrap();
+        assert_eq!(read(path.clone()), "4");
+        assert_eq!(read(backup_path(&path, 1)), "3");
+        assert_eq!(read(backup_path(&path, 2)), "2");
+        assert!(!backup_path(&path, 3).exists());
+        assert!(!with_suffix(&path, "tmp").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_manage_saves() {
+        let dir = std::env::temp_dir().join(format!("qz-test-manage-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("first.save.ron");
+        write_save(&path, &SaveFile::default().to_ron().unwrap(), 1).unwrap();
+        write_save(&path, &SaveFile::default().to_ron().unwrap(), 1).unwrap();
+        fs::write(dir.join("corrupt.save.ron"), "not a save").unwrap();
+
+        let copy = duplicate_save(&path).unwrap();
+        assert_eq!(save_name(&copy), "first-copy1");
+        let renamed = rename_save(&path, "second").unwrap();
+        assert!(!path.exists() && !backup_path(&path, 1).exists());
+        assert!(backup_path(&renamed, 1).exists());
+        assert!(rename_save(&renamed, "first-copy1").is_err());
+
+        let saves = list_saves(&dir).unwrap();
+        assert_eq!(saves.len(), 3);
+        let corrupt = saves.iter().find(|save| save.name() == "corrupt").unwrap();
+        assert!(corrupt.header.is_err());
+
+        let chunk = chunk_path(&chunk_dir(&renamed), NodeIndex::new(1), (2, -3).into());
+        fs::create_dir_all(chunk.parent().unwrap()).unwrap();
+        fs::write(&chunk, "").unwrap();
+        let copy = duplicate_save(&renamed).unwrap();
+        assert!(chunk_path(&chunk_dir(&copy), NodeIndex::new(1), (2, -3).into()).exists());
+
+        delete_save(&renamed).unwrap();
+        assert!(!renamed.exists() && !backup_path(&renamed, 1).exists());
+        assert!(list_saves(&dir.join("missing")).unwrap().is_empty());
+
+        // Quicksaves are not listed on their own, and follow their save around
+        let quicksave = quicksave_path(&copy);
+        write_save(&quicksave, &SaveFile::default().to_ron().unwrap(), 0).unwrap();
+        fs::create_dir_all(chunk_dir(&quicksave)).unwrap();
+        let saves = list_saves(&dir).unwrap();
+        assert_eq!(saves.len(), 3);
+        assert!(saves.iter().all(|save| save.path != quicksave));
+        let duplicate = duplicate_save(&copy).unwrap();
+        assert!(quicksave_path(&duplicate).exists());
+        assert!(chunk_dir(&quicksave_path(&duplicate)).exists());
+        let renamed = rename_save(&copy, "third").unwrap();
+        assert!(!quicksave.exists() && !chunk_dir(&quicksave).exists());
+        assert!(quicksave_path(&renamed).exists());
+        assert!(chunk_dir(&quicksave_path(&renamed)).exists());
+        delete_save(&renamed).unwrap();
+        assert!(!quicksave_path(&renamed).exists());
+        assert!(!chunk_dir(&quicksave_path(&renamed)).exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_chunks_are_kept_per_save() {
+        let dir = std::env::temp_dir().join(format!("qz-test-staging-{}", std::process::id()));
+        let path = dir.join("test.save.ron");
+        let quicksave = quicksave_path(&path);
+        let staged = |name: &str| {
+            chunk_path(&staged_chunk_dir(&path), NodeIndex::new(0), (0, 0).into())
+                .with_file_name(name)
+        };
+        let write = |path: PathBuf| {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        };
+        let committed = |save: &Path, name: &str| {
+            chunk_path(&chunk_dir(save), NodeIndex::new(0), (0, 0).into())
+                .with_file_name(name)
+                .exists()
+        };
+
+        // Chunks unloaded before the first save belong to it once it is written
+        write(staged("a"));
+        snapshot_chunks(&path).unwrap();
+        write(staged("b"));
+        commit_chunks(&path).unwrap();
+        assert!(committed(&path, "a") && !committed(&path, "b"));
+
+        // A quicksave does not change the chunks of its save
+        snapshot_chunks(&quicksave).unwrap();
+        commit_chunks(&quicksave).unwrap();
+        assert!(committed(&quicksave, "b") && !committed(&path, "b"));
+
+        // Loading the save again drops chunks unloaded since it was written
+        stage_chunks(&path).unwrap();
+        assert!(staged("a").exists() && !staged("b").exists());
+        stage_chunks(&quicksave).unwrap();
+        assert!(staged("b").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_quicksave_path() {
+        let path = Path::new("saves/red-fox.save.ron");
+        let quicksave = quicksave_path(path);
+        assert_eq!(quicksave, Path::new("saves/red-fox.quicksave.ron"));
+        assert_eq!(primary_save_path(&quicksave), path);
+        assert_eq!(primary_save_path(path), path);
+    }
+}
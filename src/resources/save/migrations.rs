@@ -0,0 +1,126 @@
+use std::collections::BTreeMap;
+
+use bevy::{prelude::*, reflect::TypePath};
+
+use super::{SaveFile, SAVE_FORMAT_VERSION};
+use crate::prelude::*;
+
+/// Upgrades a [`SaveFile`] by exactly one format version. The header version is incremented
+/// after the migration succeeds, so migrations should not modify it themselves.
+pub type SaveMigration = fn(&mut SaveFile) -> Result<(), SaveError>;
+
+/// Registry of [`SaveMigration`]s, keyed by the version they upgrade from
+#[derive(Resource, Clone)]
+pub struct SaveMigrations(BTreeMap<u32, SaveMigration>);
+
+impl Default for SaveMigrations {
+    /// All migrations shipped with the game
+    fn default() -> Self {
+        Self::empty()
+            .with(1, remove_render_components)
+            .with(2, add_generated_chunks)
+            .with(3, add_universe)
+    }
+}
+
+impl SaveMigrations {
+    /// Create a registry without any migrations
+    pub fn empty() -> Self {
+        Self(BTreeMap::new())
+    }
+
+    /// Register a migration that upgrades saves from the `from` version to the next version
+    pub fn with(mut self, from: u32, migration: SaveMigration) -> Self {
+        self.insert(from, migration);
+        self
+    }
+
+    /// Register a migration that upgrades saves from the `from` version to the next version,
+    /// replacing any existing migration for that version
+    pub fn insert(&mut self, from: u32, migration: SaveMigration) {
+        self.0.insert(from, migration);
+    }
+
+    /// Run every migration needed to bring a save up to [`SAVE_FORMAT_VERSION`]
+    pub fn migrate(&self, save: &mut SaveFile) -> Result<(), SaveError> {
+        while save.header.version < SAVE_FORMAT_VERSION {
+            let version = save.header.version;
+            let migration = self
+                .0
+                .get(&version)
+                .ok_or(SaveError::Unmigratable { version })?;
+            migration(save)?;
+            save.header.version = version + 1;
+        }
+        Ok(())
+    }
+}
+
+/// Version 1 saves wrote render-only components that are rebuilt when the scene is spawned
+fn remove_render_components(save: &mut SaveFile) -> Result<(), SaveError> {
+    for type_path in [
+        "bevy_render::primitives::CascadesFrusta",
+        "bevy_pbr::light::Cascades",
+        "bevy_pbr::bundle::CascadesVisibleEntities",
+        "bevy_render::view::visibility::InheritedVisibility",
+        "bevy_render::view::visibility::ViewVisibility",
+    ] {
+        save.remove_component(type_path);
+    }
+    Ok(())
+}
+
+/// Version 2 saves did not record which chunks were generated, but every one of them generated the
+/// same cross of chunks when the game was created
+fn add_generated_chunks(save: &mut SaveFile) -> Result<(), SaveError> {
+    let mut chunks = Chunks::default();
+    for chunk_index in [(0, 0), (0, 1), (1, 0), (-1, 0)] {
+        chunks.insert(chunk_index.into());
+    }
+    save.resources
+        .insert(Chunks::type_path().to_string(), ron::to_string(&chunks)?);
+    Ok(())
+}
+
+/// Version 3 saves had a single zone, which is the starting zone of a universe
+fn add_universe(save: &mut SaveFile) -> Result<(), SaveError> {
+    save.resources.insert(
+        Universe::type_path().to_string(),
+        ron::to_string(&Universe::default())?,
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remove_render_components() {
+        let mut save = SaveFile {
+            header: SaveHeader {
+                version: 1,
+                ..default()
+            },
+            ..default()
+        };
+        save.entities.insert(
+            0,
+            [
+                ("bevy_render::primitives::CascadesFrusta", "()"),
+                ("bevy_render::view::visibility::ViewVisibility", "(false)"),
+                ("bevy_template::components::credits::Credits", "(20)"),
+            ]
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .into(),
+        );
+        SaveMigrations::default().migrate(&mut save).unwrap();
+        assert_eq!(save.header.version, SAVE_FORMAT_VERSION);
+        assert!(save.resources.contains_key(Chunks::type_path()));
+        assert!(save.resources.contains_key(Universe::type_path()));
+        assert_eq!(
+            save.entities[&0].keys().collect::<Vec<_>>(),
+            vec!["bevy_template::components::credits::Credits"]
+        );
+    }
+}
@@ -0,0 +1,370 @@
+//! Versioned save files
+//!
+//! A save is a header followed by every saved resource and entity. Each component is stored as
+//! its own RON string keyed by type path, so that a [`SaveMigration`] can rename, rewrite or drop
+//! individual components of an older save before any of it reaches the type registry.
+
+/// Writing saves to disk
+mod disk;
+/// Migrations that upgrade older saves
+mod migrations;
+/// Registration of saved components
+mod registry;
+
+use std::{collections::BTreeMap, time::Duration};
+
+use bevy::{
+    prelude::*,
+    reflect::{
+        serde::{TypedReflectDeserializer, TypedReflectSerializer},
+        TypeRegistry,
+    },
+    scene::{serde::SceneDeserializer, DynamicEntity},
+};
+use ron::ser::PrettyConfig;
+use serde::{de::DeserializeSeed, Deserialize, Serialize};
+
+use crate::prelude::*;
+
+pub use {disk::*, migrations::*, registry::*};
+
+/// The save format version written by this build. Bump this and register a [`SaveMigration`]
+/// whenever a saved component changes shape.
+pub const SAVE_FORMAT_VERSION: u32 = 4;
+
+/// Describes the save that follows it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SaveHeader {
+    /// Format version of this save
+    pub version: u32,
+    /// Summary of the save. Saves written before metadata existed have an empty summary.
+    #[serde(default)]
+    pub metadata: SaveMetadata,
+}
+
+impl Default for SaveHeader {
+    fn default() -> Self {
+        Self {
+            version: SAVE_FORMAT_VERSION,
+            metadata: SaveMetadata::default(),
+        }
+    }
+}
+
+/// Summary of a save, shown in the save browser without loading the save
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SaveMetadata {
+    /// Name of the player
+    pub player_name: String,
+    /// Credits held by the player
+    pub credits: usize,
+    /// Total time spent playing this save
+    pub play_time: Duration,
+    /// Number of chunks generated in the world
+    pub chunks: usize,
+    /// Seconds since the Unix epoch when the save was written
+    pub timestamp: u64,
+    /// Version of the game that wrote the save
+    pub game_version: String,
+}
+
+/// A save file on disk
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SaveFile {
+    /// Save header
+    pub header: SaveHeader,
+    /// Resources, mapped from type path to their RON representation
+    pub resources: BTreeMap<String, String>,
+    /// Entities by their saved ID, each mapping a component type path to its RON representation
+    pub entities: BTreeMap<u64, BTreeMap<String, String>>,
+}
+
+impl SaveFile {
+    /// Read a save of any supported version, upgrading it to [`SAVE_FORMAT_VERSION`]
+    pub fn from_ron(
+        text: &str,
+        migrations: &SaveMigrations,
+        registry: &TypeRegistry,
+    ) -> Result<Self, SaveError> {
+        let version = Self::read_version(text)?;
+        if version > SAVE_FORMAT_VERSION {
+            return Err(SaveError::TooNew {
+                version,
+                supported: SAVE_FORMAT_VERSION,
+            });
+        }
+        let mut save = match version {
+            // Saves without a header are bare scenes
+            0 => Self::from_legacy_scene(text, registry)?,
+            _ => ron::from_str::<Self>(text)?,
+        };
+        migrations.migrate(&mut save)?;
+        Ok(save)
+    }
+
+    /// Write this save as pretty RON
+    pub fn to_ron(&self) -> Result<String, SaveError> {
+        Ok(ron::ser::to_string_pretty(self, PrettyConfig::default())?)
+    }
+
+    /// Obtain only the format version of a save, without reading the rest of it
+    pub fn read_version(text: &str) -> Result<u32, SaveError> {
+        /// Only the parts of the header that every version shares
+        #[derive(Deserialize)]
+        struct Version {
+            version: u32,
+        }
+        /// Every field other than the header is skipped
+        #[derive(Deserialize)]
+        struct Probe {
+            #[serde(default)]
+            header: Option<Version>,
+        }
+        let probe = ron::from_str::<Probe>(text)?;
+        Ok(probe.header.map(|h| h.version).unwrap_or_default())
+    }
+
+    /// Obtain only the header of a save, without reading the rest of it. Headerless saves have
+    /// a default header with a version of `0`.
+    pub fn read_header(text: &str) -> Result<SaveHeader, SaveError> {
+        /// Every field other than the header is skipped
+        #[derive(Deserialize)]
+        struct Probe {
+            #[serde(default)]
+            header: Option<SaveHeader>,
+        }
+        let probe = ron::from_str::<Probe>(text)?;
+        Ok(probe.header.unwrap_or(SaveHeader {
+            version: 0,
+            ..default()
+        }))
+    }
+
+    /// Convert a headerless save, which is a plain serialized [`DynamicScene`]. All of its types
+    /// must still be registered.
+    pub fn from_legacy_scene(text: &str, registry: &TypeRegistry) -> Result<Self, SaveError> {
+        let mut deserializer = ron::Deserializer::from_str(text)?;
+        let scene = SceneDeserializer {
+            type_registry: registry,
+        }
+        .deserialize(&mut deserializer)?;
+        let mut save = Self::from_scene(&scene, registry)?;
+        // The legacy layout is identical to the first versioned layout
+        save.header.version = 1;
+        Ok(save)
+    }
+
+    /// Serialize each resource and component of a scene
+    pub fn from_scene(scene: &DynamicScene, registry: &TypeRegistry) -> Result<Self, SaveError> {
+        let serialize = |value: &dyn Reflect| -> Result<(String, String), SaveError> {
+            let type_path = value
+                .get_represented_type_info()
+                .map(|info| info.type_path())
+                .unwrap_or_else(|| value.reflect_type_path())
+                .to_string();
+            let ron = ron::to_string(&TypedReflectSerializer::new(value, registry))?;
+            Ok((type_path, ron))
+        };
+
+        Ok(Self {
+            header: SaveHeader::default(),
+            resources: scene
+                .resources
+                .iter()
+                .map(|resource| serialize(resource.as_ref()))
+                .collect::<Result<_, _>>()?,
+            entities: scene
+                .entities
+                .iter()
+                .map(|entity| {
+                    let components = entity
+                        .components
+                        .iter()
+                        .map(|component| serialize(component.as_ref()))
+                        .collect::<Result<_, _>>()?;
+                    Ok((entity.entity.to_bits(), components))
+                })
+                .collect::<Result<_, SaveError>>()?,
+        })
+    }
+
+    /// Deserialize this save into a scene that can be spawned
+    pub fn into_scene(self, registry: &TypeRegistry) -> Result<DynamicScene, SaveError> {
+        let deserialize = |type_path: &str, ron: &str| -> Result<Box<dyn Reflect>, SaveError> {
+            let registration = registry
+                .get_with_type_path(type_path)
+                .ok_or_else(|| SaveError::UnregisteredType(type_path.to_string()))?;
+            let mut deserializer = ron::Deserializer::from_str(ron)?;
+            Ok(TypedReflectDeserializer::new(registration, registry)
+                .deserialize(&mut deserializer)?)
+        };
+
+        Ok(DynamicScene {
+            resources: self
+                .resources
+                .iter()
+                .map(|(type_path, ron)| deserialize(type_path, ron))
+                .collect::<Result<_, _>>()?,
+            entities: self
+                .entities
+                .iter()
+                .map(|(bits, components)| {
+                    Ok(DynamicEntity {
+                        entity: Entity::from_bits(*bits),
+                        components: components
+                            .iter()
+                            .map(|(type_path, ron)| deserialize(type_path, ron))
+                            .collect::<Result<_, _>>()?,
+                    })
+                })
+                .collect::<Result<_, SaveError>>()?,
+        })
+    }
+
+    /// Iterate through every saved component with the given type path
+    pub fn components_mut<'a>(
+        &'a mut self,
+        type_path: &'a str,
+    ) -> impl Iterator<Item = &'a mut String> + 'a {
+        self.entities
+            .values_mut()
+            .filter_map(move |components| components.get_mut(type_path))
+    }
+
+    /// Rename a component type path on every entity, such as when a type is moved or renamed
+    pub fn rename_component(&mut self, from: &str, to: impl Into<String>) {
+        let to = to.into();
+        for components in self.entities.values_mut() {
+            if let Some(ron) = components.remove(from) {
+                components.insert(to.clone(), ron);
+            }
+        }
+    }
+
+    /// Remove a component type path from every entity
+    pub fn remove_component(&mut self, type_path: &str) {
+        for components in self.entities.values_mut() {
+            components.remove(type_path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A headerless save as written before save versioning existed
+    const LEGACY_SAVE: &str = include_str!("fixtures/legacy.save.ron");
+
+    fn registry() -> TypeRegistry {
+        let mut registry = TypeRegistry::new();
+        registry.register::<Player>();
+        registry.register::<Persistent>();
+        registry.register::<Credits>();
+        registry.register::<InventoryBuilder>();
+        registry.register::<EquippedBuilder>();
+        registry.register::<EquipmentTypeId>();
+        registry.register::<Chunks>();
+        registry.register::<Universe>();
+        registry
+    }
+
+    /// Get the single component of type `T` saved on the player
+    fn player_component<T: serde::de::DeserializeOwned>(save: &SaveFile, type_path: &str) -> T {
+        let player = save
+            .entities
+            .values()
+            .find(|components| components.contains_key(Player::type_path()))
+            .expect("save should contain a player");
+        ron::from_str(&player[type_path]).unwrap()
+    }
+
+    #[test]
+    fn test_read_version() {
+        assert_eq!(SaveFile::read_version(LEGACY_SAVE).unwrap(), 0);
+        let save = SaveFile::default().to_ron().unwrap();
+        assert_eq!(SaveFile::read_version(&save).unwrap(), SAVE_FORMAT_VERSION);
+    }
+
+    #[test]
+    fn test_read_header() {
+        assert_eq!(SaveFile::read_header(LEGACY_SAVE).unwrap().version, 0);
+        let mut save = SaveFile::default();
+        save.header.metadata.player_name = "player".to_string();
+        save.header.metadata.credits = 20;
+        let header = SaveFile::read_header(&save.to_ron().unwrap()).unwrap();
+        assert_eq!(header, save.header);
+        assert!(SaveFile::read_header("not a save").is_err());
+    }
+
+    #[test]
+    fn test_too_new() {
+        let save = SaveFile {
+            header: SaveHeader {
+                version: SAVE_FORMAT_VERSION + 1,
+                ..default()
+            },
+            ..default()
+        };
+        let text = save.to_ron().unwrap();
+        assert!(matches!(
+            SaveFile::from_ron(&text, &SaveMigrations::default(), &registry()),
+            Err(SaveError::TooNew { .. })
+        ));
+    }
+
+    #[test]
+    fn test_unmigratable() {
+        // Headerless saves are imported as scenes, not migrated
+        let mut save = SaveFile {
+            header: SaveHeader {
+                version: 0,
+                ..default()
+            },
+            ..default()
+        };
+        assert!(matches!(
+            SaveMigrations::default().migrate(&mut save),
+            Err(SaveError::Unmigratable { version: 0 })
+        ));
+    }
+
+    #[test]
+    fn test_legacy_save_survives() {
+        let registry = registry();
+        let save = SaveFile::from_ron(LEGACY_SAVE, &SaveMigrations::default(), &registry).unwrap();
+        assert_eq!(save.header.version, SAVE_FORMAT_VERSION);
+
+        let credits: Credits = player_component(&save, Credits::type_path());
+        assert_eq!(credits.get(), 1250);
+
+        let inventory: InventoryBuilder = player_component(&save, InventoryBuilder::type_path());
+        assert_eq!(inventory.capacity, 70);
+        assert_eq!(inventory.items, vec![("scrap_metal.item".to_string(), 12)]);
+
+        let equipped: EquippedBuilder = player_component(&save, EquippedBuilder::type_path());
+        assert_eq!(
+            equipped.equipped,
+            vec!["light_laser.weapon".to_string(), "ion.battery".to_string()]
+        );
+        assert_eq!(
+            equipped.slots,
+            vec![(EquipmentTypeId::Weapon, 1), (EquipmentTypeId::Battery, 3)]
+        );
+
+        // The upgraded save can be turned back into a scene
+        let scene = save.into_scene(&registry).unwrap();
+        assert_eq!(scene.entities.len(), 1);
+        assert_eq!(scene.entities[0].components.len(), 5);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let registry = registry();
+        let legacy =
+            SaveFile::from_ron(LEGACY_SAVE, &SaveMigrations::default(), &registry).unwrap();
+        let text = legacy.to_ron().unwrap();
+        let save = SaveFile::from_ron(&text, &SaveMigrations::default(), &registry).unwrap();
+        assert_eq!(save.entities, legacy.entities);
+    }
+}
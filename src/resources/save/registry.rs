@@ -0,0 +1,191 @@
+use bevy::{
+    prelude::*,
+    reflect::{GetTypeRegistration, TypePath},
+    scene::DynamicEntity,
+};
+
+use crate::prelude::*;
+
+/// Reads the saved form of a component from a persistent entity, if the entity has it
+pub type SaveExtractor = fn(&World, Entity) -> Option<Box<dyn Reflect>>;
+
+/// Every component written into a save. Components are added with [`SaveableAppExt`].
+#[derive(Resource, Default)]
+pub struct SaveRegistry {
+    /// Type path of the saved type, paired with the function that extracts it
+    extractors: Vec<(&'static str, SaveExtractor)>,
+}
+
+impl SaveRegistry {
+    /// Register an extractor for a saved type. Registering the same type twice has no effect.
+    pub fn insert(&mut self, type_path: &'static str, extractor: SaveExtractor) {
+        if !self.contains(type_path) {
+            self.extractors.push((type_path, extractor));
+        }
+    }
+
+    /// Returns `true` if a saved type with this path is registered
+    pub fn contains(&self, type_path: &str) -> bool {
+        self.extractors.iter().any(|(path, _)| *path == type_path)
+    }
+
+    /// Extract every registered component from an entity
+    pub fn extract(&self, world: &World, entity: Entity) -> DynamicEntity {
+        DynamicEntity {
+            entity,
+            components: self
+                .extractors
+                .iter()
+                .filter_map(|(_, extract)| extract(world, entity))
+                .collect(),
+        }
+    }
+
+    /// Extract every registered component from all [`Persistent`] entities, skipping entities
+    /// that have nothing to save
+    pub fn extract_persistent(&self, world: &World) -> Vec<DynamicEntity> {
+        world
+            .iter_entities()
+            .filter(|entity| entity.contains::<Persistent>())
+            .map(|entity| self.extract(world, entity.id()))
+            .filter(|dynamic_entity| !dynamic_entity.components.is_empty())
+            .collect()
+    }
+}
+
+/// Register components that should be written into saves for any [`Persistent`] entity
+pub trait SaveableAppExt {
+    /// Save a component as-is
+    fn register_saveable<T>(&mut self) -> &mut Self
+    where
+        T: Component + Reflect + TypePath + GetTypeRegistration + Clone;
+
+    /// Save a component as its [`Builder`], which rebuilds the component when loaded
+    fn register_saveable_builder<B>(&mut self) -> &mut Self
+    where
+        B: Builder + Reflect + TypePath + GetTypeRegistration,
+        B::Output: Component + Clone;
+
+    /// Save a type `T` produced by a custom `extract` function, such as when the saved form
+    /// depends on other entities
+    fn register_saveable_with<T>(&mut self, extract: SaveExtractor) -> &mut Self
+    where
+        T: Reflect + TypePath + GetTypeRegistration;
+}
+
+impl SaveableAppExt for App {
+    fn register_saveable<T>(&mut self) -> &mut Self
+    where
+        T: Component + Reflect + TypePath + GetTypeRegistration + Clone,
+    {
+        self.register_saveable_with::<T>(|world, entity| {
+            world
+                .get::<T>(entity)
+                .map(|component| Box::new(component.clone()) as Box<dyn Reflect>)
+        })
+    }
+
+    fn register_saveable_builder<B>(&mut self) -> &mut Self
+    where
+        B: Builder + Reflect + TypePath + GetTypeRegistration,
+        B::Output: Component + Clone,
+    {
+        self.register_saveable_with::<B>(|world, entity| {
+            world
+                .get::<B::Output>(entity)
+                .cloned()
+                .map(|output| Box::new(B::from_output(output)) as Box<dyn Reflect>)
+        })
+    }
+
+    fn register_saveable_with<T>(&mut self, extract: SaveExtractor) -> &mut Self
+    where
+        T: Reflect + TypePath + GetTypeRegistration,
+    {
+        self.register_type::<T>();
+        self.world_mut()
+            .get_resource_or_insert_with(SaveRegistry::default)
+            .insert(T::type_path(), extract);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use avian3d::prelude::DistanceJoint;
+    use bevy::ecs::{entity::EntityHashMap, system::RunSystemOnce};
+
+    use super::*;
+    use crate::plugins::restore_docking_joints;
+
+    #[test]
+    fn test_entity_references_round_trip() {
+        let mut app = App::new();
+        app.register_saveable::<Persistent>()
+            .register_saveable::<Player>()
+            .register_saveable::<Docked>()
+            .register_saveable::<Dockings>()
+            .register_saveable::<SpawnedFrom>()
+            .register_saveable::<Target>()
+            .register_saveable::<Waypoint>();
+
+        // A player docked at a nest, with creatures spawned from the nest that target the player
+        let world = app.world_mut();
+        let nest = world.spawn((Persistent, Dockings::default())).id();
+        let player = world.spawn((Persistent, Player(0), Docked(nest))).id();
+        let joint = world.spawn_empty().id();
+        world
+            .get_mut::<Dockings>(nest)
+            .unwrap()
+            .insert(player, joint);
+        let creatures = [(); 2].map(|_| {
+            world
+                .spawn((
+                    Persistent,
+                    SpawnedFrom(nest),
+                    Target(player),
+                    Waypoint::Entity(nest),
+                ))
+                .id()
+        });
+
+        let type_registry = world.resource::<AppTypeRegistry>().clone();
+        let scene = DynamicScene {
+            resources: Vec::new(),
+            entities: world.resource::<SaveRegistry>().extract_persistent(world),
+        };
+        let text = SaveFile::from_scene(&scene, &type_registry.read())
+            .and_then(|save| save.to_ron())
+            .unwrap();
+        let scene = SaveFile::from_ron(&text, &SaveMigrations::default(), &type_registry.read())
+            .and_then(|save| save.into_scene(&type_registry.read()))
+            .unwrap();
+
+        // Load into a world where the saved entity IDs are already taken
+        let mut world = World::new();
+        world.insert_resource(type_registry);
+        for _ in 0..10 {
+            world.spawn_empty();
+        }
+        let mut entity_map = EntityHashMap::default();
+        scene.write_to_world(&mut world, &mut entity_map).unwrap();
+
+        let nest = entity_map[&nest];
+        let player = entity_map[&player];
+        assert_eq!(**world.get::<Docked>(player).unwrap(), nest);
+        // The joint was not saved, so it must be rebuilt
+        let dockings = world.get::<Dockings>(nest).unwrap();
+        assert!(world.get_entity(dockings[&player]).is_none());
+        world.run_system_once(restore_docking_joints);
+        let dockings = world.get::<Dockings>(nest).unwrap();
+        let joint = world.get::<DistanceJoint>(dockings[&player]).unwrap();
+        assert_eq!((joint.entity1, joint.entity2), (player, nest));
+        for creature in creatures.map(|creature| entity_map[&creature]) {
+            assert_eq!(world.get::<SpawnedFrom>(creature).unwrap().0, nest);
+            assert_eq!(world.get::<Target>(creature).unwrap().0, player);
+            assert!(
+                matches!(world.get::<Waypoint>(creature), Some(Waypoint::Entity(e)) if *e == nest)
+            );
+        }
+    }
+}
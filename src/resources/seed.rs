@@ -0,0 +1,69 @@
+use bevy::prelude::*;
+use bevy_turborand::prelude::*;
+use petgraph::graph::NodeIndex;
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+/// Seed that all world generation is derived from. It is saved with the world so that chunks
+/// which have not been generated yet still generate the same way after loading.
+#[derive(Resource, Reflect, Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[reflect(Resource, Serialize, Deserialize)]
+pub struct WorldSeed(pub u64);
+
+impl WorldSeed {
+    /// Pick a random seed
+    pub fn random() -> Self {
+        Self(rand::random())
+    }
+
+    /// Read a seed typed by the player. Numbers are used as-is, and any other text is hashed.
+    pub fn from_text(text: &str) -> Self {
+        let text = text.trim();
+        text.parse()
+            .map(Self)
+            .unwrap_or_else(|_| Self(stable_hash(text.as_bytes())))
+    }
+
+    /// Seed for a zone of the [`Universe`]. The starting zone uses the world seed itself.
+    pub fn zone(&self, zone: NodeIndex) -> Self {
+        match zone.index() {
+            0 => *self,
+            index => Self(mix(self.0 ^ mix(index as u64))),
+        }
+    }
+
+    /// Random number generator for the contents of a chunk. It depends only on the seed and
+    /// the chunk, so a chunk generates identically regardless of the order chunks are visited.
+    pub fn chunk_rng(&self, chunk_index: ChunkIndex) -> Rng {
+        let IVec2 { x, y } = chunk_index.into();
+        let chunk = ((x as u32 as u64) << 32) | y as u32 as u64;
+        Rng::with_seed(mix(self.0 ^ mix(chunk)))
+    }
+}
+
+/// SplitMix64 finalizer, which spreads similar inputs (like neighboring chunks) far apart
+fn mix(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_rng() {
+        let seed = WorldSeed(42);
+        let roll = |chunk: (i32, i32)| seed.chunk_rng(chunk.into()).u64(..);
+        assert_eq!(roll((3, -2)), roll((3, -2)));
+        assert_ne!(roll((3, -2)), roll((-2, 3)));
+        assert_ne!(roll((0, 0)), WorldSeed(43).chunk_rng((0, 0).into()).u64(..));
+        assert_eq!(seed.zone(NodeIndex::new(0)), seed);
+        assert_ne!(seed.zone(NodeIndex::new(1)), seed.zone(NodeIndex::new(2)));
+        assert_eq!(WorldSeed::from_text(" 42 "), seed);
+        assert_eq!(WorldSeed::from_text("qz"), WorldSeed::from_text("qz"));
+    }
+}
@@ -2,6 +2,8 @@
 
 use bevy::{prelude::*, window::WindowMode};
 
+use crate::prelude::{Item, Rarity};
+
 /// The root settings asset (and resource) for fine-tuning within the
 /// application.
 #[derive(
@@ -12,6 +14,18 @@ pub struct Settings {
     pub window: Window,
     /// Controls
     pub controls: Controls,
+    /// Saving
+    pub save: SaveSettings,
+    /// Chunk streaming
+    pub chunks: ChunkSettings,
+    /// Universe generation
+    pub zones: ZoneSettings,
+    /// Items picked up by the take action
+    #[serde(default)]
+    pub loot: LootFilter,
+    /// What happens when crafts are destroyed
+    #[serde(default)]
+    pub death: DeathSettings,
 }
 
 /// Application window related settings
@@ -21,6 +35,105 @@ pub struct Window {
     pub mode: WindowMode,
 }
 
+/// Save related settings
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
+pub struct SaveSettings {
+    /// Seconds between autosaves, or `None` to disable autosaving
+    pub autosave_interval: Option<f32>,
+    /// Number of older copies kept of each save
+    pub backups: usize,
+}
+
+/// Chunk streaming related settings
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
+pub struct ChunkSettings {
+    /// Chunks within this many chunks of the player are generated, or reloaded from disk
+    pub load_radius: i32,
+    /// Chunks further than this many chunks from the player are stored on disk and despawned
+    pub unload_radius: i32,
+}
+
+/// Universe generation related settings
+#[derive(serde::Deserialize, serde::Serialize, Clone)]
+pub struct ZoneSettings {
+    /// Number of zones between the starting zone and the deepest zones
+    pub depth: usize,
+    /// Range of how many zones are generated at each depth, from a minimum to maximum
+    pub width: (usize, usize),
+}
+
+/// Which items are picked up when taking from a chest
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, Default)]
+pub struct LootFilter {
+    /// Categories of items to take, such as `weapon`. Every category is taken when empty.
+    #[serde(default)]
+    pub categories: Vec<String>,
+    /// Tags of items to take. Items with any of these tags are taken, or every item when empty.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Names of items that are never taken
+    #[serde(default)]
+    pub ignore: Vec<String>,
+    /// Items less rare than this are left behind
+    #[serde(default)]
+    pub min_rarity: Rarity,
+    /// Items worth less than this many credits for each space they take up are left behind
+    #[serde(default)]
+    pub min_value_per_size: f32,
+}
+
+impl LootFilter {
+    /// Whether an item passes the filter
+    pub fn matches(&self, item: &Item) -> bool {
+        let category = item.equipment_type_str();
+        (self.categories.is_empty() || self.categories.iter().any(|c| c == category))
+            && (self.tags.is_empty() || self.tags.iter().any(|tag| item.has_tag(tag)))
+            && !self.ignore.contains(&item.name)
+            && item.rarity >= self.min_rarity
+            && item.value_per_size() >= self.min_value_per_size
+    }
+}
+
+/// What happens when crafts, including the player, are destroyed
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+pub struct DeathSettings {
+    /// Seconds the wreck of a destroyed craft can be salvaged before it decays
+    pub wreck_lifetime: f32,
+    /// Fraction of every stack of cargo the player drops in a chest when destroyed
+    pub cargo_dropped: f32,
+    /// Fraction of credits the player drops when destroyed
+    pub credits_dropped: f32,
+    /// Items with any of these tags are never dropped
+    #[serde(default)]
+    pub keep_tags: Vec<String>,
+}
+
+impl Default for DeathSettings {
+    fn default() -> Self {
+        Self {
+            wreck_lifetime: 60f32,
+            cargo_dropped: 1f32,
+            credits_dropped: 0f32,
+            keep_tags: Vec::new(),
+        }
+    }
+}
+
+impl DeathSettings {
+    /// How many of a stack of items the player drops when destroyed
+    pub fn items_dropped(&self, item: &Item, amount: usize) -> usize {
+        if self.keep_tags.iter().any(|tag| item.has_tag(tag)) {
+            return 0;
+        }
+        (amount as f32 * self.cargo_dropped.clamp(0f32, 1f32)).round() as usize
+    }
+
+    /// How many credits the player drops when destroyed
+    pub fn credits_dropped(&self, credits: usize) -> usize {
+        (credits as f32 * self.credits_dropped.clamp(0f32, 1f32)).round() as usize
+    }
+}
+
 /// Game controls
 #[derive(serde::Deserialize, serde::Serialize, Clone)]
 pub struct Controls {
@@ -39,12 +152,112 @@ pub struct KeyboardControls {
     pub thrust: KeyCode,
     /// Brake
     pub brake: KeyCode,
-    /// Fire
+    /// Burn the afterburners while thrusting
+    pub afterburner: KeyCode,
+    /// Fire the primary weapon group
     pub fire: KeyCode,
+    /// Fire the secondary weapon group
+    pub fire_secondary: KeyCode,
     /// Toggle debug
     pub console: KeyCode,
     /// Take items
     pub take: KeyCode,
     /// Interact with items and objects
     pub interact: KeyCode,
+    /// Save to the quicksave slot
+    pub quicksave: KeyCode,
+    /// Load the quicksave slot
+    pub quickload: KeyCode,
+    /// Move a pip of power to the engines
+    pub power_engines: KeyCode,
+    /// Move a pip of power to the shields
+    pub power_shields: KeyCode,
+    /// Move a pip of power to the weapons
+    pub power_weapons: KeyCode,
+    /// Share power evenly between every system
+    pub power_balance: KeyCode,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_death_settings() {
+        let settings = DeathSettings {
+            wreck_lifetime: 1f32,
+            cargo_dropped: 0.5,
+            credits_dropped: 0.1,
+            keep_tags: vec!["keepsake".into()],
+        };
+        let scrap = Item {
+            name: "scrap".into(),
+            ..Default::default()
+        };
+        let keepsake = Item {
+            name: "locket".into(),
+            tags: vec!["keepsake".into()],
+            ..Default::default()
+        };
+        assert_eq!(settings.items_dropped(&scrap, 10), 5);
+        assert_eq!(settings.items_dropped(&keepsake, 10), 0);
+        assert_eq!(settings.credits_dropped(1000), 100);
+    }
+
+    #[test]
+    fn test_loot_filter() {
+        let scrap = Item {
+            name: "scrap".into(),
+            value: 10,
+            size: 10,
+            ..Default::default()
+        };
+        let gem = Item {
+            name: "gem".into(),
+            value: 500,
+            size: 1,
+            rarity: Rarity::Rare,
+            tags: vec!["valuable".into()],
+            ..Default::default()
+        };
+        let filter = |filter: LootFilter| [&scrap, &gem].map(|item| filter.matches(item));
+
+        assert_eq!(filter(LootFilter::default()), [true, true]);
+        assert_eq!(
+            filter(LootFilter {
+                categories: vec!["weapon".into()],
+                ..default()
+            }),
+            [false, false]
+        );
+        assert_eq!(
+            filter(LootFilter {
+                categories: vec!["item".into()],
+                tags: vec!["valuable".into()],
+                ..default()
+            }),
+            [false, true]
+        );
+        assert_eq!(
+            filter(LootFilter {
+                ignore: vec!["gem".into()],
+                ..default()
+            }),
+            [true, false]
+        );
+        assert_eq!(
+            filter(LootFilter {
+                min_rarity: Rarity::Uncommon,
+                ..default()
+            }),
+            [false, true]
+        );
+        assert_eq!(
+            filter(LootFilter {
+                min_value_per_size: 2f32,
+                ..default()
+            }),
+            [false, true]
+        );
+    }
 }
@@ -29,6 +29,12 @@ pub struct SpawnBuilding {
     pub rotation: f32,
     /// Alliegance
     pub alliegance: Alliegance,
+    /// Seed for anything rolled for the building, such as the stock of a store
+    pub seed: u64,
+    /// Danger level of the biome, which is added to the maximum of a spawner
+    pub danger: usize,
+    /// If a gate, the index of the zone it leads to
+    pub gate: Option<usize>,
 }
 
 /// Generate a new chunk
@@ -57,9 +63,18 @@ pub enum InventoryTransferSettings {
         item: Handle<Item>,
         /// Amount of this type of item to transfer
         quantity: usize,
+        /// Metadata of the items to transfer
+        metadata: ItemMetadata,
     },
     /// Transfer all of one inventory into another
     All,
+    /// Transfer as much as fits, taking the most valuable items for their size first
+    Fit,
+    /// Transfer as much as fits of items in a category, such as `weapon` (see
+    /// [`Item::equipment_type_str`])
+    Category(String),
+    /// Transfer as much as fits of items that pass a loot filter
+    Filter(settings::LootFilter),
 }
 
 /// Remove items from the inventory and create a chest in the world
@@ -71,6 +86,8 @@ pub struct TossItemOverboard {
     pub item: Handle<Item>,
     /// The amount of the selected item to throw overboard
     pub quantity: usize,
+    /// Metadata of the items to throw overboard
+    pub metadata: ItemMetadata,
 }
 
 /**
@@ -84,6 +101,8 @@ pub struct Equip {
     pub entity: Entity,
     /// The handle of the [`Item`] to equip
     pub item: Handle<Item>,
+    /// Metadata of the item to equip, such as its modifiers
+    pub metadata: ItemMetadata,
     /// If `true`, we will transfer this item out of the inventory and error if it does not exist
     pub transfer_from_inventory: bool,
 }
@@ -96,3 +115,30 @@ pub struct Unequip {
     /// If `true`, we will transfer this item back into the inventory
     pub transfer_into_inventory: bool,
 }
+
+/// Change the group and fire mode of an equipped weapon
+#[derive(Event)]
+pub struct ConfigureWeapon {
+    /// The child [`Equipment`] entity with a [`Weapon`]
+    pub weapon: Entity,
+    /// New group and fire mode
+    pub fire_control: FireControl,
+}
+
+/// Queue a recipe at a fabricator that the patron is docked with
+#[derive(Event, Clone)]
+pub struct Fabricate {
+    /// Entity that pays for the recipe and receives its output
+    pub patron: Entity,
+    /// Building with a [`Fabricator`]
+    pub fabricator: Entity,
+    /// Name of the recipe
+    pub recipe: String,
+}
+
+/// Bring a destroyed player back at its [`RespawnPoint`]
+#[derive(Event, Clone, Copy)]
+pub struct Respawn {
+    /// The destroyed player
+    pub player: Entity,
+}
@@ -0,0 +1,225 @@
+use bevy::prelude::*;
+use bevy_turborand::prelude::*;
+use petgraph::graph::{NodeIndex, UnGraph};
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+
+/// A zone of the [`Universe`]. Each zone has its own set of chunks.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Zone {
+    /// The name identifier of this zone
+    pub name: String,
+    /// The depth of this `Zone` in the [`Universe`]. This can help dictate difficulty
+    pub depth: usize,
+    /// Chunks of this zone, stored here while the player is in another zone
+    #[serde(default)]
+    pub chunks: Chunks,
+}
+
+impl Zone {
+    pub fn from_depth(depth: usize, rng: &mut Rng) -> Self {
+        let words = random_word::all(random_word::Lang::En);
+        Self {
+            name: (0..2)
+                .map(|_| words[rng.usize(..words.len())].to_string())
+                .reduce(|acc, e| acc + " " + &e)
+                .unwrap(),
+            depth,
+            chunks: Chunks::default(),
+        }
+    }
+}
+
+/// Every [`Zone`], connected by jump gates. The universe is generated with a new game and saved
+/// with the world.
+#[derive(Resource, Clone, Reflect, Serialize, Deserialize)]
+#[serde(from = "UniverseData", into = "UniverseData")]
+#[reflect_value(Resource, Serialize, Deserialize)]
+pub struct Universe {
+    /// Zones, with an edge for each gate between two zones
+    graph: UnGraph<Zone, ()>,
+    /// The zone the player is in
+    current: NodeIndex,
+}
+
+impl Default for Universe {
+    /// A universe with a single zone
+    fn default() -> Self {
+        let mut graph = UnGraph::default();
+        let current = graph.add_node(Zone {
+            name: "origin".into(),
+            depth: 0,
+            chunks: Chunks::default(),
+        });
+        Self { graph, current }
+    }
+}
+
+impl Universe {
+    /// Generate a universe with a depth of `depth` zones beyond the starting zone. Every depth
+    /// after the first has a number of zones in the `width` range, and each zone has a gate to at
+    /// least one zone of the previous depth.
+    pub fn generate(depth: usize, width: (usize, usize), rng: &mut Rng) -> Self {
+        let mut graph = UnGraph::default();
+        let mut previous = vec![graph.add_node(Zone::from_depth(0, rng))];
+        let current = previous[0];
+        // Settings may give the range backwards
+        let (min, max) = (width.0.min(width.1).max(1), width.0.max(width.1).max(1));
+
+        for depth in 1..=depth {
+            let zones = (0..rng.usize(min..=max))
+                .map(|_| graph.add_node(Zone::from_depth(depth, rng)))
+                .collect::<Vec<_>>();
+            for zone in zones.iter() {
+                let from = previous[rng.usize(..previous.len())];
+                graph.update_edge(from, *zone, ());
+                // Some zones have a second way in
+                if rng.chance(0.3) {
+                    let from = previous[rng.usize(..previous.len())];
+                    graph.update_edge(from, *zone, ());
+                }
+            }
+            previous = zones;
+        }
+
+        Self { graph, current }
+    }
+
+    /// The zone the player is in
+    pub fn current(&self) -> NodeIndex {
+        self.current
+    }
+
+    /// Get a zone
+    pub fn zone(&self, zone: NodeIndex) -> Option<&Zone> {
+        self.graph.node_weight(zone)
+    }
+
+    /// The zone the player is in
+    pub fn current_zone(&self) -> &Zone {
+        &self.graph[self.current]
+    }
+
+    /// Zones reachable through the gates of a zone, in a stable order
+    pub fn gates(&self, zone: NodeIndex) -> Vec<NodeIndex> {
+        let mut gates = self.graph.neighbors(zone).collect::<Vec<_>>();
+        gates.sort();
+        gates
+    }
+
+    /// Number of zones
+    pub fn len(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.graph.node_count() == 0
+    }
+
+    /// Move the player to another zone. The chunks of the zone being left are stored with that
+    /// zone, and the chunks of the destination are returned.
+    pub fn travel(&mut self, destination: NodeIndex, chunks: Chunks) -> Chunks {
+        self.graph[self.current].chunks = chunks;
+        self.current = destination;
+        std::mem::take(&mut self.graph[destination].chunks)
+    }
+}
+
+/// How a [`Universe`] is saved, which does not depend on how `petgraph` serializes graphs
+#[derive(Serialize, Deserialize)]
+struct UniverseData {
+    zones: Vec<Zone>,
+    gates: Vec<(usize, usize)>,
+    current: usize,
+}
+
+impl From<Universe> for UniverseData {
+    fn from(universe: Universe) -> Self {
+        let gates = universe
+            .graph
+            .edge_indices()
+            .filter_map(|edge| universe.graph.edge_endpoints(edge))
+            .map(|(a, b)| (a.index(), b.index()))
+            .collect();
+        let (zones, _) = universe.graph.into_nodes_edges();
+        Self {
+            zones: zones.into_iter().map(|node| node.weight).collect(),
+            gates,
+            current: universe.current.index(),
+        }
+    }
+}
+
+impl From<UniverseData> for Universe {
+    fn from(data: UniverseData) -> Self {
+        let mut graph = UnGraph::default();
+        let nodes = data
+            .zones
+            .into_iter()
+            .map(|zone| graph.add_node(zone))
+            .collect::<Vec<_>>();
+        let node = |index: usize| nodes.get(index).copied();
+        for (a, b) in data.gates {
+            if let (Some(a), Some(b)) = (node(a), node(b)) {
+                graph.update_edge(a, b, ());
+            }
+        }
+        Self {
+            graph,
+            current: node(data.current).unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::utils::HashMap;
+
+    #[test]
+    fn test_universe() {
+        let universe = Universe::generate(4, (1, 3), &mut Rng::with_seed(3));
+        assert_eq!(universe.current_zone().depth, 0);
+
+        // Every zone can be reached from the starting zone
+        let mut depths = HashMap::new();
+        let mut visit = vec![universe.current()];
+        while let Some(zone) = visit.pop() {
+            if depths
+                .insert(zone, universe.zone(zone).unwrap().depth)
+                .is_none()
+            {
+                visit.extend(universe.gates(zone));
+            }
+        }
+        assert_eq!(depths.len(), universe.len());
+        assert_eq!(depths.values().max(), Some(&4));
+
+        // A backwards width range is the same as the right way around
+        let backwards = Universe::generate(2, (3, 1), &mut Rng::with_seed(3));
+        assert!((3..=7).contains(&backwards.len()));
+
+        // Zones keep their chunks when travelling back and forth
+        let mut universe = universe;
+        let destination = universe.gates(universe.current())[0];
+        let mut chunks = Chunks::default();
+        chunks.insert((2, 2).into());
+        let origin = universe.current();
+        assert!(universe.travel(destination, chunks).is_empty());
+        let chunks = universe.travel(origin, Chunks::default());
+        assert!(chunks.is_generated(&(2, 2).into()));
+
+        // Saving keeps every zone and gate
+        let loaded: Universe = ron::from_str(&ron::to_string(&universe).unwrap()).unwrap();
+        assert_eq!(loaded.len(), universe.len());
+        assert_eq!(loaded.current(), universe.current());
+        for zone in depths.keys() {
+            assert_eq!(loaded.gates(*zone), universe.gates(*zone));
+            assert_eq!(
+                loaded.zone(*zone).unwrap().name,
+                universe.zone(*zone).unwrap().name
+            );
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
 
 use bevy::{prelude::*, utils::HashSet};
 use serde::{Deserialize, Serialize};
@@ -6,10 +6,15 @@ use serde::{Deserialize, Serialize};
 #[derive(Default, Resource, Deref, DerefMut, PartialEq, Eq)]
 pub struct DrawInspector(pub bool);
 
-#[derive(Resource, Default)]
+/// Tracks the state of every chunk, and is saved with the world
+#[derive(Resource, Default, Clone, Reflect, Serialize, Deserialize)]
+#[reflect(Resource, Serialize, Deserialize)]
 pub struct Chunks {
-    /// Chunks that are already loaded and spawned into the game
+    /// Chunks that are already generated
     loaded: HashSet<ChunkIndex>,
+    /// Generated chunks whose entities are stored on disk instead of spawned into the game
+    #[serde(default)]
+    unloaded: HashSet<ChunkIndex>,
 }
 
 impl Chunks {
@@ -19,14 +24,44 @@ impl Chunks {
     pub fn insert(&mut self, k: ChunkIndex) {
         self.loaded.insert(k);
     }
+    /// Number of generated chunks
+    pub fn len(&self) -> usize {
+        self.loaded.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.loaded.is_empty()
+    }
+    /// Whether the entities of a generated chunk are stored on disk
+    pub fn is_unloaded(&self, chunk_index: &ChunkIndex) -> bool {
+        self.unloaded.contains(chunk_index)
+    }
+    /// Mark a generated chunk as stored on disk, or as spawned into the game
+    pub fn set_unloaded(&mut self, chunk_index: ChunkIndex, unloaded: bool) {
+        match unloaded {
+            true => self.unloaded.insert(chunk_index),
+            false => self.unloaded.remove(&chunk_index),
+        };
+    }
 }
 
 /// Units in a chunk
 const CHUNK_SIZE: usize = 128;
-#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Reflect, Serialize, Deserialize)]
 pub struct ChunkIndex(IVec2);
 
 impl ChunkIndex {
+    /// Chunks within `radius` chunks of this one, including diagonals
+    pub fn around(&self, radius: i32) -> impl Iterator<Item = ChunkIndex> {
+        let center = self.0;
+        (-radius..=radius).flat_map(move |x| {
+            (-radius..=radius).map(move |y| ChunkIndex(center + IVec2::new(x, y)))
+        })
+    }
+    /// Number of chunks between this chunk and another, including diagonals
+    pub fn distance(&self, other: &ChunkIndex) -> i32 {
+        let delta = (self.0 - other.0).abs();
+        delta.x.max(delta.y)
+    }
     /// Get the offset of this chunk in world units
     pub fn to_world_coordinates(&self) -> Vec2 {
         let coord = self.0 * CHUNK_SIZE as i32;
@@ -43,12 +78,22 @@ impl ChunkIndex {
 #[reflect(Resource, Serialize, Deserialize)]
 pub struct SavePath(pub Option<PathBuf>);
 
+/// Total time spent playing the current save
+#[derive(Resource, Deref, DerefMut, Default, Clone, Copy)]
+pub struct PlayTime(pub Duration);
+
 impl From<IVec2> for ChunkIndex {
     fn from(value: IVec2) -> Self {
         Self(value)
     }
 }
 
+impl From<ChunkIndex> for IVec2 {
+    fn from(value: ChunkIndex) -> Self {
+        value.0
+    }
+}
+
 impl From<(i32, i32)> for ChunkIndex {
     fn from(value: (i32, i32)) -> Self {
         Self(IVec2::new(value.0, value.1))
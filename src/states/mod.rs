@@ -2,6 +2,8 @@ use std::path::PathBuf;
 
 use bevy::prelude::*;
 
+use crate::prelude::WorldSeed;
+
 // use crate::prelude::UniverseDescription;
 
 /// Controls the state of our application
@@ -18,7 +20,10 @@ pub enum AppState {
     Main,
 
     /// Create a new game and generate the world, additionally assigning a save game name
-    NewGame,
+    NewGame {
+        /// World generation seed, or `None` to pick one at random
+        seed: Option<WorldSeed>,
+    },
 
     /// Save the current universe
     SaveGame {
@@ -28,6 +33,11 @@ pub enum AppState {
     LoadGame {
         path: PathBuf,
     },
+    /// Travel through a gate, storing the current zone and loading the destination
+    Travel {
+        /// Index of the zone in the universe to travel to
+        destination: usize,
+    },
 }
 
 // Discard data so we can use the gamestate to hold relevant information
@@ -74,6 +84,11 @@ impl AppState {
     /// AppState::NewGame { .. }
     #[inline(always)]
     pub fn new_game() -> Self {
-        Self::NewGame
+        Self::NewGame { seed: None }
+    }
+    /// AppState::Travel { .. }
+    #[inline(always)]
+    pub fn travel() -> Self {
+        Self::Travel { destination: 0 }
     }
 }
@@ -77,15 +77,19 @@ impl<'a> Widget for Slot<'a> {
                                 spread,
                                 shots,
                                 damage,
+                                damage_type,
                                 radius,
                                 lifetime,
                                 distance,
                                 energy,
                                 projectile_model,
+                                blast,
+                                heat_per_shot,
                             } => {}
                             WeaponType::LaserWeapon {
                                 tracking,
                                 damage_per_second,
+                                damage_type,
                                 energy_per_second,
                                 range,
                                 width,
@@ -103,6 +107,7 @@ impl<'a> Widget for Slot<'a> {
                                 //     ..Default::default()
                                 // });
                             }
+                            WeaponType::MissileWeapon { .. } => {}
                         },
                         _ => (),
                     }
@@ -142,6 +142,8 @@ pub enum PhysicsCategory {
     Weapon,
     /// Items, like chests and floating credits
     Item,
+    /// Missiles, which can be shot down by other weapons
+    Missile,
 }
 
 /// Piped system to handle errors and show them as in-game toasts
@@ -158,6 +160,13 @@ where
 /// Numerator over denominator
 pub type Chance = (usize, usize);
 
+/// FNV-1a hash, which unlike the std hashers is guaranteed to be the same across builds
+pub fn stable_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
 pub trait Builder: Clone + Component {
     type Output: Bundle;
     fn on_add(mut world: DeferredWorld, entity: Entity, _component_id: ComponentId) {